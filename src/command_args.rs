@@ -0,0 +1,114 @@
+use std::collections::{HashMap, HashSet};
+
+/// Slash command arguments split into positionals, `--flag value` options and
+/// bare `--switch`es. Zed hands us whitespace-separated words, so the words are
+/// re-joined and split again honouring double quotes (`--title "My App"`).
+#[derive(Debug, Clone, Default)]
+pub struct CommandArgs {
+    positional: Vec<String>,
+    values: HashMap<String, String>,
+    switches: HashSet<String>,
+}
+
+impl CommandArgs {
+    pub fn parse(args: &[String], value_flags: &[&str]) -> Result<Self, String> {
+        let mut parsed = CommandArgs::default();
+        let mut tokens = split_quoted(&args.join(" ")).into_iter();
+
+        while let Some(token) = tokens.next() {
+            match token.strip_prefix("--") {
+                Some(flag) if !flag.is_empty() => {
+                    if value_flags.contains(&flag) {
+                        let value = tokens
+                            .next()
+                            .ok_or_else(|| format!("--{} requires a value", flag))?;
+                        parsed.values.insert(flag.to_string(), value);
+                    } else {
+                        parsed.switches.insert(flag.to_string());
+                    }
+                }
+                _ => parsed.positional.push(token),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(|s| s.as_str())
+    }
+
+    pub fn value(&self, flag: &str) -> Option<&str> {
+        self.values.get(flag).map(|s| s.as_str())
+    }
+
+    pub fn has(&self, flag: &str) -> bool {
+        self.switches.contains(flag)
+    }
+}
+
+fn split_quoted(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    tokens.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+
+    if has_token {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(input: &str) -> Vec<String> {
+        input.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_flags_and_positionals() {
+        let args = CommandArgs::parse(
+            &words(r#"my_app --title "My App" --force --email a@b.c"#),
+            &["title", "email"],
+        )
+        .unwrap();
+
+        assert_eq!(args.positional(), &["my_app".to_string()]);
+        assert_eq!(args.value("title"), Some("My App"));
+        assert_eq!(args.value("email"), Some("a@b.c"));
+        assert!(args.has("force"));
+        assert!(!args.has("title"));
+    }
+
+    #[test]
+    fn test_missing_flag_value() {
+        let result = CommandArgs::parse(&words("my_app --title"), &["title"]);
+        assert!(result.is_err());
+    }
+}
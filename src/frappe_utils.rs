@@ -1,8 +1,13 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::net::ToSocketAddrs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrappeApp {
@@ -13,6 +18,9 @@ pub struct FrappeApp {
     pub doctypes: Vec<DocTypeInfo>,
     pub pages: Vec<PageInfo>,
     pub reports: Vec<ReportInfo>,
+    /// False for apps pip-installed into the bench's virtualenv rather than
+    /// checked out under `apps/`; those are read-only.
+    pub editable: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +33,16 @@ pub struct DocTypeInfo {
     pub fields: Vec<FieldInfo>,
     pub permissions: Vec<PermissionInfo>,
     pub links: Vec<LinkInfo>,
+    #[serde(default)]
+    pub states: Vec<DocTypeState>,
+}
+
+/// An entry of a DocType's `states` table: the indicator shown for a value of
+/// its status field in list and form views.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocTypeState {
+    pub title: String,
+    pub color: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +62,27 @@ pub struct PermissionInfo {
     pub write: Option<i32>,
     pub create: Option<i32>,
     pub delete: Option<i32>,
+    pub submit: Option<i32>,
+}
+
+/// How many DocTypes grant a role each right, counting a DocType once per
+/// right however many permission rows it has for the role.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RolePermissions {
+    pub role: String,
+    pub read: usize,
+    pub write: usize,
+    pub create: usize,
+    pub delete: usize,
+    pub submit: usize,
+}
+
+impl RolePermissions {
+    /// Write access to DocTypes the role can't read anywhere is almost always
+    /// a misconfigured permission row.
+    pub fn writes_without_read(&self) -> bool {
+        self.write > 0 && self.read == 0
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +90,10 @@ pub struct LinkInfo {
     pub source_field: String,
     pub target_doctype: String,
     pub link_type: String,
+    /// Table and Table MultiSelect fields, whose target is a child DocType
+    /// rather than a record the parent points at.
+    #[serde(default)]
+    pub is_child_table: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +120,109 @@ pub struct FrappeProject {
     pub apps: Vec<FrappeApp>,
     pub sites: Vec<SiteInfo>,
     pub default_site: Option<String>,
+    /// Directories or entries that couldn't be read and were left out, with the reason.
+    pub skipped_paths: Vec<(PathBuf, String)>,
+    /// DocType, Page and Report JSON files that failed to load, with the reason.
+    pub parse_errors: Vec<(PathBuf, String)>,
+    pub timings: AnalysisTimings,
+}
+
+/// How long each phase of `analyze_project` took.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnalysisTimings {
+    pub apps: Duration,
+    pub sites: Duration,
+    pub default_site: Duration,
+    pub total: Duration,
+}
+
+/// Placeholders `export_doctype_template` puts in place of the DocType's own
+/// name and module.
+pub const TEMPLATE_DOCTYPE: &str = "TEMPLATE_DOCTYPE";
+pub const TEMPLATE_MODULE: &str = "TEMPLATE_MODULE";
+
+/// Files touched by `generate_workflow`.
+#[derive(Debug, Clone)]
+pub struct GeneratedWorkflow {
+    pub path: PathBuf,
+    pub doctype_path: PathBuf,
+    pub field_added: bool,
+}
+
+/// Events a Notification can fire on that need nothing beyond the DocType.
+pub const NOTIFICATION_EVENTS: &[&str] = &[
+    "New",
+    "Save",
+    "Submit",
+    "Cancel",
+    "Days After",
+    "Days Before",
+    "Value Change",
+];
+
+/// A record added to an app's `fixtures/` folder. `in_fixtures` is false
+/// when the app's hooks.py doesn't export that DocType yet.
+#[derive(Debug, Clone)]
+pub struct GeneratedFixture {
+    pub path: PathBuf,
+    pub in_fixtures: bool,
+}
+
+/// Roles Frappe creates itself, so they never show up in app fixtures.
+const STANDARD_ROLES: &[&str] = &[
+    "Administrator",
+    "All",
+    "Desk User",
+    "Guest",
+    "System Manager",
+    "Website Manager",
+];
+
+/// A string marked for translation and where it first appears.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslatableString {
+    pub text: String,
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+/// Result of `generate_web_form`; `route` is the URL path the form is served at.
+#[derive(Debug, Clone)]
+pub struct GeneratedWebForm {
+    pub files: Vec<PathBuf>,
+    pub route: String,
+    pub fields: usize,
+}
+
+/// Aggregates a Number Card can compute over its DocType.
+pub const NUMBER_CARD_FUNCTIONS: &[&str] = &["Count", "Sum", "Average", "Minimum", "Maximum"];
+
+/// A standard record exported under a module folder, e.g. a Dashboard Chart.
+#[derive(Debug, Clone)]
+struct ModuleRecord {
+    name: String,
+    module: String,
+    module_dir: PathBuf,
+}
+
+/// Result of `generate_api_method`; `endpoint` is the dotted path served at
+/// `/api/method/<endpoint>`.
+#[derive(Debug, Clone)]
+pub struct GeneratedApiMethod {
+    pub path: PathBuf,
+    pub endpoint: String,
+    pub created: bool,
+}
+
+/// Project-wide totals; `modules` holds the ten modules with the most DocTypes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectStats {
+    pub apps: usize,
+    pub doctypes: usize,
+    pub pages: usize,
+    pub reports: usize,
+    pub fields: usize,
+    pub modules: Vec<(String, usize)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,13 +233,237 @@ pub struct SiteInfo {
     pub database: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookIssue {
+    pub hook: String,
+    pub reference: String,
+    pub line: usize,
+    pub message: String,
+}
+
+/// A modeling problem found in a DocType's JSON. Errors break `bench
+/// migrate`; warnings are performance hints.
+#[derive(Debug, Clone)]
+pub struct DocTypeIssue {
+    pub doctype: String,
+    pub path: PathBuf,
+    pub error: bool,
+    pub message: String,
+}
+
+/// DocTypes with more Link fields than this get a performance hint.
+pub const MAX_LINK_FIELDS: usize = 20;
+
+/// A function registered under `scheduler_events` in hooks.py. Cron jobs
+/// carry their crontab expression in `cron`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledJob {
+    pub frequency: String,
+    pub cron: Option<String>,
+    pub method: String,
+}
+
+/// One service from the bench `Procfile`, e.g. `web: bench serve --port 8000`.
+/// `port` is only known when the command passes `--port`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcEntry {
+    pub name: String,
+    pub command: String,
+    pub port: Option<u16>,
+}
+
+impl ProcEntry {
+    /// Services `bench start --no-dev` leaves out.
+    pub fn dev_only(&self) -> bool {
+        self.name == "watch"
+    }
+}
+
+/// A service the bench depends on, with where it should be listening.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceEndpoint {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+}
+
+/// A leftover build artifact and its size on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleFile {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// A file in the bench's `logs` directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFile {
+    pub name: String,
+    pub size: u64,
+}
+
+/// A quoted string found inside a hooks.py assignment, with its 1-based line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookReference {
+    pub value: String,
+    pub line: usize,
+}
+
+/// One regex hit from `search_text`; `line` and `column` are 1-based, the
+/// column counted in bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMatch {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+/// A field present in both DocTypes whose definition differs. Each entry in
+/// `differences` reads like "fieldtype: Data → Link".
+#[derive(Debug, Clone)]
+pub struct FieldDifference {
+    pub fieldname: String,
+    pub differences: Vec<String>,
+}
+
+/// Result of `duplicate_doctype`. `self_references` are fields whose Link or
+/// Table options still name the source DocType.
+#[derive(Debug, Clone)]
+pub struct DuplicatedDocType {
+    pub json: serde_json::Value,
+    pub source: String,
+    pub self_references: Vec<String>,
+}
+
+/// Keys that differ between two site configs, each list sorted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SiteConfigDiff {
+    pub only_left: Vec<String>,
+    pub only_right: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl SiteConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Field-level changes to one DocType since the last commit.
+#[derive(Debug, Clone)]
+pub struct DocTypeChanges {
+    pub doctype: String,
+    pub path: PathBuf,
+    pub added: Vec<FieldInfo>,
+    pub removed: Vec<FieldInfo>,
+    pub modified: Vec<FieldDifference>,
+}
+
+impl DocTypeChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DocTypeDiff {
+    pub left: String,
+    pub right: String,
+    pub only_left: Vec<FieldInfo>,
+    pub only_right: Vec<FieldInfo>,
+    pub changed_fields: Vec<FieldDifference>,
+    pub only_left_roles: Vec<String>,
+    pub only_right_roles: Vec<String>,
+    pub changed_roles: Vec<FieldDifference>,
+}
+
+impl DocTypeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty()
+            && self.only_right.is_empty()
+            && self.changed_fields.is_empty()
+            && self.only_left_roles.is_empty()
+            && self.only_right_roles.is_empty()
+            && self.changed_roles.is_empty()
+    }
+}
+
+/// One file touched by a DocType rename. `renamed_to` is the final location
+/// when the file moves; `content` is the rewritten text when it changes.
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub renamed_to: Option<PathBuf>,
+    pub content: Option<String>,
+}
+
+/// Every edit needed to rename a DocType, computed up front so nothing is
+/// written until the whole rename is known to be possible.
+#[derive(Debug, Clone)]
+pub struct RenamePlan {
+    pub old_name: String,
+    pub new_name: String,
+    pub old_dir: PathBuf,
+    pub new_dir: PathBuf,
+    pub changes: Vec<FileChange>,
+}
+
+/// Standalone script records that can be exported into an app's module.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptKind {
+    Server,
+    Client,
+}
+
+impl ScriptKind {
+    pub fn doctype(&self) -> &'static str {
+        match self {
+            ScriptKind::Server => "Server Script",
+            ScriptKind::Client => "Client Script",
+        }
+    }
+}
+
+/// Standard report flavours `generate_report` can scaffold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportKind {
+    Query,
+    Script,
+}
+
+impl ReportKind {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "query" => Ok(ReportKind::Query),
+            "script" => Ok(ReportKind::Script),
+            _ => Err(format!(
+                "Unknown report type '{}' (expected query or script)",
+                name
+            )),
+        }
+    }
+
+    pub fn report_type(&self) -> &'static str {
+        match self {
+            ReportKind::Query => "Query Report",
+            ReportKind::Script => "Script Report",
+        }
+    }
+}
+
 pub struct FrappeAnalyzer {
     project: Option<FrappeProject>,
+    skipped_paths: Mutex<Vec<(PathBuf, String)>>,
+    parse_errors: Mutex<Vec<(PathBuf, String)>>,
 }
 
 impl FrappeAnalyzer {
     pub fn new() -> Self {
-        Self { project: None }
+        Self {
+            project: None,
+            skipped_paths: Mutex::new(Vec::new()),
+            parse_errors: Mutex::new(Vec::new()),
+        }
     }
 
     pub fn analyze_project(&mut self, workspace_path: &Path) -> Result<(), String> {
@@ -101,21 +471,212 @@ impl FrappeAnalyzer {
             return Err("Not a valid Frappe workspace".to_string());
         }
 
+        let started = Instant::now();
         let bench_path = workspace_path.to_path_buf();
+        self.skipped_paths.lock().unwrap().clear();
+        self.parse_errors.lock().unwrap().clear();
         let apps = self.discover_apps(&bench_path)?;
+        let apps_done = Instant::now();
         let sites = self.discover_sites(&bench_path)?;
+        let sites_done = Instant::now();
         let default_site = self.get_default_site(&bench_path)?;
+        let finished = Instant::now();
 
+        let mut skipped_paths = std::mem::take(&mut *self.skipped_paths.lock().unwrap());
+        skipped_paths.sort();
+        skipped_paths.dedup_by(|a, b| a.0 == b.0);
+        let mut parse_errors = std::mem::take(&mut *self.parse_errors.lock().unwrap());
+        parse_errors.sort();
         self.project = Some(FrappeProject {
             bench_path,
             apps,
             sites,
             default_site,
+            skipped_paths,
+            parse_errors,
+            timings: AnalysisTimings {
+                apps: apps_done - started,
+                sites: sites_done - apps_done,
+                default_site: finished - sites_done,
+                total: finished - started,
+            },
         });
 
         Ok(())
     }
 
+    /// Services from `<bench>/Procfile` in file order; empty when there is none.
+    pub fn parse_procfile(&self, bench_path: &Path) -> Vec<ProcEntry> {
+        let Ok(content) = fs::read_to_string(bench_path.join("Procfile")) else {
+            return Vec::new();
+        };
+        let port_re = Regex::new(r"--port[ =](\d+)").unwrap();
+
+        content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, command)| {
+                let command = command.trim().to_string();
+                ProcEntry {
+                    name: name.trim().to_string(),
+                    port: port_re
+                        .captures(&command)
+                        .and_then(|captures| captures[1].parse().ok()),
+                    command,
+                }
+            })
+            .collect()
+    }
+
+    /// Ports the bench is configured to use: `--port` in the Procfile plus the
+    /// `*_port` and `redis_*` entries of common_site_config.json.
+    pub fn bench_ports(&self, bench_path: &Path) -> Vec<u16> {
+        let mut ports: Vec<u16> = self
+            .parse_procfile(bench_path)
+            .iter()
+            .filter_map(|entry| entry.port)
+            .collect();
+
+        let common_config = bench_path.join("sites").join("common_site_config.json");
+        if let Ok(config) = self.read_site_config(&common_config) {
+            let url_port = Regex::new(r":(\d+)/?$").unwrap();
+            for (key, value) in &config {
+                let port = if key.ends_with("_port") {
+                    value.as_u64().and_then(|p| u16::try_from(p).ok())
+                } else if key.starts_with("redis_") {
+                    value
+                        .as_str()
+                        .and_then(|url| url_port.captures(url))
+                        .and_then(|captures| captures[1].parse().ok())
+                } else {
+                    None
+                };
+                ports.extend(port);
+            }
+        }
+
+        ports.sort();
+        ports.dedup();
+        ports
+    }
+
+    /// Redis, MariaDB and the web server as configured in
+    /// common_site_config.json, with the Procfile's `web` port taking
+    /// precedence and Frappe's defaults filling the gaps.
+    pub fn service_endpoints(&self, bench_path: &Path) -> Vec<ServiceEndpoint> {
+        let config = self
+            .read_site_config(&bench_path.join("sites").join("common_site_config.json"))
+            .unwrap_or_default();
+        let endpoint = |name: &str, host: &str, port: u16| ServiceEndpoint {
+            name: name.to_string(),
+            host: host.to_string(),
+            port,
+        };
+        let config_port = |key: &str| {
+            config
+                .get(key)
+                .and_then(|v| v.as_u64())
+                .and_then(|p| u16::try_from(p).ok())
+        };
+
+        let redis_url = Regex::new(r"^redis://(?:[^@/]*@)?([^:/]+):(\d+)").unwrap();
+        let mut endpoints: Vec<ServiceEndpoint> = ["redis_cache", "redis_queue", "redis_socketio"]
+            .iter()
+            .filter_map(|key| {
+                let captures = redis_url.captures(config.get(*key)?.as_str()?)?;
+                Some(endpoint(key, &captures[1], captures[2].parse().ok()?))
+            })
+            .collect();
+
+        let db_host = config
+            .get("db_host")
+            .and_then(|v| v.as_str())
+            .unwrap_or("127.0.0.1");
+        endpoints.push(endpoint(
+            "mariadb",
+            db_host,
+            config_port("db_port").unwrap_or(3306),
+        ));
+
+        endpoints.push(endpoint("web", "127.0.0.1", self.web_port(bench_path)));
+
+        endpoints
+    }
+
+    /// The dev server's port: the Procfile's `web` entry, then
+    /// `webserver_port` in common_site_config.json, then Frappe's 8000.
+    pub fn web_port(&self, bench_path: &Path) -> u16 {
+        self.parse_procfile(bench_path)
+            .iter()
+            .find(|entry| entry.name == "web")
+            .and_then(|entry| entry.port)
+            .or_else(|| {
+                self.read_site_config(&bench_path.join("sites").join("common_site_config.json"))
+                    .ok()?
+                    .get("webserver_port")?
+                    .as_u64()
+                    .and_then(|p| u16::try_from(p).ok())
+            })
+            .unwrap_or(8000)
+    }
+
+    /// Browser URL for the dev server. Frappe picks the site from the Host
+    /// header, so the default site's name is used when it resolves (e.g.
+    /// `*.localhost` or an /etc/hosts entry); otherwise `localhost`.
+    pub fn site_url(&self, bench_path: &Path, default_site: Option<&str>) -> String {
+        let port = self.web_port(bench_path);
+        let host = default_site
+            .filter(|site| {
+                (*site, port)
+                    .to_socket_addrs()
+                    .is_ok_and(|mut a| a.next().is_some())
+            })
+            .unwrap_or("localhost");
+        format!("http://{}:{}", host, port)
+    }
+
+    /// Files in `<bench>/logs`, where Frappe writes its own persistent logs,
+    /// with their sizes in bytes, sorted by name.
+    pub fn bench_logs(&self, bench_path: &Path) -> Result<Vec<LogFile>, String> {
+        let logs_dir = bench_path.join("logs");
+        let entries = fs::read_dir(&logs_dir)
+            .map_err(|_| format!("Could not read {}", logs_dir.display()))?;
+
+        let mut logs: Vec<LogFile> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                metadata.is_file().then(|| LogFile {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    size: metadata.len(),
+                })
+            })
+            .collect();
+        logs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(logs)
+    }
+
+    /// The last `count` lines of `<bench>/logs/<name>`. `name` must be a
+    /// plain file name so it can't reach outside the logs directory.
+    pub fn tail_bench_log(
+        &self,
+        bench_path: &Path,
+        name: &str,
+        count: usize,
+    ) -> Result<Vec<String>, String> {
+        let is_plain_name = Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name);
+        if !is_plain_name || name.starts_with('.') {
+            return Err(format!("Invalid log file name: {}", name));
+        }
+        let path = bench_path.join("logs").join(name);
+        if !path.is_file() {
+            return Err(format!("Log file not found: {}", path.display()));
+        }
+        tail_lines(&path, count)
+    }
+
     pub fn is_frappe_workspace(&self, path: &Path) -> bool {
         let apps_txt = path.join("apps.txt");
         let sites_dir = path.join("sites");
@@ -126,31 +687,275 @@ impl FrappeAnalyzer {
             && (procfile.exists() || path.join("bench-repo").exists())
     }
 
-    pub fn discover_apps(&self, bench_path: &Path) -> Result<Vec<FrappeApp>, String> {
+    pub fn read_apps_txt(&self, bench_path: &Path) -> Result<Vec<String>, String> {
         let apps_txt_path = bench_path.join("apps.txt");
         let apps_content =
             fs::read_to_string(apps_txt_path).map_err(|_| "Could not read apps.txt".to_string())?;
 
-        let mut apps = Vec::new();
-        let apps_dir = bench_path.join("apps");
+        Ok(apps_content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|app_name| !app_name.is_empty() && !app_name.starts_with('#'))
+            .map(|app_name| app_name.to_string())
+            .collect())
+    }
+
+    /// Apps active on `site`: its own `sites/<site>/apps.txt` when it has one,
+    /// otherwise the bench-wide `apps.txt`.
+    pub fn site_installed_apps(&self, bench_path: &Path, site: &str) -> Vec<String> {
+        self.read_apps_txt(&bench_path.join("sites").join(site))
+            .or_else(|_| self.read_apps_txt(bench_path))
+            .unwrap_or_default()
+    }
+
+    /// `__version__` from `<app>/<app>/__init__.py`, without analyzing the app.
+    pub fn read_app_version(&self, app_path: &Path, app_name: &str) -> Option<String> {
+        let content = fs::read_to_string(app_path.join(app_name).join("__init__.py")).ok()?;
+        let version_re = Regex::new(r#"(?m)^__version__\s*=\s*["']([^"']+)["']"#).ok()?;
+        version_re
+            .captures(&content)
+            .map(|captures| captures[1].to_string())
+    }
+
+    /// Entries of `<app>/<app>/patches.txt` in run order, minus section headers.
+    pub fn read_patches(&self, app_path: &Path, app_name: &str) -> Vec<String> {
+        fs::read_to_string(app_path.join(app_name).join("patches.txt"))
+            .map(|content| {
+                content
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| {
+                        !line.is_empty() && !line.starts_with('#') && !line.starts_with('[')
+                    })
+                    .map(|line| line.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Package directories under `<app>/<app>` that Python can't import
+    /// because they have no `__init__.py`: the package itself, its modules,
+    /// and their doctype/page/report folders and entries.
+    pub fn missing_init_files(&self, app_path: &Path, app_name: &str) -> Vec<PathBuf> {
+        let package = app_path.join(app_name);
+        let subdirs = |dir: &Path| -> Vec<PathBuf> {
+            let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| entry.ok())
+                        .map(|entry| entry.path())
+                        .filter(|path| {
+                            path.is_dir()
+                                && path
+                                    .file_name()
+                                    .map(|name| {
+                                        let name = name.to_string_lossy();
+                                        !name.starts_with('.') && !name.starts_with("__")
+                                    })
+                                    .unwrap_or(false)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            dirs.sort();
+            dirs
+        };
+
+        let kinds = ["doctype", "page", "report"];
+        let mut packages = vec![package.clone()];
+        for module_dir in subdirs(&package) {
+            let kind_dirs: Vec<PathBuf> = kinds
+                .iter()
+                .map(|kind| module_dir.join(kind))
+                .filter(|dir| dir.is_dir())
+                .collect();
+            // Only folders holding DocTypes, Pages or Reports are Frappe modules
+            if kind_dirs.is_empty() {
+                continue;
+            }
+            packages.push(module_dir);
+            for kind_dir in kind_dirs {
+                packages.extend(subdirs(&kind_dir));
+                packages.push(kind_dir);
+            }
+        }
 
-        for line in apps_content.lines() {
-            let app_name = line.trim();
-            if app_name.is_empty() || app_name.starts_with('#') {
+        let mut missing: Vec<PathBuf> = packages
+            .into_iter()
+            .filter(|dir| !dir.join("__init__.py").is_file())
+            .collect();
+        missing.sort();
+        missing
+    }
+
+    /// `.pyc` files under `app_path` whose `.py` source is gone. Python still
+    /// imports a sourceless `.pyc` sitting next to where the source was, so
+    /// these can resurrect deleted modules.
+    pub fn orphaned_bytecode(&self, app_path: &Path) -> Vec<StaleFile> {
+        let mut orphans = Vec::new();
+        let mut pending = vec![app_path.to_path_buf()];
+        let mut visited = HashSet::new();
+
+        while let Some(current) = pending.pop() {
+            if !first_visit(&mut visited, &current) {
+                continue;
+            }
+            let Ok(entries) = fs::read_dir(&current) else {
                 continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if path.is_dir() {
+                    if !(name.starts_with('.') || name == "node_modules") {
+                        pending.push(path);
+                    }
+                    continue;
+                }
+                if path.extension().and_then(|e| e.to_str()) != Some("pyc") {
+                    continue;
+                }
+
+                // __pycache__/<module>.cpython-311.pyc belongs to ../<module>.py
+                let source = if current.file_name().and_then(|n| n.to_str()) == Some("__pycache__")
+                {
+                    let module = name.split('.').next().unwrap_or_default();
+                    current
+                        .parent()
+                        .map(|dir| dir.join(format!("{}.py", module)))
+                } else {
+                    Some(path.with_extension("py"))
+                };
+                if source.is_some_and(|source| !source.exists()) {
+                    orphans.push(StaleFile {
+                        size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                        path,
+                    });
+                }
             }
+        }
+
+        orphans.sort_by(|a, b| a.path.cmp(&b.path));
+        orphans
+    }
 
-            let app_path = apps_dir.join(app_name);
-            if app_path.exists() {
-                if let Ok(app) = self.analyze_app(app_name, &app_path) {
-                    apps.push(app);
+    /// Built bundles listed in `sites/assets/assets.json` (and the RTL map)
+    /// that are older than their entry file in the app's `public` folder.
+    /// Only the entry file is compared, not what it imports.
+    pub fn outdated_assets(&self, bench_path: &Path) -> Vec<StaleFile> {
+        let sites_dir = bench_path.join("sites");
+        let mut sources: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        let mut outdated = Vec::new();
+
+        for manifest in ["assets.json", "assets-rtl.json"] {
+            let Ok(map) = read_json(&sites_dir.join("assets").join(manifest)) else {
+                continue;
+            };
+            for (entry, built) in map.as_object().into_iter().flatten() {
+                let Some(built) = built.as_str() else {
+                    continue;
+                };
+                // "/assets/<app>/dist/js/desk.bundle.HASH.js"
+                let Some(app) = built.trim_start_matches('/').split('/').nth(1) else {
+                    continue;
+                };
+                let built_path = sites_dir.join(built.trim_start_matches('/'));
+                let Ok(built_meta) = fs::metadata(&built_path) else {
+                    continue;
+                };
+
+                let public = sources.entry(app.to_string()).or_insert_with(|| {
+                    let mut files = Vec::new();
+                    let mut pending =
+                        vec![bench_path.join("apps").join(app).join(app).join("public")];
+                    while let Some(dir) = pending.pop() {
+                        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+                            let path = entry.path();
+                            let name = entry.file_name();
+                            if path.is_dir() {
+                                if name != "dist" && name != "node_modules" {
+                                    pending.push(path);
+                                }
+                            } else {
+                                files.push(path);
+                            }
+                        }
+                    }
+                    files
+                });
+                let newer_source = public
+                    .iter()
+                    .filter(|path| path.file_name().and_then(|n| n.to_str()) == Some(entry))
+                    .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+                    .any(|modified| {
+                        built_meta
+                            .modified()
+                            .is_ok_and(|built_modified| modified > built_modified)
+                    });
+                if newer_source {
+                    outdated.push(StaleFile {
+                        path: built_path,
+                        size: built_meta.len(),
+                    });
                 }
             }
         }
 
+        outdated.sort_by(|a, b| a.path.cmp(&b.path));
+        outdated.dedup();
+        outdated
+    }
+
+    pub fn discover_apps(&self, bench_path: &Path) -> Result<Vec<FrappeApp>, String> {
+        let apps_dir = bench_path.join("apps");
+        let app_names = self.read_apps_txt(bench_path)?;
+
+        // Apps are independent and scanning is I/O bound, so analyze them in parallel
+        let mut apps: Vec<FrappeApp> = std::thread::scope(|scope| {
+            let handles: Vec<_> = app_names
+                .iter()
+                .map(|app_name| {
+                    let app_path = resolve_symlink(apps_dir.join(app_name));
+                    scope.spawn(move || {
+                        if app_path.exists() {
+                            self.analyze_app(app_name, &app_path).ok()
+                        } else {
+                            // Production benches may pip-install apps into env/
+                            let site_packages = self.find_installed_app(bench_path, app_name)?;
+                            let mut app = self.analyze_app(app_name, &site_packages).ok()?;
+                            app.editable = false;
+                            Some(app)
+                        }
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok().flatten())
+                .collect()
+        });
+
+        apps.sort_by(|a, b| a.name.cmp(&b.name));
         Ok(apps)
     }
 
+    /// The `site-packages` directory of the bench's virtualenv that holds
+    /// package `name`, for apps installed without a checkout in `apps/`.
+    pub fn find_installed_app(&self, bench_path: &Path, name: &str) -> Option<PathBuf> {
+        let mut pythons: Vec<PathBuf> = fs::read_dir(bench_path.join("env").join("lib"))
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("python"))
+            .map(|entry| entry.path().join("site-packages"))
+            .collect();
+        pythons.sort();
+
+        pythons
+            .into_iter()
+            .find(|site_packages| site_packages.join(name).join("hooks.py").is_file())
+    }
+
     pub fn analyze_app(&self, name: &str, path: &Path) -> Result<FrappeApp, String> {
         let module_path = path.join(name);
         let hooks_path = path.join(name).join("hooks.py");
@@ -167,16 +972,64 @@ impl FrappeAnalyzer {
             doctypes,
             pages,
             reports,
+            editable: true,
         })
     }
 
+    /// Entries of `dir`. An unreadable directory or entry is recorded in
+    /// `skipped_paths` instead of failing the whole walk.
+    fn list_dir(&self, dir: &Path) -> Vec<PathBuf> {
+        let skip = |path: &Path, error: std::io::Error| {
+            self.skipped_paths
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), error.to_string()));
+        };
+
+        match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| match entry {
+                    Ok(entry) => Some(entry.path()),
+                    Err(e) => {
+                        skip(dir, e);
+                        None
+                    }
+                })
+                .collect(),
+            Err(e) => {
+                skip(dir, e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Records why the metadata folder `dir` couldn't be loaded, pointing at
+    /// its JSON when there is one. `__pycache__` and hidden folders aren't
+    /// metadata and are ignored.
+    fn note_parse_error(&self, dir: &Path, error: String) {
+        let Some(name) = dir.file_name().and_then(|n| n.to_str()) else {
+            return;
+        };
+        if name.starts_with("__") || name.starts_with('.') {
+            return;
+        }
+        let json_file = dir.join(format!("{}.json", name));
+        let path = if json_file.is_file() {
+            json_file
+        } else {
+            dir.to_path_buf()
+        };
+        self.parse_errors.lock().unwrap().push((path, error));
+    }
+
     pub fn discover_doctypes(&self, module_path: &Path) -> Result<Vec<DocTypeInfo>, String> {
         let mut doctypes = Vec::new();
 
-        for entry in fs::read_dir(module_path).map_err(|_| "Could not read module directory")? {
-            let entry = entry.map_err(|_| "Invalid directory entry")?;
-            let path = entry.path();
+        if !module_path.is_dir() {
+            return Err("Could not read module directory".to_string());
+        }
 
+        for path in self.list_dir(module_path) {
             if path.is_dir() {
                 let doctype_dir = path.join("doctype");
                 if doctype_dir.exists() {
@@ -193,10 +1046,7 @@ impl FrappeAnalyzer {
     pub fn scan_doctype_directory(&self, doctype_dir: &Path) -> Result<Vec<DocTypeInfo>, String> {
         let mut doctypes = Vec::new();
 
-        for entry in fs::read_dir(doctype_dir).map_err(|_| "Could not read doctype directory")? {
-            let entry = entry.map_err(|_| "Invalid directory entry")?;
-            let path = entry.path();
-
+        for path in self.list_dir(doctype_dir) {
             if path.is_dir() {
                 let doctype_name = path
                     .file_name()
@@ -204,8 +1054,9 @@ impl FrappeAnalyzer {
                     .unwrap_or("")
                     .to_string();
 
-                if let Ok(doctype_info) = self.parse_doctype(&path, &doctype_name) {
-                    doctypes.push(doctype_info);
+                match self.parse_doctype(&path, &doctype_name) {
+                    Ok(doctype_info) => doctypes.push(doctype_info),
+                    Err(e) => self.note_parse_error(&path, e),
                 }
             }
         }
@@ -214,17 +1065,19 @@ impl FrappeAnalyzer {
     }
 
     pub fn parse_doctype(&self, doctype_path: &Path, name: &str) -> Result<DocTypeInfo, String> {
-        let json_file =
+        let mut json_file =
             doctype_path.join(format!("{}.json", name.to_lowercase().replace(" ", "_")));
 
         if !json_file.exists() {
-            return Err(format!("DocType JSON not found: {}", json_file.display()));
+            json_file = self
+                .find_doctype_json(doctype_path)
+                .ok_or_else(|| format!("DocType JSON not found: {}", json_file.display()))?;
         }
 
-        let content = fs::read_to_string(&json_file).map_err(|_| "Could not read DocType JSON")?;
+        let content = read_json_text(&json_file)?;
 
         let json_value: serde_json::Value =
-            serde_json::from_str(&content).map_err(|_| "Invalid JSON format")?;
+            serde_json::from_str(&content).map_err(|e| format!("Invalid JSON format: {}", e))?;
 
         let module = json_value
             .get("module")
@@ -235,8 +1088,15 @@ impl FrappeAnalyzer {
         let fields = self.parse_fields(&json_value)?;
         let permissions = self.parse_permissions(&json_value)?;
         let links = self.analyze_doctype_links(&fields);
+        let states = parse_states(&json_value);
 
-        let controller_name = name.to_lowercase().replace(" ", "_");
+        // Controllers sit next to the JSON and share its stem, even when that
+        // doesn't match the folder name.
+        let controller_name = json_file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(name)
+            .to_string();
         let controller_path = doctype_path.join(format!("{}.py", controller_name));
         let client_script_path = doctype_path.join(format!("{}.js", controller_name));
 
@@ -257,9 +1117,39 @@ impl FrappeAnalyzer {
             fields,
             permissions,
             links,
+            states,
         })
     }
 
+    /// Fallback for folders whose JSON filename doesn't match the folder name:
+    /// picks the only `*.json` in the directory whose `doctype` key is "DocType".
+    fn find_doctype_json(&self, doctype_path: &Path) -> Option<PathBuf> {
+        let mut candidates = Vec::new();
+
+        for entry in fs::read_dir(doctype_path).ok()?.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let is_doctype = read_json_text(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+                .map(|value| value.get("doctype").and_then(|v| v.as_str()) == Some("DocType"))
+                .unwrap_or(false);
+
+            if is_doctype {
+                candidates.push(path);
+            }
+        }
+
+        if candidates.len() == 1 {
+            candidates.pop()
+        } else {
+            None
+        }
+    }
+
     pub fn parse_fields(&self, json_value: &serde_json::Value) -> Result<Vec<FieldInfo>, String> {
         let fields_array = json_value
             .get("fields")
@@ -366,6 +1256,10 @@ impl FrappeAnalyzer {
             .get("delete")
             .and_then(|v| v.as_i64())
             .map(|n| n as i32);
+        let submit = perm_val
+            .get("submit")
+            .and_then(|v| v.as_i64())
+            .map(|n| n as i32);
 
         Ok(PermissionInfo {
             role,
@@ -373,6 +1267,7 @@ impl FrappeAnalyzer {
             write,
             create,
             delete,
+            submit,
         })
     }
 
@@ -387,6 +1282,7 @@ impl FrappeAnalyzer {
                             source_field: field.fieldname.clone(),
                             target_doctype: target.clone(),
                             link_type: "Link".to_string(),
+                            is_child_table: false,
                         });
                     }
                 }
@@ -395,14 +1291,16 @@ impl FrappeAnalyzer {
                         source_field: field.fieldname.clone(),
                         target_doctype: "Dynamic".to_string(),
                         link_type: "Dynamic Link".to_string(),
+                        is_child_table: false,
                     });
                 }
-                "Table" => {
+                "Table" | "Table MultiSelect" => {
                     if let Some(target) = &field.options {
                         links.push(LinkInfo {
                             source_field: field.fieldname.clone(),
                             target_doctype: target.clone(),
-                            link_type: "Table".to_string(),
+                            link_type: field.fieldtype.clone(),
+                            is_child_table: true,
                         });
                     }
                 }
@@ -416,10 +1314,11 @@ impl FrappeAnalyzer {
     pub fn discover_pages(&self, module_path: &Path) -> Result<Vec<PageInfo>, String> {
         let mut pages = Vec::new();
 
-        for entry in fs::read_dir(module_path).map_err(|_| "Could not read module directory")? {
-            let entry = entry.map_err(|_| "Invalid directory entry")?;
-            let path = entry.path();
+        if !module_path.is_dir() {
+            return Err("Could not read module directory".to_string());
+        }
 
+        for path in self.list_dir(module_path) {
             if path.is_dir() {
                 let page_dir = path.join("page");
                 if page_dir.exists() {
@@ -436,10 +1335,7 @@ impl FrappeAnalyzer {
     pub fn scan_page_directory(&self, page_dir: &Path) -> Result<Vec<PageInfo>, String> {
         let mut pages = Vec::new();
 
-        for entry in fs::read_dir(page_dir).map_err(|_| "Could not read page directory")? {
-            let entry = entry.map_err(|_| "Invalid directory entry")?;
-            let path = entry.path();
-
+        for path in self.list_dir(page_dir) {
             if path.is_dir() {
                 let page_name = path
                     .file_name()
@@ -447,8 +1343,9 @@ impl FrappeAnalyzer {
                     .unwrap_or("")
                     .to_string();
 
-                if let Ok(page_info) = self.parse_page(&path, &page_name) {
-                    pages.push(page_info);
+                match self.parse_page(&path, &page_name) {
+                    Ok(page_info) => pages.push(page_info),
+                    Err(e) => self.note_parse_error(&path, e),
                 }
             }
         }
@@ -463,10 +1360,10 @@ impl FrappeAnalyzer {
             return Err(format!("Page JSON not found: {}", json_file.display()));
         }
 
-        let content = fs::read_to_string(&json_file).map_err(|_| "Could not read Page JSON")?;
+        let content = read_json_text(&json_file)?;
 
         let json_value: serde_json::Value =
-            serde_json::from_str(&content).map_err(|_| "Invalid JSON format")?;
+            serde_json::from_str(&content).map_err(|e| format!("Invalid JSON format: {}", e))?;
 
         let title = json_value
             .get("title")
@@ -498,10 +1395,11 @@ impl FrappeAnalyzer {
     pub fn discover_reports(&self, module_path: &Path) -> Result<Vec<ReportInfo>, String> {
         let mut reports = Vec::new();
 
-        for entry in fs::read_dir(module_path).map_err(|_| "Could not read module directory")? {
-            let entry = entry.map_err(|_| "Invalid directory entry")?;
-            let path = entry.path();
+        if !module_path.is_dir() {
+            return Err("Could not read module directory".to_string());
+        }
 
+        for path in self.list_dir(module_path) {
             if path.is_dir() {
                 let report_dir = path.join("report");
                 if report_dir.exists() {
@@ -518,10 +1416,7 @@ impl FrappeAnalyzer {
     pub fn scan_report_directory(&self, report_dir: &Path) -> Result<Vec<ReportInfo>, String> {
         let mut reports = Vec::new();
 
-        for entry in fs::read_dir(report_dir).map_err(|_| "Could not read report directory")? {
-            let entry = entry.map_err(|_| "Invalid directory entry")?;
-            let path = entry.path();
-
+        for path in self.list_dir(report_dir) {
             if path.is_dir() {
                 let report_name = path
                     .file_name()
@@ -529,8 +1424,9 @@ impl FrappeAnalyzer {
                     .unwrap_or("")
                     .to_string();
 
-                if let Ok(report_info) = self.parse_report(&path, &report_name) {
-                    reports.push(report_info);
+                match self.parse_report(&path, &report_name) {
+                    Ok(report_info) => reports.push(report_info),
+                    Err(e) => self.note_parse_error(&path, e),
                 }
             }
         }
@@ -545,10 +1441,10 @@ impl FrappeAnalyzer {
             return Err(format!("Report JSON not found: {}", json_file.display()));
         }
 
-        let content = fs::read_to_string(&json_file).map_err(|_| "Could not read Report JSON")?;
+        let content = read_json_text(&json_file)?;
 
         let json_value: serde_json::Value =
-            serde_json::from_str(&content).map_err(|_| "Invalid JSON format")?;
+            serde_json::from_str(&content).map_err(|e| format!("Invalid JSON format: {}", e))?;
 
         let report_type = json_value
             .get("report_type")
@@ -616,14 +1512,7 @@ impl FrappeAnalyzer {
     }
 
     pub fn extract_database_name(&self, config_path: &Path) -> Result<String, String> {
-        if !config_path.exists() {
-            return Err("Site config not found".to_string());
-        }
-
-        let content = fs::read_to_string(config_path).map_err(|_| "Could not read site config")?;
-
-        let config: serde_json::Value =
-            serde_json::from_str(&content).map_err(|_| "Invalid site config JSON")?;
+        let config = self.read_site_config(config_path)?;
 
         let db_name = config
             .get("db_name")
@@ -633,17 +1522,118 @@ impl FrappeAnalyzer {
         Ok(db_name.to_string())
     }
 
-    pub fn get_default_site(&self, bench_path: &Path) -> Result<Option<String>, String> {
-        let common_config_path = bench_path.join("sites").join("common_site_config.json");
-
-        if !common_config_path.exists() {
-            return Ok(None);
+    pub fn read_site_config(
+        &self,
+        config_path: &Path,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+        if !config_path.exists() {
+            return Err("Site config not found".to_string());
         }
 
-        let content = fs::read_to_string(common_config_path)
-            .map_err(|_| "Could not read common site config")?;
+        let content = fs::read_to_string(config_path).map_err(|_| "Could not read site config")?;
 
-        let config: serde_json::Value =
+        match serde_json::from_str(&content).map_err(|_| "Invalid site config JSON")? {
+            serde_json::Value::Object(config) => Ok(config),
+            _ => Err("Site config must be a JSON object".to_string()),
+        }
+    }
+
+    /// Sets `key` in a site config, writing through a temp file so a crash
+    /// can't leave a half-written config behind. Returns the stored value.
+    pub fn set_site_config_value(
+        &self,
+        config_path: &Path,
+        key: &str,
+        raw_value: &str,
+    ) -> Result<serde_json::Value, String> {
+        let mut config = self.read_site_config(config_path)?;
+        let value = parse_config_value(raw_value);
+        config.insert(key.to_string(), value.clone());
+
+        write_atomically(
+            config_path,
+            &to_frappe_json(&serde_json::Value::Object(config))?,
+        )?;
+
+        Ok(value)
+    }
+
+    /// Rewrites a DocType's JSON in Frappe's export format so UI edits don't
+    /// leave whitespace-only diffs. Returns whether the file changed.
+    pub fn format_doctype(&self, doctype: &str) -> Result<bool, String> {
+        let info = self
+            .find_doctype(doctype)
+            .ok_or(format!("DocType '{}' not found", doctype))?;
+        let current = read_json_text(&info.file_path)?;
+        let formatted = to_frappe_json(&read_json(&info.file_path)?)?;
+        if current == formatted {
+            return Ok(false);
+        }
+        write_atomically(&info.file_path, &formatted)?;
+        Ok(true)
+    }
+
+    /// Rewrites a DocType's `field_order` to `new_order`, which must name
+    /// every field exactly once. The `fields` array itself is left as is.
+    pub fn reorder_fields(&self, doctype: &str, new_order: &[String]) -> Result<(), String> {
+        let info = self
+            .find_doctype(doctype)
+            .ok_or(format!("DocType '{}' not found", doctype))?;
+        let mut json = read_json(&info.file_path)?;
+
+        let fieldnames: Vec<String> = json
+            .get("fields")
+            .and_then(|v| v.as_array())
+            .ok_or(format!("{} has no fields list", info.file_path.display()))?
+            .iter()
+            .filter_map(|field| field.get("fieldname").and_then(|v| v.as_str()))
+            .map(|s| s.to_string())
+            .collect();
+
+        let unknown: Vec<&str> = new_order
+            .iter()
+            .filter(|name| !fieldnames.contains(name))
+            .map(|s| s.as_str())
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!(
+                "Unknown fields in {}: {}",
+                info.name,
+                unknown.join(", ")
+            ));
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = new_order.iter().find(|name| !seen.insert(name.as_str())) {
+            return Err(format!("Field {} is listed more than once", duplicate));
+        }
+        let missing: Vec<&str> = fieldnames
+            .iter()
+            .filter(|name| !new_order.contains(name))
+            .map(|s| s.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "New order for {} leaves out: {}",
+                info.name,
+                missing.join(", ")
+            ));
+        }
+
+        json["field_order"] = serde_json::json!(new_order);
+        write_atomically(&info.file_path, &to_frappe_json(&json)?)
+    }
+
+    pub fn get_default_site(&self, bench_path: &Path) -> Result<Option<String>, String> {
+        let common_config_path = bench_path.join("sites").join("common_site_config.json");
+
+        if !common_config_path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(common_config_path)
+            .map_err(|_| "Could not read common site config")?;
+
+        let config: serde_json::Value =
             serde_json::from_str(&content).map_err(|_| "Invalid common site config JSON")?;
 
         Ok(config
@@ -740,13 +1730,30 @@ impl FrappeAnalyzer {
         self.project.as_ref()
     }
 
-    pub fn search_doctypes(&self, query: &str) -> Vec<&DocTypeInfo> {
+    /// DocTypes matching `query`, optionally limited to one app and/or module.
+    /// Module names match either form ("Stock Settings" or "stock_settings").
+    pub fn search_doctypes_in(
+        &self,
+        query: &str,
+        app_filter: Option<&str>,
+        module_filter: Option<&str>,
+    ) -> Vec<&DocTypeInfo> {
         if let Some(project) = &self.project {
             let mut results = Vec::new();
             let query_lower = query.to_lowercase();
+            let module_filter = module_filter.map(doctype_folder_name);
 
             for app in &project.apps {
+                if app_filter.is_some_and(|wanted| !app.name.eq_ignore_ascii_case(wanted)) {
+                    continue;
+                }
                 for doctype in &app.doctypes {
+                    if module_filter
+                        .as_ref()
+                        .is_some_and(|wanted| doctype_folder_name(&doctype.module) != *wanted)
+                    {
+                        continue;
+                    }
                     if doctype.name.to_lowercase().contains(&query_lower)
                         || doctype.module.to_lowercase().contains(&query_lower)
                     {
@@ -761,72 +1768,5231 @@ impl FrappeAnalyzer {
         }
     }
 
-    pub fn find_doctype_dependencies(&self, doctype_name: &str) -> HashMap<String, Vec<String>> {
-        let mut dependencies = HashMap::new();
+    /// Looks up a DocType by name, accepting either the display name ("Sales
+    /// Invoice") or the folder name ("sales_invoice").
+    pub fn find_doctype(&self, name: &str) -> Option<&DocTypeInfo> {
+        let wanted = doctype_folder_name(name);
+        self.project.as_ref().and_then(|project| {
+            project
+                .apps
+                .iter()
+                .flat_map(|app| app.doctypes.iter())
+                .find(|dt| doctype_folder_name(&dt.name) == wanted)
+        })
+    }
+
+    /// An app's DocTypes sorted by module, then name. When the app wasn't
+    /// found, the error lists the apps that were.
+    pub fn app_doctypes(&self, app_name: &str) -> Result<Vec<&DocTypeInfo>, String> {
+        let apps = self
+            .project
+            .as_ref()
+            .map(|project| project.apps.as_slice())
+            .unwrap_or_default();
+        let Some(app) = apps.iter().find(|app| app.name == app_name) else {
+            let mut names: Vec<&str> = apps.iter().map(|app| app.name.as_str()).collect();
+            names.sort();
+            return Err(format!(
+                "App '{}' not found. Available apps: {}",
+                app_name,
+                if names.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    names.join(", ")
+                }
+            ));
+        };
+        let mut doctypes: Vec<&DocTypeInfo> = app.doctypes.iter().collect();
+        doctypes.sort_by(|a, b| (&a.module, &a.name).cmp(&(&b.module, &b.name)));
+        Ok(doctypes)
+    }
+
+    /// Role × right counts over one app's DocTypes, or the whole project
+    /// without `app_name`, sorted by role.
+    pub fn permission_matrix(
+        &self,
+        app_name: Option<&str>,
+    ) -> Result<Vec<RolePermissions>, String> {
+        let doctypes: Vec<&DocTypeInfo> = match app_name {
+            Some(app_name) => self.app_doctypes(app_name)?,
+            None => self
+                .project
+                .iter()
+                .flat_map(|project| project.apps.iter())
+                .flat_map(|app| app.doctypes.iter())
+                .collect(),
+        };
+
+        let mut roles: HashMap<&str, RolePermissions> = HashMap::new();
+        for doctype in doctypes {
+            let mut granted: HashMap<&str, [bool; 5]> = HashMap::new();
+            for perm in &doctype.permissions {
+                if perm.role.is_empty() {
+                    continue;
+                }
+                let rights = granted.entry(perm.role.as_str()).or_default();
+                for (right, value) in rights.iter_mut().zip([
+                    perm.read,
+                    perm.write,
+                    perm.create,
+                    perm.delete,
+                    perm.submit,
+                ]) {
+                    *right |= value == Some(1);
+                }
+            }
+            for (role, [read, write, create, delete, submit]) in granted {
+                let counts = roles.entry(role).or_insert_with(|| RolePermissions {
+                    role: role.to_string(),
+                    ..Default::default()
+                });
+                counts.read += usize::from(read);
+                counts.write += usize::from(write);
+                counts.create += usize::from(create);
+                counts.delete += usize::from(delete);
+                counts.submit += usize::from(submit);
+            }
+        }
+
+        let mut matrix: Vec<RolePermissions> = roles.into_values().collect();
+        matrix.sort_by(|a, b| a.role.cmp(&b.role));
+        Ok(matrix)
+    }
+
+    /// Up to three DocType names close to `name`, for "did you mean" hints.
+    pub fn suggest_doctypes(&self, name: &str) -> Vec<String> {
+        let wanted = doctype_folder_name(name);
+        let Some(project) = &self.project else {
+            return Vec::new();
+        };
+
+        let mut candidates: Vec<(usize, &str)> = project
+            .apps
+            .iter()
+            .flat_map(|app| app.doctypes.iter())
+            .filter_map(|dt| {
+                let folder = doctype_folder_name(&dt.name);
+                let distance = edit_distance(&wanted, &folder);
+                if folder.contains(&wanted) || wanted.contains(&folder) {
+                    Some((0, dt.name.as_str()))
+                } else if distance <= 3 {
+                    Some((distance, dt.name.as_str()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        candidates.sort();
+        candidates
+            .into_iter()
+            .take(3)
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+
+    /// The DocType's JSON without instance metadata, with its name and module
+    /// replaced by `TEMPLATE_DOCTYPE`/`TEMPLATE_MODULE` placeholders (also
+    /// used for Links back to itself), ready to copy into another app.
+    pub fn export_doctype_template(&self, name: &str) -> Result<String, String> {
+        let doctype = self
+            .find_doctype(name)
+            .ok_or(format!("DocType '{}' not found", name))?;
+        let mut json = read_json(&doctype.file_path)?;
+        let own_name = json
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&doctype.name)
+            .to_string();
+        strip_instance_metadata(&mut json, false);
+
+        json["name"] = TEMPLATE_DOCTYPE.into();
+        json["module"] = TEMPLATE_MODULE.into();
+        if let Some(fields) = json.get_mut("fields").and_then(|v| v.as_array_mut()) {
+            for field in fields {
+                if field.get("options").and_then(|v| v.as_str()) == Some(own_name.as_str()) {
+                    field["options"] = TEMPLATE_DOCTYPE.into();
+                }
+            }
+        }
+
+        to_frappe_json(&json)
+    }
+
+    /// A copy of `source`'s JSON as a new DocType `name` in `module`: fields,
+    /// permissions and settings are kept, instance metadata and document
+    /// links are reset.
+    pub fn duplicate_doctype(
+        &self,
+        source: &str,
+        name: &str,
+        module: &str,
+    ) -> Result<DuplicatedDocType, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("DocType name is required".to_string());
+        }
+        if self.find_doctype(name).is_some() {
+            return Err(format!("DocType '{}' already exists", name));
+        }
+        let doctype = self
+            .find_doctype(source)
+            .ok_or(format!("Source DocType '{}' not found", source))?;
+        let mut json = read_json(&doctype.file_path)?;
+        let source_name = json
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&doctype.name)
+            .to_string();
+
+        strip_instance_metadata(&mut json, false);
+        json["name"] = name.into();
+        json["module"] = module.into();
+        // Connections point at fields linking to the source, not to the copy
+        json["links"] = serde_json::json!([]);
+
+        let self_references = json
+            .get("fields")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|field| {
+                let fieldtype = field.get("fieldtype").and_then(|v| v.as_str());
+                matches!(fieldtype, Some("Link" | "Table" | "Table MultiSelect"))
+                    && field.get("options").and_then(|v| v.as_str()) == Some(source_name.as_str())
+            })
+            .filter_map(|field| field.get("fieldname").and_then(|v| v.as_str()))
+            .map(|fieldname| fieldname.to_string())
+            .collect();
+
+        Ok(DuplicatedDocType {
+            json,
+            source: source_name,
+            self_references,
+        })
+    }
+
+    /// Writes a new DocType's `__init__.py`, JSON, controller and client
+    /// script to `<module>/doctype/<name>/` and returns their paths. Fails when
+    /// that folder already exists.
+    pub fn write_doctype(
+        &self,
+        name: &str,
+        module: &str,
+        json: &serde_json::Value,
+        controller: &str,
+        client_script: &str,
+    ) -> Result<Vec<PathBuf>, String> {
+        let stem = doctype_folder_name(name);
+        let doctype_dir = self.module_dir(module)?.join("doctype").join(&stem);
+        if doctype_dir.exists() {
+            return Err(format!("{} already exists", doctype_dir.display()));
+        }
+
+        let files = [
+            (doctype_dir.join("__init__.py"), String::new()),
+            (
+                doctype_dir.join(format!("{}.json", stem)),
+                to_frappe_json(json)?,
+            ),
+            (
+                doctype_dir.join(format!("{}.py", stem)),
+                controller.to_string(),
+            ),
+            (
+                doctype_dir.join(format!("{}.js", stem)),
+                client_script.to_string(),
+            ),
+        ];
+        fs::create_dir_all(&doctype_dir)
+            .map_err(|e| format!("Could not create {}: {}", doctype_dir.display(), e))?;
+        for (path, content) in &files {
+            fs::write(path, content)
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        }
+
+        Ok(files.into_iter().map(|(path, _)| path).collect())
+    }
+
+    /// The folder of `module` in the editable app that has it: found through
+    /// a DocType already in the module, or a matching folder in an app's
+    /// package.
+    fn module_dir(&self, module: &str) -> Result<PathBuf, String> {
+        let apps: Vec<&FrappeApp> = self
+            .project
+            .iter()
+            .flat_map(|project| project.apps.iter())
+            .filter(|app| app.editable)
+            .collect();
+
+        // <module>/doctype/<doctype>/<doctype>.json
+        let from_doctype = apps
+            .iter()
+            .flat_map(|app| app.doctypes.iter())
+            .find(|dt| dt.module.eq_ignore_ascii_case(module))
+            .and_then(|dt| dt.file_path.ancestors().nth(3));
+        if let Some(dir) = from_doctype {
+            return Ok(dir.to_path_buf());
+        }
+
+        apps.iter()
+            .map(|app| app.module_path.join(doctype_folder_name(module)))
+            .find(|dir| dir.is_dir())
+            .ok_or(format!("Module '{}' not found in any app", module))
+    }
+
+    /// Field- and role-level differences between two DocTypes.
+    pub fn diff_doctypes(&self, left: &str, right: &str) -> Result<DocTypeDiff, String> {
+        let lookup = |name: &str| {
+            self.find_doctype(name).ok_or_else(|| {
+                let suggestions = self.suggest_doctypes(name);
+                if suggestions.is_empty() {
+                    format!("DocType '{}' not found", name)
+                } else {
+                    format!(
+                        "DocType '{}' not found. Did you mean: {}?",
+                        name,
+                        suggestions.join(", ")
+                    )
+                }
+            })
+        };
+        let left_dt = lookup(left)?;
+        let right_dt = lookup(right)?;
+
+        let (only_left, only_right, changed_fields) =
+            diff_fields(&left_dt.fields, &right_dt.fields);
+
+        let left_roles = first_rule_per_role(left_dt);
+        let right_roles = first_rule_per_role(right_dt);
+
+        let mut changed_roles = Vec::new();
+        for perm in &left_roles {
+            let Some(other) = right_roles.iter().find(|p| p.role == perm.role) else {
+                continue;
+            };
+            let rights = [
+                ("read", perm.read, other.read),
+                ("write", perm.write, other.write),
+                ("create", perm.create, other.create),
+                ("delete", perm.delete, other.delete),
+            ];
+            let differences: Vec<String> = rights
+                .iter()
+                .filter(|(_, a, b)| a.unwrap_or(0) != b.unwrap_or(0))
+                .map(|(right, a, b)| format!("{}: {} → {}", right, a.unwrap_or(0), b.unwrap_or(0)))
+                .collect();
+            if !differences.is_empty() {
+                changed_roles.push(FieldDifference {
+                    fieldname: perm.role.clone(),
+                    differences,
+                });
+            }
+        }
+
+        Ok(DocTypeDiff {
+            left: left_dt.name.clone(),
+            right: right_dt.name.clone(),
+            only_left,
+            only_right,
+            changed_fields,
+            only_left_roles: left_roles
+                .iter()
+                .filter(|p| !right_roles.iter().any(|o| o.role == p.role))
+                .map(|p| p.role.clone())
+                .collect(),
+            only_right_roles: right_roles
+                .iter()
+                .filter(|p| !left_roles.iter().any(|o| o.role == p.role))
+                .map(|p| p.role.clone())
+                .collect(),
+            changed_roles,
+        })
+    }
+
+    /// Fields added, removed and modified in a DocType's working-tree JSON
+    /// compared to `committed_json`, its version at git HEAD.
+    pub fn doctype_changes(
+        &self,
+        name: &str,
+        committed_json: &str,
+    ) -> Result<DocTypeChanges, String> {
+        let doctype = self
+            .find_doctype(name)
+            .ok_or_else(|| format!("DocType '{}' not found", name))?;
+        let committed: serde_json::Value = serde_json::from_str(committed_json)
+            .map_err(|e| format!("Committed {} is not valid JSON: {}", doctype.name, e))?;
+        let committed_fields = self.parse_fields(&committed)?;
+
+        let (removed, added, modified) = diff_fields(&committed_fields, &doctype.fields);
+        Ok(DocTypeChanges {
+            doctype: doctype.name.clone(),
+            path: doctype.file_path.clone(),
+            added,
+            removed,
+            modified,
+        })
+    }
+
+    /// Top three field types per app, keyed by app name.
+    pub fn field_type_stats_by_app(&self) -> HashMap<String, Vec<(String, usize)>> {
+        let mut stats = HashMap::new();
+
+        if let Some(project) = &self.project {
+            for app in &project.apps {
+                let mut counts: HashMap<String, usize> = HashMap::new();
+                for doctype in &app.doctypes {
+                    for field in &doctype.fields {
+                        *counts.entry(field.fieldtype.clone()).or_insert(0) += 1;
+                    }
+                }
+
+                let mut sorted: Vec<(String, usize)> = counts.into_iter().collect();
+                sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                sorted.truncate(3);
+
+                stats.insert(app.name.clone(), sorted);
+            }
+        }
+
+        stats
+    }
+
+    pub fn project_stats(&self) -> ProjectStats {
+        let Some(project) = &self.project else {
+            return ProjectStats::default();
+        };
+
+        let doctypes = || project.apps.iter().flat_map(|app| app.doctypes.iter());
+        let mut modules: HashMap<String, usize> = HashMap::new();
+        for doctype in doctypes() {
+            *modules.entry(doctype.module.clone()).or_insert(0) += 1;
+        }
+        let mut modules: Vec<(String, usize)> = modules.into_iter().collect();
+        modules.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        modules.truncate(10);
+
+        ProjectStats {
+            apps: project.apps.len(),
+            doctypes: doctypes().count(),
+            pages: project.apps.iter().map(|app| app.pages.len()).sum(),
+            reports: project.apps.iter().map(|app| app.reports.len()).sum(),
+            fields: doctypes().map(|dt| dt.fields.len()).sum(),
+            modules,
+        }
+    }
+
+    /// Fields whose fieldname or label contains `query`, optionally restricted
+    /// to a single fieldtype, paired with the DocType that owns them.
+    pub fn grep_fields(
+        &self,
+        query: &str,
+        fieldtype: Option<&str>,
+    ) -> Vec<(&DocTypeInfo, &FieldInfo)> {
+        let mut results = Vec::new();
+        let query_lower = query.to_lowercase();
 
         if let Some(project) = &self.project {
             for app in &project.apps {
-                for dt in &app.doctypes {
-                    if dt.name == doctype_name {
-                        let mut deps = Vec::new();
-                        for link in &dt.links {
-                            if link.target_doctype != "Dynamic" {
-                                deps.push(link.target_doctype.clone());
+                for doctype in &app.doctypes {
+                    for field in &doctype.fields {
+                        if let Some(fieldtype) = fieldtype {
+                            if !field.fieldtype.eq_ignore_ascii_case(fieldtype) {
+                                continue;
                             }
                         }
-                        if !deps.is_empty() {
-                            dependencies.insert("dependencies".to_string(), deps);
-                        }
-                    }
 
-                    // Find reverse dependencies
-                    for link in &dt.links {
-                        if link.target_doctype == doctype_name {
-                            dependencies
-                                .entry("dependents".to_string())
-                                .or_insert_with(Vec::new)
-                                .push(dt.name.clone());
+                        if field.fieldname.to_lowercase().contains(&query_lower)
+                            || field.label.to_lowercase().contains(&query_lower)
+                        {
+                            results.push((doctype, field));
                         }
                     }
                 }
             }
         }
 
-        dependencies
+        results
     }
-}
 
-pub fn generate_field_suggestions(field_name: &str) -> Vec<(String, String)> {
-    let analyzer = FrappeAnalyzer::new();
-    let suggested_type = analyzer.suggest_field_type(field_name);
+    /// Places in editable apps' Python, JavaScript and JSON files where
+    /// `doctype_name` appears as a quoted string.
+    pub fn grep_doctype_references(&self, doctype_name: &str) -> Result<Vec<TextMatch>, String> {
+        let doctype = self
+            .find_doctype(doctype_name)
+            .ok_or(format!("DocType '{}' not found", doctype_name))?;
+        let roots: Vec<PathBuf> = self
+            .project
+            .iter()
+            .flat_map(|project| project.apps.iter())
+            .filter(|app| app.editable)
+            .map(|app| app.module_path.clone())
+            .collect();
+
+        let pattern = format!(r#"["']{}["']"#, regex::escape(&doctype.name));
+        search_text(&roots, &pattern, &["py", "js", "json"])
+    }
 
-    let mut suggestions = vec![(
-        suggested_type.clone(),
-        "Primary suggestion based on field name".to_string(),
-    )];
+    /// Literal strings passed to `_()` (Python) or `__()` (JavaScript) in an
+    /// app, each listed once at the first place it appears.
+    pub fn extract_translatable_strings(
+        &self,
+        app_name: &str,
+    ) -> Result<Vec<TranslatableString>, String> {
+        let app = self
+            .project
+            .iter()
+            .flat_map(|project| project.apps.iter())
+            .find(|app| app.name == app_name)
+            .ok_or(format!("App '{}' not found", app_name))?;
+
+        let call_re =
+            Regex::new(r#"\b__?\(\s*(?:"((?:[^"\\]|\\.)*)"|'((?:[^'\\]|\\.)*)')"#).unwrap();
+        let matches = search_text(
+            std::slice::from_ref(&app.module_path),
+            r#"\b__?\(\s*["']"#,
+            &["py", "js"],
+        )?;
+
+        let mut seen = HashSet::new();
+        let mut strings = Vec::new();
+        for found in matches {
+            for captures in call_re.captures_iter(&found.text) {
+                let Some(text) = captures.get(1).or(captures.get(2)) else {
+                    continue;
+                };
+                if !text.as_str().is_empty() && seen.insert(text.as_str().to_string()) {
+                    strings.push(TranslatableString {
+                        text: text.as_str().to_string(),
+                        path: found.path.clone(),
+                        line: found.line,
+                    });
+                }
+            }
+        }
+        Ok(strings)
+    }
 
-    // Add common alternatives
-    match suggested_type.as_str() {
-        "Data" => {
-            suggestions.push((
-                "Small Text".to_string(),
-                "For longer text content".to_string(),
+    /// Markdown reference page for a DocType: fields, permissions and links
+    /// in both directions.
+    pub fn doctype_to_markdown(&self, dt: &DocTypeInfo) -> String {
+        let flag = |value: Option<i32>| if value == Some(1) { "✓" } else { "" };
+        let mut md = format!("# {}\n\nModule: {}\n\n## Fields\n\n", dt.name, dt.module);
+
+        if dt.fields.is_empty() {
+            md.push_str("_No fields._\n");
+        } else {
+            md.push_str("| Name | Type | Label | Required | Options |\n");
+            md.push_str("| --- | --- | --- | --- | --- |\n");
+            for field in &dt.fields {
+                md.push_str(&format!(
+                    "| `{}` | {} | {} | {} | {} |\n",
+                    field.fieldname,
+                    field.fieldtype,
+                    markdown_cell(&field.label),
+                    flag(field.reqd),
+                    markdown_cell(field.options.as_deref().unwrap_or(""))
+                ));
+            }
+        }
+
+        md.push_str("\n## Permissions\n\n");
+        if dt.permissions.is_empty() {
+            md.push_str("_No permissions._\n");
+        } else {
+            md.push_str("| Role | Read | Write | Create | Delete |\n");
+            md.push_str("| --- | --- | --- | --- | --- |\n");
+            for perm in &dt.permissions {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    markdown_cell(&perm.role),
+                    flag(perm.read),
+                    flag(perm.write),
+                    flag(perm.create),
+                    flag(perm.delete)
+                ));
+            }
+        }
+
+        if !dt.states.is_empty() {
+            md.push_str("\n## States\n\n| State | Color |\n| --- | --- |\n");
+            for state in &dt.states {
+                md.push_str(&format!(
+                    "| {} | {} |\n",
+                    markdown_cell(&state.title),
+                    markdown_cell(state.color.as_deref().unwrap_or(""))
+                ));
+            }
+        }
+
+        let linked_from: Vec<(&str, &LinkInfo)> = self
+            .project
+            .iter()
+            .flat_map(|project| project.apps.iter())
+            .flat_map(|app| app.doctypes.iter())
+            .flat_map(|other| {
+                other
+                    .links
+                    .iter()
+                    .filter(|link| link.target_doctype == dt.name)
+                    .map(move |link| (other.name.as_str(), link))
+            })
+            .collect();
+
+        md.push_str("\n## Links\n\n");
+        if dt.links.is_empty() && linked_from.is_empty() {
+            md.push_str("_No links._\n");
+        }
+        // Child tables get their own arrow: their rows live inside this
+        // DocType rather than being records it points at
+        let arrow = |link: &LinkInfo| if link.is_child_table { "⇒" } else { "→" };
+        for link in &dt.links {
+            md.push_str(&format!(
+                "- `{}` {} {} ({})\n",
+                link.source_field,
+                arrow(link),
+                link.target_doctype,
+                link.link_type
             ));
-            suggestions.push(("Text".to_string(), "For very long text".to_string()));
         }
-        "Link" => {
-            suggestions.push((
-                "Dynamic Link".to_string(),
-                "For variable DocType links".to_string(),
+        for (source, link) in linked_from {
+            md.push_str(&format!(
+                "- {}.`{}` {} this DocType ({})\n",
+                source,
+                link.source_field,
+                arrow(link),
+                link.link_type
             ));
-            suggestions.push(("Data".to_string(), "If not linking to DocType".to_string()));
         }
-        "Float" => {
-            suggestions.push(("Currency".to_string(), "If represents money".to_string()));
-            suggestions.push(("Int".to_string(), "If whole numbers only".to_string()));
+
+        md
+    }
+
+    /// Cycles in the Link/Table graph between DocTypes, each listed from its
+    /// alphabetically first DocType. Dynamic Links and self-links (tree
+    /// parents) are left out.
+    pub fn find_link_cycles(&self) -> Vec<Vec<String>> {
+        let Some(project) = &self.project else {
+            return Vec::new();
+        };
+
+        // Keyed by folder name, since Link options use the display name
+        let mut display: HashMap<String, String> = HashMap::new();
+        let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+        for dt in project.apps.iter().flat_map(|app| app.doctypes.iter()) {
+            let key = doctype_folder_name(&dt.name);
+            display
+                .entry(key.clone())
+                .or_insert_with(|| dt.name.clone());
+            graph.entry(key.clone()).or_default();
+            for link in dt.links.iter().filter(|l| l.target_doctype != "Dynamic") {
+                let target = doctype_folder_name(&link.target_doctype);
+                display.insert(target.clone(), link.target_doctype.clone());
+                if target != key {
+                    graph.entry(key.clone()).or_default().push(target);
+                }
+            }
         }
-        _ => {}
+        for targets in graph.values_mut() {
+            targets.sort();
+            targets.dedup();
+        }
+
+        fn visit(
+            node: &str,
+            graph: &HashMap<String, Vec<String>>,
+            path: &mut Vec<String>,
+            done: &mut HashSet<String>,
+            cycles: &mut HashSet<Vec<String>>,
+        ) {
+            path.push(node.to_string());
+            for target in graph.get(node).into_iter().flatten() {
+                if let Some(start) = path.iter().position(|n| n == target) {
+                    let mut cycle = path[start..].to_vec();
+                    let first = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap_or(0);
+                    cycle.rotate_left(first);
+                    cycles.insert(cycle);
+                } else if !done.contains(target) {
+                    visit(target, graph, path, done, cycles);
+                }
+            }
+            path.pop();
+            done.insert(node.to_string());
+        }
+
+        let mut nodes: Vec<&String> = graph.keys().collect();
+        nodes.sort();
+        let mut done = HashSet::new();
+        let mut cycles = HashSet::new();
+        for node in nodes {
+            if !done.contains(node) {
+                visit(node, &graph, &mut Vec::new(), &mut done, &mut cycles);
+            }
+        }
+
+        let mut cycles: Vec<Vec<String>> = cycles
+            .into_iter()
+            .map(|cycle| cycle.iter().map(|key| display[key].clone()).collect())
+            .collect();
+        cycles.sort();
+        cycles
     }
 
-    suggestions
+    /// DocTypes that no Link or Table field in the project points at (self
+    /// links aside) and whose controller is missing or only holds empty
+    /// stubs, sorted by module, then name. Only candidates for removal: code
+    /// that uses a DocType at runtime, Dynamic Links included, isn't seen.
+    pub fn unused_doctype_candidates(&self) -> Vec<&DocTypeInfo> {
+        let Some(project) = &self.project else {
+            return Vec::new();
+        };
+        let all = || project.apps.iter().flat_map(|app| app.doctypes.iter());
+
+        let referenced: HashSet<String> = all()
+            .flat_map(|dt| {
+                let key = doctype_folder_name(&dt.name);
+                dt.links
+                    .iter()
+                    .map(|link| doctype_folder_name(&link.target_doctype))
+                    .filter(move |target| *target != key)
+            })
+            .collect();
+
+        let mut candidates: Vec<&DocTypeInfo> = all()
+            .filter(|dt| !referenced.contains(&doctype_folder_name(&dt.name)))
+            .filter(|dt| match &dt.controller_path {
+                Some(path) => fs::read_to_string(path)
+                    .map(|source| is_stub_controller(&source))
+                    .unwrap_or(false),
+                None => true,
+            })
+            .collect();
+        candidates.sort_by(|a, b| (&a.module, &a.name).cmp(&(&b.module, &b.name)));
+        candidates
+    }
+}
+
+/// Rows without a title are skipped, as Frappe can't match them to a status.
+fn parse_states(json_value: &serde_json::Value) -> Vec<DocTypeState> {
+    json_value
+        .get("states")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|state| {
+            let title = state.get("title")?.as_str()?.trim();
+            if title.is_empty() {
+                return None;
+            }
+            Some(DocTypeState {
+                title: title.to_string(),
+                color: state
+                    .get("color")
+                    .and_then(|v| v.as_str())
+                    .filter(|color| !color.is_empty())
+                    .map(|color| color.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// True when a Python controller has nothing beyond imports, class and method
+/// headers, docstrings, `pass` and Frappe's auto-generated type block.
+fn is_stub_controller(source: &str) -> bool {
+    let mut in_types = false;
+    let mut in_docstring = false;
+    for line in source.lines().map(str::trim) {
+        if in_types {
+            in_types = !line.starts_with("# end: auto-generated types");
+            continue;
+        }
+        if line.starts_with("# begin: auto-generated types") {
+            in_types = true;
+            continue;
+        }
+        if in_docstring {
+            in_docstring = !(line.ends_with(r#"""""#) || line.ends_with("'''"));
+            continue;
+        }
+        if let Some(quote) = [r#"""""#, "'''"].into_iter().find(|q| line.starts_with(*q)) {
+            in_docstring = line.len() < 6 || !line[3..].ends_with(quote);
+            continue;
+        }
+
+        let is_boilerplate = line.is_empty()
+            || line.starts_with('#')
+            || line == "pass"
+            || line.starts_with("import ")
+            || line.starts_with("from ")
+            || (line.starts_with("class ") || line.starts_with("def ")) && line.ends_with(':');
+        if !is_boilerplate {
+            return false;
+        }
+    }
+    true
+}
+
+// hooks.py helpers. Parsing is deliberately regex-based and conservative: we
+// only look at literal assignments and skip anything built dynamically.
+impl FrappeAnalyzer {
+    pub fn lint_hooks(&self, app: &FrappeApp) -> Result<Vec<HookIssue>, String> {
+        let content = fs::read_to_string(&app.hooks_path)
+            .map_err(|_| format!("Could not read {}", app.hooks_path.display()))?;
+        let mut issues = Vec::new();
+
+        if let Some(references) = extract_hook_strings(&content, "doc_events") {
+            for reference in references.iter().filter(|r| is_dotted_path(&r.value)) {
+                if let Some(message) = self.check_dotted_path(&reference.value) {
+                    issues.push(HookIssue {
+                        hook: "doc_events".to_string(),
+                        reference: reference.value.clone(),
+                        line: reference.line,
+                        message,
+                    });
+                }
+            }
+        }
+
+        for hook in ["app_include_js", "app_include_css"] {
+            for reference in extract_hook_strings(&content, hook).unwrap_or_default() {
+                if let Some(message) = self.check_asset_path(app, &reference.value) {
+                    issues.push(HookIssue {
+                        hook: hook.to_string(),
+                        reference: reference.value.clone(),
+                        line: reference.line,
+                        message,
+                    });
+                }
+            }
+        }
+
+        issues.extend(self.check_fixtures(&content));
+
+        Ok(issues)
+    }
+
+    /// Returns an issue message if the module behind `path` clearly doesn't
+    /// exist or doesn't mention the function. Paths into apps we didn't analyze
+    /// are skipped.
+    fn check_dotted_path(&self, path: &str) -> Option<String> {
+        let project = self.project.as_ref()?;
+        let parts: Vec<&str> = path.split('.').collect();
+        let (function, module_parts) = parts.split_last()?;
+        let app = project
+            .apps
+            .iter()
+            .find(|app| app.name == module_parts[0])?;
+
+        let module_base = module_parts
+            .iter()
+            .fold(app.path.clone(), |acc, part| acc.join(part));
+        let module_file = module_base.with_extension("py");
+        let package_init = module_base.join("__init__.py");
+
+        let source = if module_file.is_file() {
+            fs::read_to_string(&module_file).ok()?
+        } else if package_init.is_file() {
+            fs::read_to_string(&package_init).ok()?
+        } else {
+            return Some(format!("Module {} not found", module_parts.join(".")));
+        };
+
+        let mentioned = Regex::new(&format!(r"\b{}\b", regex::escape(function)))
+            .map(|re| re.is_match(&source))
+            .unwrap_or(true);
+        if mentioned {
+            None
+        } else {
+            Some(format!(
+                "Function {} not found in {}",
+                function,
+                module_parts.join(".")
+            ))
+        }
+    }
+
+    fn check_asset_path(&self, app: &FrappeApp, asset: &str) -> Option<String> {
+        let public_dir = app.module_path.join("public");
+
+        if let Some(rest) = asset.strip_prefix(&format!("/assets/{}/", app.name)) {
+            // dist/ output only exists after a build
+            if rest.starts_with("dist/") || public_dir.join(rest).exists() {
+                return None;
+            }
+            return Some(format!("Asset not found: public/{}", rest));
+        }
+
+        // esbuild bundles (v14+) are referenced by file name only
+        if !asset.contains('/') && asset.contains(".bundle.") {
+            if find_file_named(&public_dir, asset) {
+                return None;
+            }
+            return Some(format!("Bundle {} not found under public/", asset));
+        }
+
+        None
+    }
+
+    fn check_fixtures(&self, content: &str) -> Vec<HookIssue> {
+        let mut issues = Vec::new();
+        let Some(project) = &self.project else {
+            return issues;
+        };
+
+        // Core DocTypes like "Custom Field" live in frappe; without it we can't tell
+        if !project.apps.iter().any(|app| app.name == "frappe") {
+            return issues;
+        }
+
+        let known: Vec<String> = project
+            .apps
+            .iter()
+            .flat_map(|app| app.doctypes.iter())
+            .map(|dt| dt.name.to_lowercase().replace(' ', "_"))
+            .collect();
+
+        let Some((block, start_line)) = extract_hook_block(content, "fixtures") else {
+            return issues;
+        };
+        // Anything that isn't a plain literal list is dynamic; leave it alone
+        let Some(serde_json::Value::Array(entries)) = python_literal_to_json(&block) else {
+            return issues;
+        };
+
+        // Entries are either bare DocType names or {"dt"/"doctype": ...} dicts
+        for entry in entries {
+            let name = match &entry {
+                serde_json::Value::String(name) => name.clone(),
+                serde_json::Value::Object(map) => match map
+                    .get("dt")
+                    .or(map.get("doctype"))
+                    .and_then(|v| v.as_str())
+                {
+                    Some(name) => name.to_string(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            if !known.contains(&name.to_lowercase().replace(' ', "_")) {
+                let offset = block.find(&name).unwrap_or(0);
+                issues.push(HookIssue {
+                    hook: "fixtures".to_string(),
+                    reference: name.clone(),
+                    line: start_line + block[..offset].matches('\n').count(),
+                    message: format!("DocType {} not found in any app", name),
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Jobs from `scheduler_events`, ordered from most to least frequent with
+    /// cron entries last. An app without the hook has no jobs.
+    pub fn scheduler_events(&self, app: &FrappeApp) -> Result<Vec<ScheduledJob>, String> {
+        let content = fs::read_to_string(&app.hooks_path)
+            .map_err(|_| format!("Could not read {}", app.hooks_path.display()))?;
+        let Some((block, line)) = extract_hook_block(&content, "scheduler_events") else {
+            return Ok(Vec::new());
+        };
+        let value = python_literal_to_json(&block).ok_or(format!(
+            "Could not parse scheduler_events at {}:{}",
+            app.hooks_path.display(),
+            line
+        ))?;
+        let buckets = value
+            .as_object()
+            .ok_or("scheduler_events must be a dict".to_string())?;
+
+        let methods = |value: &serde_json::Value| -> Vec<String> {
+            value
+                .as_array()
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut jobs = Vec::new();
+        for (frequency, entries) in buckets {
+            if frequency == "cron" {
+                for (expression, cron_entries) in entries.as_object().into_iter().flatten() {
+                    jobs.extend(
+                        methods(cron_entries)
+                            .into_iter()
+                            .map(|method| ScheduledJob {
+                                frequency: frequency.clone(),
+                                cron: Some(expression.clone()),
+                                method,
+                            }),
+                    );
+                }
+            } else {
+                jobs.extend(methods(entries).into_iter().map(|method| ScheduledJob {
+                    frequency: frequency.clone(),
+                    cron: None,
+                    method,
+                }));
+            }
+        }
+
+        jobs.sort_by_key(|job| frequency_rank(&job.frequency));
+        Ok(jobs)
+    }
+
+    /// Static checks over an app's DocTypes: child tables that nest Table
+    /// fields (Frappe can't save those) and DocTypes with more than
+    /// `MAX_LINK_FIELDS` Link fields, which make every save and list view
+    /// join-heavy.
+    pub fn doctype_issues(&self, app: &FrappeApp) -> Vec<DocTypeIssue> {
+        let mut issues = Vec::new();
+
+        for doctype in &app.doctypes {
+            let Ok(json) = read_json(&doctype.file_path) else {
+                continue;
+            };
+            let fields = json
+                .get("fields")
+                .and_then(|v| v.as_array())
+                .map(|fields| fields.as_slice())
+                .unwrap_or_default();
+            let of_type = |fieldtype: &'static str| {
+                fields
+                    .iter()
+                    .filter(move |f| f.get("fieldtype").and_then(|v| v.as_str()) == Some(fieldtype))
+            };
+            let name = json
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&doctype.name)
+                .to_string();
+            let mut issue = |error: bool, message: String| {
+                issues.push(DocTypeIssue {
+                    doctype: name.clone(),
+                    path: doctype.file_path.clone(),
+                    error,
+                    message,
+                })
+            };
+
+            if json.get("istable").and_then(|v| v.as_i64()) == Some(1) {
+                for table in of_type("Table").chain(of_type("Table MultiSelect")) {
+                    issue(
+                        true,
+                        format!(
+                            "child table has Table field '{}'; child tables can't nest tables",
+                            table
+                                .get("fieldname")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("?")
+                        ),
+                    );
+                }
+            }
+
+            let links = of_type("Link").count();
+            if links > MAX_LINK_FIELDS {
+                let unindexed = of_type("Link")
+                    .filter(|f| f.get("search_index").and_then(|v| v.as_i64()) != Some(1))
+                    .count();
+                issue(
+                    false,
+                    format!(
+                        "{} Link fields ({} without search_index); consider splitting it or indexing the ones you filter on",
+                        links, unindexed
+                    ),
+                );
+            }
+        }
+
+        issues
+    }
+}
+
+fn frequency_rank(frequency: &str) -> usize {
+    const ORDER: [&str; 9] = [
+        "all",
+        "hourly",
+        "hourly_long",
+        "daily",
+        "daily_long",
+        "weekly",
+        "weekly_long",
+        "monthly",
+        "monthly_long",
+    ];
+    ORDER
+        .iter()
+        .position(|f| *f == frequency)
+        .unwrap_or(ORDER.len())
+}
+
+// DocType renames. The plan is built without touching disk; `apply` then
+// writes contents, renames files and finally moves the folder, undoing what
+// it did if a step fails.
+impl FrappeAnalyzer {
+    pub fn rename_doctype(&self, old: &str, new: &str) -> Result<RenamePlan, String> {
+        let new_name = new.trim().to_string();
+        if new_name.is_empty()
+            || !new_name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+        {
+            return Err(format!("Invalid DocType name: '{}'", new));
+        }
+
+        let doctype = self
+            .find_doctype(old)
+            .ok_or(format!("DocType '{}' not found", old))?;
+        if self.find_doctype(&new_name).is_some() {
+            return Err(format!("DocType '{}' already exists", new_name));
+        }
+
+        let json_value = read_json(&doctype.file_path)?;
+        let old_name = json_value
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&doctype.name)
+            .to_string();
+
+        let old_dir = doctype
+            .file_path
+            .parent()
+            .ok_or("DocType JSON has no parent folder")?
+            .to_path_buf();
+        let old_stem = doctype_folder_name(&doctype.name);
+        let new_stem = doctype_folder_name(&new_name);
+        let new_dir = old_dir
+            .parent()
+            .ok_or("DocType folder has no parent")?
+            .join(&new_stem);
+        if new_dir.exists() {
+            return Err(format!("{} already exists", new_dir.display()));
+        }
+
+        let old_class = doctype_class_name(&old_name);
+        let new_class = doctype_class_name(&new_name);
+        let class_re = Regex::new(&format!(r"\b(Test)?{}\b", regex::escape(&old_class)))
+            .map_err(|e| e.to_string())?;
+
+        let mut changes = Vec::new();
+        let mut entries: Vec<PathBuf> = fs::read_dir(&old_dir)
+            .map_err(|_| format!("Could not read {}", old_dir.display()))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let renamed_file = file_name.replacen(&old_stem, &new_stem, 1);
+
+            let content = if path == doctype.file_path {
+                // Self-links too, e.g. a tree's parent_<doctype> field
+                let mut value = json_value.clone();
+                value["name"] = serde_json::Value::String(new_name.clone());
+                retarget_references(&mut value, &old_name, &new_name);
+                Some(to_frappe_json(&value)?)
+            } else if matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("py") | Some("js")
+            ) {
+                let original = fs::read_to_string(&path)
+                    .map_err(|_| format!("Could not read {}", path.display()))?;
+                let rewritten = class_re
+                    .replace_all(&original, |caps: &regex::Captures| {
+                        format!("{}{}", caps.get(1).map_or("", |m| m.as_str()), new_class)
+                    })
+                    .replace(&format!("\"{}\"", old_name), &format!("\"{}\"", new_name))
+                    .replace(&format!("'{}'", old_name), &format!("'{}'", new_name));
+                (rewritten != original).then_some(rewritten)
+            } else {
+                None
+            };
+
+            changes.push(FileChange {
+                renamed_to: Some(new_dir.join(renamed_file)),
+                path,
+                content,
+            });
+        }
+
+        // Link and Table fields in other DocTypes that point at the old name
+        if let Some(project) = &self.project {
+            for other in project.apps.iter().flat_map(|app| app.doctypes.iter()) {
+                if other.file_path == doctype.file_path
+                    || !other
+                        .fields
+                        .iter()
+                        .any(|f| is_doctype_reference(f) && f.options.as_deref() == Some(&old_name))
+                {
+                    continue;
+                }
+
+                let mut value = read_json(&other.file_path)?;
+                retarget_references(&mut value, &old_name, &new_name);
+
+                changes.push(FileChange {
+                    path: other.file_path.clone(),
+                    renamed_to: None,
+                    content: Some(to_frappe_json(&value)?),
+                });
+            }
+        }
+
+        Ok(RenamePlan {
+            old_name,
+            new_name,
+            old_dir,
+            new_dir,
+            changes,
+        })
+    }
+}
+
+/// Points the Link, Table and Table MultiSelect fields of a DocType JSON that
+/// reference `old_name` at `new_name`.
+fn retarget_references(value: &mut serde_json::Value, old_name: &str, new_name: &str) {
+    let Some(fields) = value.get_mut("fields").and_then(|f| f.as_array_mut()) else {
+        return;
+    };
+    for field in fields {
+        let fieldtype = field.get("fieldtype").and_then(|v| v.as_str());
+        let is_reference = matches!(
+            fieldtype,
+            Some("Link") | Some("Table") | Some("Table MultiSelect")
+        );
+        if is_reference && field.get("options").and_then(|v| v.as_str()) == Some(old_name) {
+            field["options"] = serde_json::Value::String(new_name.to_string());
+        }
+    }
+}
+
+/// A step of `RenamePlan::apply` that has been carried out, kept so it can be
+/// undone if a later one fails.
+enum AppliedStep {
+    Wrote { path: PathBuf, original: Vec<u8> },
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl RenamePlan {
+    /// Applies the plan. If any step fails, the ones already done are undone
+    /// in reverse so the tree is left as it was.
+    pub fn apply(&self) -> Result<(), String> {
+        let mut done = Vec::new();
+        let Err(error) = self.apply_steps(&mut done) else {
+            return Ok(());
+        };
+
+        let mut undo_errors = Vec::new();
+        for step in done.into_iter().rev() {
+            let undone = match &step {
+                AppliedStep::Wrote { path, original } => fs::write(path, original),
+                AppliedStep::Renamed { from, to } => fs::rename(to, from),
+            };
+            if let Err(e) = undone {
+                let path = match &step {
+                    AppliedStep::Wrote { path, .. } => path,
+                    AppliedStep::Renamed { to, .. } => to,
+                };
+                undo_errors.push(format!("{}: {}", path.display(), e));
+            }
+        }
+        if undo_errors.is_empty() {
+            Err(format!("{}; the rename was rolled back", error))
+        } else {
+            Err(format!(
+                "{}; rolling back also failed for {}",
+                error,
+                undo_errors.join(", ")
+            ))
+        }
+    }
+
+    fn apply_steps(&self, done: &mut Vec<AppliedStep>) -> Result<(), String> {
+        for change in &self.changes {
+            if let Some(content) = &change.content {
+                let original = fs::read(&change.path)
+                    .map_err(|e| format!("Could not read {}: {}", change.path.display(), e))?;
+                fs::write(&change.path, content)
+                    .map_err(|e| format!("Could not write {}: {}", change.path.display(), e))?;
+                done.push(AppliedStep::Wrote {
+                    path: change.path.clone(),
+                    original,
+                });
+            }
+        }
+
+        for change in &self.changes {
+            if let Some(file_name) = change.renamed_to.as_ref().and_then(|p| p.file_name()) {
+                let staged = self.old_dir.join(file_name);
+                if staged != change.path {
+                    fs::rename(&change.path, &staged).map_err(|e| {
+                        format!("Could not rename {}: {}", change.path.display(), e)
+                    })?;
+                    done.push(AppliedStep::Renamed {
+                        from: change.path.clone(),
+                        to: staged,
+                    });
+                }
+            }
+        }
+
+        fs::rename(&self.old_dir, &self.new_dir)
+            .map_err(|e| format!("Could not move {}: {}", self.old_dir.display(), e))
+    }
+}
+
+// Script and Workflow generators. Records are written where developer-mode
+// export puts them, e.g. `<module>/server_script/<name>/<name>.json`, next to
+// the module of the DocType they attach to.
+impl FrappeAnalyzer {
+    pub fn generate_script(
+        &self,
+        kind: ScriptKind,
+        name: &str,
+        doctype_name: &str,
+        event: Option<&str>,
+    ) -> Result<PathBuf, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("{} name is required", kind.doctype()));
+        }
+
+        let (doctype, reference, record_dir) =
+            self.record_target(doctype_name, kind.doctype(), name)?;
+        let record_path = record_dir.join(format!("{}.json", doctype_folder_name(name)));
+        if record_path.exists() {
+            return Err(format!("{} already exists", record_path.display()));
+        }
+
+        let record = match kind {
+            ScriptKind::Server => serde_json::json!({
+                "allow_guest": 0,
+                "disabled": 0,
+                "doctype": kind.doctype(),
+                "doctype_event": event.unwrap_or("Before Save"),
+                "module": doctype.module,
+                "name": name,
+                "reference_doctype": reference,
+                "script": "# `doc` is the document being processed\n",
+                "script_type": "DocType Event",
+            }),
+            // Client Script keeps its target DocType in `dt`
+            ScriptKind::Client => serde_json::json!({
+                "doctype": kind.doctype(),
+                "dt": reference,
+                "enabled": 1,
+                "module": doctype.module,
+                "name": name,
+                "script": format!(
+                    "frappe.ui.form.on(\"{}\", {{\n\trefresh(frm) {{\n\n\t}},\n}});\n",
+                    reference
+                ),
+                "view": "Form",
+            }),
+        };
+
+        fs::create_dir_all(&record_dir)
+            .map_err(|e| format!("Could not create {}: {}", record_dir.display(), e))?;
+        fs::write(&record_path, to_frappe_json(&record)?)
+            .map_err(|e| format!("Could not write {}: {}", record_path.display(), e))?;
+
+        Ok(record_path)
+    }
+
+    /// Writes a Workflow for `doctype_name` with Draft and Approved states,
+    /// adding a `workflow_state` field to the DocType when it has none.
+    pub fn generate_workflow(
+        &self,
+        name: &str,
+        doctype_name: &str,
+    ) -> Result<GeneratedWorkflow, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Workflow name is required".to_string());
+        }
+
+        let (doctype, reference, record_dir) =
+            self.record_target(doctype_name, "Workflow", name)?;
+        let record_path = record_dir.join(format!("{}.json", doctype_folder_name(name)));
+        if record_path.exists() {
+            return Err(format!("{} already exists", record_path.display()));
+        }
+
+        let mut doctype_json = read_json(&doctype.file_path)?;
+        // Submittable DocTypes submit on approval; others just change state
+        let approved_status =
+            if doctype_json.get("is_submittable").and_then(|v| v.as_i64()) == Some(1) {
+                "1"
+            } else {
+                "0"
+            };
+
+        let state = |idx: u32, state: &str, doc_status: &str| {
+            serde_json::json!({
+                "allow_edit": "System Manager",
+                "doc_status": doc_status,
+                "doctype": "Workflow Document State",
+                "idx": idx,
+                "parent": name,
+                "parentfield": "states",
+                "parenttype": "Workflow",
+                "state": state,
+            })
+        };
+        let record = serde_json::json!({
+            "doctype": "Workflow",
+            "document_type": reference,
+            "is_active": 1,
+            "module": doctype.module,
+            "name": name,
+            "override_status": 0,
+            "send_email_alert": 0,
+            "states": [state(1, "Draft", "0"), state(2, "Approved", approved_status)],
+            "transitions": [{
+                "action": "Approve",
+                "allow_self_approval": 1,
+                "allowed": "System Manager",
+                "doctype": "Workflow Transition",
+                "idx": 1,
+                "next_state": "Approved",
+                "parent": name,
+                "parentfield": "transitions",
+                "parenttype": "Workflow",
+                "state": "Draft",
+            }],
+            "workflow_name": name,
+            "workflow_state_field": "workflow_state",
+        });
+
+        let has_state_field = doctype
+            .fields
+            .iter()
+            .any(|field| field.fieldname == "workflow_state");
+        if !has_state_field {
+            let fields = doctype_json
+                .get_mut("fields")
+                .and_then(|v| v.as_array_mut())
+                .ok_or(format!(
+                    "{} has no fields list",
+                    doctype.file_path.display()
+                ))?;
+            fields.push(serde_json::json!({
+                "allow_on_submit": 1,
+                "fieldname": "workflow_state",
+                "fieldtype": "Select",
+                "label": "Workflow State",
+                "no_copy": 1,
+                "options": "Draft\nApproved",
+                "read_only": 1,
+            }));
+            if let Some(order) = doctype_json
+                .get_mut("field_order")
+                .and_then(|v| v.as_array_mut())
+            {
+                order.push(serde_json::Value::String("workflow_state".to_string()));
+            }
+        }
+
+        fs::create_dir_all(&record_dir)
+            .map_err(|e| format!("Could not create {}: {}", record_dir.display(), e))?;
+        fs::write(&record_path, to_frappe_json(&record)?)
+            .map_err(|e| format!("Could not write {}: {}", record_path.display(), e))?;
+        if !has_state_field {
+            fs::write(&doctype.file_path, to_frappe_json(&doctype_json)?)
+                .map_err(|e| format!("Could not write {}: {}", doctype.file_path.display(), e))?;
+        }
+
+        Ok(GeneratedWorkflow {
+            path: record_path,
+            doctype_path: doctype.file_path.clone(),
+            field_added: !has_state_field,
+        })
+    }
+
+    /// Appends a `@frappe.whitelist()` stub to `<app>/<app>/api.py`, creating
+    /// the file if needed. Fails if a function of that name is already defined.
+    pub fn generate_api_method(
+        &self,
+        app_name: &str,
+        method: &str,
+        params: &[String],
+    ) -> Result<GeneratedApiMethod, String> {
+        let app = self
+            .get_project()
+            .and_then(|project| project.apps.iter().find(|app| app.name == app_name))
+            .ok_or(format!("App '{}' not found", app_name))?;
+        if !app.editable {
+            return Err(format!(
+                "{} is installed in the virtualenv and can't be edited",
+                app_name
+            ));
+        }
+        for name in std::iter::once(method).chain(params.iter().map(|p| p.as_str())) {
+            if !is_python_identifier(name) {
+                return Err(format!("'{}' is not a valid Python identifier", name));
+            }
+        }
+
+        let path = app.module_path.join("api.py");
+        let existing = if path.exists() {
+            Some(
+                fs::read_to_string(&path)
+                    .map_err(|e| format!("Could not read {}: {}", path.display(), e))?,
+            )
+        } else {
+            None
+        };
+
+        let mut content = existing.clone().unwrap_or_default();
+        let defined = Regex::new(&format!(r"(?m)^(?:async\s+)?def\s+{}\s*\(", method)).unwrap();
+        if defined.is_match(&content) {
+            return Err(format!("{} already defines {}()", path.display(), method));
+        }
+        if !Regex::new(r"(?m)^import frappe\b")
+            .unwrap()
+            .is_match(&content)
+        {
+            content = if content.trim().is_empty() {
+                "import frappe\n".to_string()
+            } else {
+                format!("import frappe\n\n{}", content)
+            };
+        }
+
+        let mut doc = format!("\t\"\"\"TODO: describe {}.", method);
+        if !params.is_empty() {
+            doc.push_str("\n\n\tArgs:\n");
+            for param in params {
+                doc.push_str(&format!("\t\t{}: TODO\n", param));
+            }
+            doc.push('\t');
+        }
+        doc.push_str("\"\"\"\n");
+
+        content = content.trim_end().to_string();
+        content.push_str(&format!(
+            "\n\n\n@frappe.whitelist()\ndef {}({}):\n{}\tpass\n",
+            method,
+            params.join(", "),
+            doc
+        ));
+        write_atomically(&path, &content)?;
+
+        Ok(GeneratedApiMethod {
+            path,
+            endpoint: format!("{}.api.{}", app_name, method),
+            created: existing.is_none(),
+        })
+    }
+
+    /// Scaffolds a standard report on `doctype_name` under its module. Query
+    /// Reports keep their SQL in the JSON and have no Python; Script Reports
+    /// get an `execute(filters)` returning columns and rows.
+    pub fn generate_report(
+        &self,
+        name: &str,
+        doctype_name: &str,
+        kind: ReportKind,
+    ) -> Result<Vec<PathBuf>, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Report name is required".to_string());
+        }
+
+        let (doctype, reference, report_dir) = self.record_target(doctype_name, "Report", name)?;
+        if report_dir.exists() {
+            return Err(format!("{} already exists", report_dir.display()));
+        }
+        let stem = doctype_folder_name(name);
+
+        let mut record = serde_json::json!({
+            "add_total_row": 0,
+            "columns": [],
+            "disabled": 0,
+            "doctype": "Report",
+            "filters": [],
+            "is_standard": "Yes",
+            "module": doctype.module,
+            "name": name,
+            "prepared_report": 0,
+            "ref_doctype": reference,
+            "report_name": name,
+            "report_type": kind.report_type(),
+            "roles": [{"role": "System Manager"}],
+        });
+        if kind == ReportKind::Query {
+            record["query"] = format!(
+                "-- Column headers are \"Label:Fieldtype/Options:Width\"\n\
+                 SELECT\n\
+                 \t`name` AS \"{}:Link/{}:200\",\n\
+                 \t`modified` AS \"Last Updated:Datetime:160\"\n\
+                 FROM `tab{}`\n\
+                 -- WHERE `docstatus` < 2\n\
+                 ORDER BY `modified` DESC",
+                reference, reference, reference
+            )
+            .into();
+        }
+
+        let mut files = vec![
+            (report_dir.join("__init__.py"), String::new()),
+            (
+                report_dir.join(format!("{}.json", stem)),
+                to_frappe_json(&record)?,
+            ),
+            (
+                report_dir.join(format!("{}.js", stem)),
+                format!(
+                    "frappe.query_reports[\"{}\"] = {{\n\tfilters: [],\n}};\n",
+                    name
+                ),
+            ),
+        ];
+        if kind == ReportKind::Script {
+            files.push((
+                report_dir.join(format!("{}.py", stem)),
+                format!(
+                    "import frappe\n\
+                     from frappe import _\n\
+                     \n\
+                     \n\
+                     def execute(filters=None):\n\
+                     \tcolumns = get_columns()\n\
+                     \tdata = get_data(filters or {{}})\n\
+                     \treturn columns, data\n\
+                     \n\
+                     \n\
+                     def get_columns():\n\
+                     \treturn [\n\
+                     \t\t{{\"label\": _(\"{reference}\"), \"fieldname\": \"name\", \"fieldtype\": \"Link\", \"options\": \"{reference}\", \"width\": 200}},\n\
+                     \t\t{{\"label\": _(\"Last Updated\"), \"fieldname\": \"modified\", \"fieldtype\": \"Datetime\", \"width\": 160}},\n\
+                     \t]\n\
+                     \n\
+                     \n\
+                     def get_data(filters):\n\
+                     \treturn frappe.get_all(\n\
+                     \t\t\"{reference}\", filters=filters, fields=[\"name\", \"modified\"], order_by=\"modified desc\"\n\
+                     \t)\n",
+                    reference = reference
+                ),
+            ));
+        }
+
+        fs::create_dir_all(&report_dir)
+            .map_err(|e| format!("Could not create {}: {}", report_dir.display(), e))?;
+        for (path, content) in &files {
+            fs::write(path, content)
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        }
+
+        Ok(files.into_iter().map(|(path, _)| path).collect())
+    }
+
+    /// Writes a standard email Notification for `doctype_name` with its
+    /// Markdown message and a `get_context` hook. Date and value events watch
+    /// `field`, defaulting to the DocType's first Date/Datetime field for
+    /// Days After/Before.
+    pub fn generate_notification(
+        &self,
+        name: &str,
+        doctype_name: &str,
+        event: &str,
+        field: Option<&str>,
+    ) -> Result<Vec<PathBuf>, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Notification name is required".to_string());
+        }
+        let event = NOTIFICATION_EVENTS
+            .iter()
+            .find(|e| e.eq_ignore_ascii_case(event.trim()))
+            .ok_or(format!(
+                "Unknown event '{}'; expected one of: {}",
+                event,
+                NOTIFICATION_EVENTS.join(", ")
+            ))?;
+
+        let (doctype, reference, record_dir) =
+            self.record_target(doctype_name, "Notification", name)?;
+        let stem = doctype_folder_name(name);
+        let record_path = record_dir.join(format!("{}.json", stem));
+        if record_path.exists() {
+            return Err(format!("{} already exists", record_path.display()));
+        }
+
+        let doctype_json = read_json(&doctype.file_path)?;
+        let submittable = doctype_json.get("is_submittable").and_then(|v| v.as_i64()) == Some(1);
+        if matches!(*event, "Submit" | "Cancel") && !submittable {
+            return Err(format!("{} is not submittable", reference));
+        }
+
+        let find_field = |fieldname: &str| {
+            doctype
+                .fields
+                .iter()
+                .find(|f| f.fieldname == fieldname)
+                .ok_or(format!("{} has no field '{}'", reference, fieldname))
+        };
+        let mut record = serde_json::json!({
+            "attach_print": 0,
+            "channel": "Email",
+            "condition": "",
+            "doctype": "Notification",
+            "document_type": reference,
+            "enabled": 1,
+            "event": event,
+            "is_standard": 1,
+            "message": "",
+            "module": doctype.module,
+            "name": name,
+            "recipients": [{
+                "doctype": "Notification Recipient",
+                "idx": 1,
+                "parent": name,
+                "parentfield": "recipients",
+                "parenttype": "Notification",
+                "receiver_by_document_field": "owner",
+            }],
+            "send_system_notification": 0,
+            "subject": format!("{{{{ doc.name }}}}: {}", name),
+        });
+        match *event {
+            "Days After" | "Days Before" => {
+                let date_field = match field {
+                    Some(fieldname) => find_field(fieldname)?,
+                    None => doctype
+                        .fields
+                        .iter()
+                        .find(|f| matches!(f.fieldtype.as_str(), "Date" | "Datetime"))
+                        .ok_or(format!("{} has no Date field for '{}'", reference, event))?,
+                };
+                if !matches!(date_field.fieldtype.as_str(), "Date" | "Datetime") {
+                    return Err(format!("{} is not a Date field", date_field.fieldname));
+                }
+                record["date_changed"] = date_field.fieldname.clone().into();
+                record["days_in_advance"] = 1.into();
+            }
+            "Value Change" => {
+                let fieldname = field.ok_or("Value Change needs --field <fieldname>")?;
+                record["value_changed"] = find_field(fieldname)?.fieldname.clone().into();
+            }
+            _ if field.is_some() => {
+                return Err(format!("--field doesn't apply to the {} event", event));
+            }
+            _ => {}
+        }
+
+        let message = format!(
+            "<p>{} {{{{ doc.name }}}} ({})</p>\n\n<p>{{{{ doc.get_url() }}}}</p>\n",
+            reference, event
+        );
+        let message_path = record_dir.join(format!("{}.md", stem));
+        let context_path = record_dir.join(format!("{}.py", stem));
+
+        fs::create_dir_all(&record_dir)
+            .map_err(|e| format!("Could not create {}: {}", record_dir.display(), e))?;
+        fs::write(&record_path, to_frappe_json(&record)?)
+            .map_err(|e| format!("Could not write {}: {}", record_path.display(), e))?;
+        fs::write(&message_path, message)
+            .map_err(|e| format!("Could not write {}: {}", message_path.display(), e))?;
+        fs::write(
+            &context_path,
+            "import frappe\n\n\ndef get_context(context):\n\t# do your magic here\n\tpass\n",
+        )
+        .map_err(|e| format!("Could not write {}: {}", context_path.display(), e))?;
+
+        Ok(vec![record_path, message_path, context_path])
+    }
+
+    /// Scaffolds a Web Form for `doctype_name` in the DocType's module, with
+    /// one Web Form Field per visible field.
+    pub fn generate_web_form(
+        &self,
+        name: &str,
+        doctype_name: &str,
+    ) -> Result<GeneratedWebForm, String> {
+        // Layout and UI-only types that Web Forms can't render
+        const SKIPPED_FIELDTYPES: &[&str] = &["Tab Break", "Button", "Fold", "Heading"];
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Web Form name is required".to_string());
+        }
+        let (doctype, reference, form_dir) = self.record_target(doctype_name, "Web Form", name)?;
+        let stem = doctype_folder_name(name);
+        let json_path = form_dir.join(format!("{}.json", stem));
+        if json_path.exists() {
+            return Err(format!("{} already exists", json_path.display()));
+        }
+
+        let doctype_json = read_json(&doctype.file_path)?;
+        let fields: Vec<serde_json::Value> = doctype_json
+            .get("fields")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|field| field.get("hidden").and_then(|v| v.as_i64()) != Some(1))
+            .filter(|field| {
+                let fieldtype = field.get("fieldtype").and_then(|v| v.as_str());
+                fieldtype.is_some_and(|t| !SKIPPED_FIELDTYPES.contains(&t))
+            })
+            .enumerate()
+            .map(|(index, field)| {
+                let text = |key: &str| field.get(key).cloned().unwrap_or(serde_json::Value::Null);
+                serde_json::json!({
+                    "allow_read_on_all_link_options": 0,
+                    "doctype": "Web Form Field",
+                    "fieldname": text("fieldname"),
+                    "fieldtype": text("fieldtype"),
+                    "hidden": 0,
+                    "idx": index + 1,
+                    "label": text("label"),
+                    "options": text("options"),
+                    "parent": name,
+                    "parentfield": "web_form_fields",
+                    "parenttype": "Web Form",
+                    "read_only": 0,
+                    "reqd": field.get("reqd").and_then(|v| v.as_i64()).unwrap_or(0),
+                })
+            })
+            .collect();
+
+        let route = stem.replace('_', "-");
+        let field_count = fields.len();
+        let record = serde_json::json!({
+            "allow_edit": 1,
+            "allow_multiple": 1,
+            "doc_type": reference,
+            "doctype": "Web Form",
+            "introduction_text": "",
+            "is_standard": 1,
+            "login_required": 1,
+            "module": doctype.module,
+            "name": name,
+            "published": 1,
+            "route": route,
+            "success_url": format!("/{}", route),
+            "title": name,
+            "web_form_fields": fields,
+        });
+
+        let init_path = form_dir.join("__init__.py");
+        let controller_path = form_dir.join(format!("{}.py", stem));
+        let script_path = form_dir.join(format!("{}.js", stem));
+        fs::create_dir_all(&form_dir)
+            .map_err(|e| format!("Could not create {}: {}", form_dir.display(), e))?;
+        fs::write(&json_path, to_frappe_json(&record)?)
+            .map_err(|e| format!("Could not write {}: {}", json_path.display(), e))?;
+        fs::write(&init_path, "")
+            .map_err(|e| format!("Could not write {}: {}", init_path.display(), e))?;
+        fs::write(
+            &controller_path,
+            "import frappe\n\n\ndef get_context(context):\n\t# do your magic here\n\tpass\n",
+        )
+        .map_err(|e| format!("Could not write {}: {}", controller_path.display(), e))?;
+        fs::write(
+            &script_path,
+            "frappe.ready(function() {\n\t// bind events here\n})\n",
+        )
+        .map_err(|e| format!("Could not write {}: {}", script_path.display(), e))?;
+
+        Ok(GeneratedWebForm {
+            files: vec![json_path, init_path, controller_path, script_path],
+            route: format!("/{}", route),
+            fields: field_count,
+        })
+    }
+
+    /// Writes a standard Number Card computing `function` over
+    /// `doctype_name`. Every function except Count aggregates a numeric
+    /// `field`.
+    pub fn generate_number_card(
+        &self,
+        name: &str,
+        doctype_name: &str,
+        function: &str,
+        field: Option<&str>,
+    ) -> Result<Vec<PathBuf>, String> {
+        const NUMERIC_FIELDTYPES: &[&str] = &["Int", "Float", "Currency", "Percent"];
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Number Card name is required".to_string());
+        }
+        let function = NUMBER_CARD_FUNCTIONS
+            .iter()
+            .find(|f| f.eq_ignore_ascii_case(function.trim()))
+            .ok_or(format!(
+                "Unknown function '{}'; expected one of: {}",
+                function,
+                NUMBER_CARD_FUNCTIONS.join(", ")
+            ))?;
+
+        let (doctype, reference, record_dir) =
+            self.record_target(doctype_name, "Number Card", name)?;
+        let record_path = record_dir.join(format!("{}.json", doctype_folder_name(name)));
+        if record_path.exists() {
+            return Err(format!("{} already exists", record_path.display()));
+        }
+
+        let based_on = match (*function, field) {
+            ("Count", None) => None,
+            ("Count", Some(_)) => return Err("--field doesn't apply to Count".to_string()),
+            (_, None) => {
+                return Err(format!(
+                    "{} needs --field <fieldname> naming a numeric field",
+                    function
+                ))
+            }
+            (_, Some(fieldname)) => {
+                let field = doctype
+                    .fields
+                    .iter()
+                    .find(|f| f.fieldname == fieldname)
+                    .ok_or(format!("{} has no field '{}'", reference, fieldname))?;
+                if !NUMERIC_FIELDTYPES.contains(&field.fieldtype.as_str()) {
+                    return Err(format!(
+                        "{} is a {} field; {} needs one of: {}",
+                        fieldname,
+                        field.fieldtype,
+                        function,
+                        NUMERIC_FIELDTYPES.join(", ")
+                    ));
+                }
+                Some(field.fieldname.clone())
+            }
+        };
+
+        let record = serde_json::json!({
+            "aggregate_function_based_on": based_on,
+            "doctype": "Number Card",
+            "document_type": reference,
+            "dynamic_filters_json": "[]",
+            "filters_json": "[]",
+            "function": function,
+            "is_public": 1,
+            "is_standard": 1,
+            "label": name,
+            "module": doctype.module,
+            "name": name,
+            "show_percentage_stats": 1,
+            "stats_time_interval": "Daily",
+            "type": "Document Type",
+        });
+
+        fs::create_dir_all(&record_dir)
+            .map_err(|e| format!("Could not create {}: {}", record_dir.display(), e))?;
+        fs::write(&record_path, to_frappe_json(&record)?)
+            .map_err(|e| format!("Could not write {}: {}", record_path.display(), e))?;
+
+        Ok(vec![record_path])
+    }
+
+    /// Writes a standard Dashboard linking existing Dashboard Charts and
+    /// Number Cards. It's exported to the module of the first chart (or card)
+    /// it references.
+    pub fn generate_dashboard(
+        &self,
+        name: &str,
+        charts: &[String],
+        cards: &[String],
+    ) -> Result<Vec<PathBuf>, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Dashboard name is required".to_string());
+        }
+        if charts.is_empty() && cards.is_empty() {
+            return Err("A Dashboard needs at least one --charts or --cards entry".to_string());
+        }
+
+        let resolve = |record_doctype: &str, wanted: &[String]| {
+            let available = self.module_records(record_doctype);
+            wanted
+                .iter()
+                .map(|name| {
+                    available
+                        .iter()
+                        .find(|record| record.name == *name)
+                        .cloned()
+                        .ok_or(format!("{} '{}' not found", record_doctype, name))
+                })
+                .collect::<Result<Vec<_>, String>>()
+        };
+        let charts = resolve("Dashboard Chart", charts)?;
+        let cards = resolve("Number Card", cards)?;
+
+        let home = charts
+            .first()
+            .or(cards.first())
+            .ok_or("A Dashboard needs at least one chart or card")?;
+        let stem = doctype_folder_name(name);
+        let record_dir = home.module_dir.join("dashboard").join(&stem);
+        let record_path = record_dir.join(format!("{}.json", stem));
+        if record_path.exists() {
+            return Err(format!("{} already exists", record_path.display()));
+        }
+
+        let link = |index: usize, key: &str, value: &str, link_doctype: &str, parentfield: &str| {
+            serde_json::json!({
+                key: value,
+                "doctype": link_doctype,
+                "idx": index + 1,
+                "parent": name,
+                "parentfield": parentfield,
+                "parenttype": "Dashboard",
+            })
+        };
+        let chart_links: Vec<serde_json::Value> = charts
+            .iter()
+            .enumerate()
+            .map(|(i, chart)| {
+                let mut row = link(i, "chart", &chart.name, "Dashboard Chart Link", "charts");
+                row["width"] = "Half".into();
+                row
+            })
+            .collect();
+        let card_links: Vec<serde_json::Value> = cards
+            .iter()
+            .enumerate()
+            .map(|(i, card)| link(i, "card", &card.name, "Number Card Link", "cards"))
+            .collect();
+        let record = serde_json::json!({
+            "cards": card_links,
+            "charts": chart_links,
+            "dashboard_name": name,
+            "doctype": "Dashboard",
+            "is_default": 0,
+            "is_standard": 1,
+            "module": home.module,
+            "name": name,
+        });
+
+        fs::create_dir_all(&record_dir)
+            .map_err(|e| format!("Could not create {}: {}", record_dir.display(), e))?;
+        fs::write(&record_path, to_frappe_json(&record)?)
+            .map_err(|e| format!("Could not write {}: {}", record_path.display(), e))?;
+
+        Ok(vec![record_path])
+    }
+
+    /// Adds an Email Template to `<app>/fixtures/email_template.json`. Without
+    /// `app_name` the template goes to the only editable app besides frappe.
+    pub fn generate_email_template(
+        &self,
+        name: &str,
+        app_name: Option<&str>,
+    ) -> Result<GeneratedFixture, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Email Template name is required".to_string());
+        }
+
+        self.add_fixture(
+            app_name,
+            serde_json::json!({
+                "doctype": "Email Template",
+                "name": name,
+                "response": "<p>Hello {{ doc.owner }},</p>\n\n<p></p>\n",
+                "response_html": null,
+                "subject": name,
+                "use_html": 0,
+            }),
+        )
+    }
+
+    /// Adds a Role to `<app>/fixtures/role.json`, refusing names already used
+    /// in DocType permissions, in any app's role fixtures or by Frappe itself.
+    pub fn generate_role(
+        &self,
+        name: &str,
+        desk_access: bool,
+        app_name: Option<&str>,
+    ) -> Result<GeneratedFixture, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Role name is required".to_string());
+        }
+        if let Some(existing) = self
+            .known_roles()
+            .into_iter()
+            .find(|role| role.eq_ignore_ascii_case(name))
+        {
+            return Err(format!("Role '{}' already exists", existing));
+        }
+
+        self.add_fixture(
+            app_name,
+            serde_json::json!({
+                "doctype": "Role",
+                "name": name,
+                "role_name": name,
+                "desk_access": i32::from(desk_access),
+                "is_custom": 1,
+            }),
+        )
+    }
+
+    /// Adds a Role Profile granting `roles` to `<app>/fixtures/role_profile.json`.
+    /// Also returns the roles that aren't known anywhere in the bench, which
+    /// usually means a typo or a role that still has to be created.
+    pub fn generate_role_profile(
+        &self,
+        name: &str,
+        roles: &[String],
+        app_name: Option<&str>,
+    ) -> Result<(GeneratedFixture, Vec<String>), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Role Profile name is required".to_string());
+        }
+        let mut roles: Vec<&str> = roles
+            .iter()
+            .map(|role| role.trim())
+            .filter(|role| !role.is_empty())
+            .collect();
+        let mut seen = HashSet::new();
+        roles.retain(|role| seen.insert(*role));
+        if roles.is_empty() {
+            return Err("At least one role is required".to_string());
+        }
+
+        let known = self.known_roles();
+        let unknown: Vec<String> = roles
+            .iter()
+            .filter(|role| !known.contains(**role))
+            .map(|role| role.to_string())
+            .collect();
+        let rows: Vec<serde_json::Value> = roles
+            .iter()
+            .map(|role| {
+                serde_json::json!({
+                    "doctype": "Has Role",
+                    "parentfield": "roles",
+                    "parenttype": "Role Profile",
+                    "role": role,
+                })
+            })
+            .collect();
+
+        let generated = self.add_fixture(
+            app_name,
+            serde_json::json!({
+                "doctype": "Role Profile",
+                "name": name,
+                "role_profile": name,
+                "roles": rows,
+            }),
+        )?;
+        Ok((generated, unknown))
+    }
+
+    /// Role names from DocType permissions, every app's `fixtures/role.json`
+    /// and Frappe's built-in roles.
+    fn known_roles(&self) -> HashSet<String> {
+        let mut roles: HashSet<String> = STANDARD_ROLES.iter().map(|r| r.to_string()).collect();
+        let Some(project) = &self.project else {
+            return roles;
+        };
+        for app in &project.apps {
+            roles.extend(
+                app.doctypes
+                    .iter()
+                    .flat_map(|dt| dt.permissions.iter())
+                    .map(|perm| perm.role.clone()),
+            );
+            let fixture = app.module_path.join("fixtures").join("role.json");
+            if let Ok(serde_json::Value::Array(records)) = read_json(&fixture) {
+                roles.extend(
+                    records
+                        .iter()
+                        .filter_map(|record| record.get("name")?.as_str())
+                        .map(|name| name.to_string()),
+                );
+            }
+        }
+        roles
+    }
+
+    /// Appends `record` to `<app>/fixtures/<record doctype>.json`. Without
+    /// `app_name` the record goes to the only editable app besides frappe.
+    fn add_fixture(
+        &self,
+        app_name: Option<&str>,
+        record: serde_json::Value,
+    ) -> Result<GeneratedFixture, String> {
+        let record_doctype = record["doctype"]
+            .as_str()
+            .ok_or("Fixture record has no doctype")?
+            .to_string();
+        let name = record["name"].as_str().unwrap_or_default().to_string();
+
+        let apps: Vec<&FrappeApp> = self
+            .get_project()
+            .map(|project| project.apps.iter().filter(|app| app.editable).collect())
+            .unwrap_or_default();
+        let app = match app_name {
+            Some(app_name) => apps
+                .iter()
+                .find(|app| app.name == app_name)
+                .ok_or(format!("App '{}' not found or not editable", app_name))?,
+            None => match apps
+                .iter()
+                .filter(|app| app.name != "frappe")
+                .collect::<Vec<_>>()
+                .as_slice()
+            {
+                [app] => *app,
+                [] => return Err(format!("No editable app to add the {} to", record_doctype)),
+                _ => return Err("Several apps found; pick one with --app <app>".to_string()),
+            },
+        };
+
+        let path = app
+            .module_path
+            .join("fixtures")
+            .join(format!("{}.json", doctype_folder_name(&record_doctype)));
+        let mut records = if path.exists() {
+            match read_json(&path)? {
+                serde_json::Value::Array(records) => records,
+                _ => return Err(format!("{} is not a JSON list", path.display())),
+            }
+        } else {
+            Vec::new()
+        };
+        if records
+            .iter()
+            .any(|existing| existing.get("name").and_then(|v| v.as_str()) == Some(&name))
+        {
+            return Err(format!("{} already has '{}'", path.display(), name));
+        }
+        records.push(record);
+
+        let fixtures_dir = path.parent().ok_or("Fixtures path has no parent")?;
+        fs::create_dir_all(fixtures_dir)
+            .map_err(|e| format!("Could not create {}: {}", fixtures_dir.display(), e))?;
+        write_atomically(&path, &to_frappe_json(&serde_json::Value::Array(records))?)?;
+
+        let in_fixtures = fs::read_to_string(&app.hooks_path)
+            .ok()
+            .and_then(|hooks| extract_hook_block(&hooks, "fixtures"))
+            .is_some_and(|(block, _)| block.contains(&format!("\"{}\"", record_doctype)));
+
+        Ok(GeneratedFixture { path, in_fixtures })
+    }
+
+    /// Standard records of `record_doctype` exported under any app's
+    /// modules, read from `<module>/<record doctype>/<record>/<record>.json`.
+    fn module_records(&self, record_doctype: &str) -> Vec<ModuleRecord> {
+        let Some(project) = &self.project else {
+            return Vec::new();
+        };
+        let folder = doctype_folder_name(record_doctype);
+        let subdirs = |dir: &Path| -> Vec<PathBuf> {
+            let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect();
+            dirs.sort();
+            dirs
+        };
+
+        let mut records = Vec::new();
+        for app in &project.apps {
+            for module_dir in subdirs(&app.module_path) {
+                for record_dir in subdirs(&module_dir.join(&folder)) {
+                    let Some(stem) = record_dir.file_name().and_then(|n| n.to_str()) else {
+                        continue;
+                    };
+                    let Ok(json) = read_json(&record_dir.join(format!("{}.json", stem))) else {
+                        continue;
+                    };
+                    if json.get("doctype").and_then(|v| v.as_str()) != Some(record_doctype) {
+                        continue;
+                    }
+                    let text = |key: &str| {
+                        json.get(key)
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string()
+                    };
+                    records.push(ModuleRecord {
+                        name: text("name"),
+                        module: text("module"),
+                        module_dir: module_dir.clone(),
+                    });
+                }
+            }
+        }
+        records
+    }
+
+    /// The DocType a record attaches to, its exact name, and the folder the
+    /// record is exported to: `<module>/<record doctype>/<record name>`.
+    fn record_target(
+        &self,
+        doctype_name: &str,
+        record_doctype: &str,
+        record_name: &str,
+    ) -> Result<(&DocTypeInfo, String, PathBuf), String> {
+        let doctype = self
+            .find_doctype(doctype_name)
+            .ok_or(format!("DocType '{}' not found", doctype_name))?;
+        let reference = read_json(&doctype.file_path)?
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| doctype.name.clone());
+
+        // <module>/doctype/<doctype>/<doctype>.json
+        let module_dir = doctype
+            .file_path
+            .ancestors()
+            .nth(3)
+            .ok_or("Could not locate the DocType's module folder")?;
+        let record_dir = module_dir
+            .join(doctype_folder_name(record_doctype))
+            .join(doctype_folder_name(record_name));
+
+        Ok((doctype, reference, record_dir))
+    }
+}
+
+/// Removes timestamps and owners, plus each child row's identity and parent
+/// links, so the JSON can be saved as a different document.
+fn strip_instance_metadata(value: &mut serde_json::Value, is_row: bool) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for key in ["creation", "modified", "modified_by", "owner"] {
+                map.remove(key);
+            }
+            // Child rows carry their own identity and parent links
+            if is_row {
+                for key in ["name", "parent", "parentfield", "parenttype"] {
+                    map.remove(key);
+                }
+            }
+            for child in map.values_mut() {
+                strip_instance_metadata(child, false);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                strip_instance_metadata(item, true);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// One CSV row per field (doctype, fieldname, fieldtype, label, reqd,
+/// options) under a header row, for reviewing DocTypes in a spreadsheet.
+pub fn fields_to_csv(doctypes: &[&DocTypeInfo]) -> String {
+    let mut csv = String::from("doctype,fieldname,fieldtype,label,reqd,options\r\n");
+    for dt in doctypes {
+        for field in &dt.fields {
+            let reqd = if field.reqd == Some(1) { "1" } else { "0" };
+            let row = [
+                dt.name.as_str(),
+                &field.fieldname,
+                &field.fieldtype,
+                &field.label,
+                reqd,
+                field.options.as_deref().unwrap_or(""),
+            ];
+            let cells: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+            csv.push_str(&cells.join(","));
+            csv.push_str("\r\n");
+        }
+    }
+    csv
+}
+
+/// Quotes a CSV cell (RFC 4180) when it holds a comma, quote or line break,
+/// doubling any embedded quotes.
+fn csv_cell(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Escapes text for a Markdown table cell; newlines (Select options) become
+/// `<br>`.
+fn markdown_cell(text: &str) -> String {
+    text.trim().replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn is_python_identifier(name: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield",
+    ];
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&name)
+}
+
+/// Writes through a sibling temp file so a crash can't leave `path` half
+/// written.
+fn write_atomically(path: &Path, content: &str) -> Result<(), String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let temp_path = path.with_extension(format!("{}.tmp", extension));
+    fs::write(&temp_path, content)
+        .map_err(|e| format!("Could not write {}: {}", temp_path.display(), e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Could not replace {}: {}", path.display(), e))
+}
+
+/// Reads a JSON file as text, dropping a leading UTF-8 BOM (which serde_json
+/// rejects).
+fn read_json_text(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+
+    std::str::from_utf8(bytes)
+        .map(str::to_string)
+        .map_err(|e| format!("{} is not valid UTF-8: {}", path.display(), e))
+}
+
+fn read_json(path: &Path) -> Result<serde_json::Value, String> {
+    let content = read_json_text(path)?;
+    serde_json::from_str(&content).map_err(|_| format!("Invalid JSON in {}", path.display()))
+}
+
+/// The last `count` lines of a file, read backwards in blocks so large logs
+/// aren't loaded whole.
+fn tail_lines(path: &Path, count: usize) -> Result<Vec<String>, String> {
+    const BLOCK: u64 = 8192;
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let size = file
+        .metadata()
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?
+        .len();
+
+    let mut start = size;
+    let mut buffer = Vec::new();
+    // One extra newline, since the file usually ends with one
+    while start > 0 && buffer.iter().filter(|&&b| b == b'\n').count() <= count {
+        let offset = start.saturating_sub(BLOCK);
+        let mut block = vec![0; (start - offset) as usize];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut block))
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        block.extend_from_slice(&buffer);
+        buffer = block;
+        start = offset;
+    }
+
+    let text = String::from_utf8_lossy(&buffer);
+    let lines: Vec<&str> = text.lines().collect();
+    let skip = lines.len().saturating_sub(count);
+    Ok(lines[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+/// Frappe exports DocType JSON with sorted keys and a one-space indent.
+fn to_frappe_json(value: &serde_json::Value) -> Result<String, String> {
+    let mut buffer = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+    value
+        .serialize(&mut serializer)
+        .map_err(|e| format!("Could not serialize JSON: {}", e))?;
+    String::from_utf8(buffer).map_err(|e| e.to_string())
+}
+
+fn is_doctype_reference(field: &FieldInfo) -> bool {
+    matches!(
+        field.fieldtype.as_str(),
+        "Link" | "Table" | "Table MultiSelect"
+    )
+}
+
+/// Python controller class name for a DocType ("Sales Invoice" -> "SalesInvoice").
+pub fn doctype_class_name(name: &str) -> String {
+    name.split([' ', '-', '_'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Interprets a command-line value the way `bench set-config` users expect:
+/// booleans, numbers and JSON literals keep their type, anything else is a string.
+pub fn parse_config_value(raw: &str) -> serde_json::Value {
+    let raw = raw.trim();
+    match raw {
+        "true" => return serde_json::Value::Bool(true),
+        "false" => return serde_json::Value::Bool(false),
+        _ => {}
+    }
+    if let Ok(number) = raw.parse::<i64>() {
+        return serde_json::Value::from(number);
+    }
+    if let Ok(number) = raw.parse::<f64>() {
+        return serde_json::Value::from(number);
+    }
+    if raw.starts_with('{') || raw.starts_with('[') {
+        if let Ok(value) = serde_json::from_str(raw) {
+            return value;
+        }
+    }
+    serde_json::Value::String(raw.to_string())
+}
+
+/// Site config keys whose values shouldn't be echoed into the editor.
+pub fn is_sensitive_config_key(key: &str) -> bool {
+    matches!(key, "db_password" | "encryption_key" | "admin_password")
+        || key.ends_with("_password")
+        || key.contains("secret")
+}
+
+pub fn diff_site_configs(
+    left: &serde_json::Map<String, serde_json::Value>,
+    right: &serde_json::Map<String, serde_json::Value>,
+) -> SiteConfigDiff {
+    let mut diff = SiteConfigDiff::default();
+    for (key, value) in left {
+        match right.get(key) {
+            None => diff.only_left.push(key.clone()),
+            Some(other) if other != value => diff.changed.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.only_right = right
+        .keys()
+        .filter(|key| !left.contains_key(*key))
+        .cloned()
+        .collect();
+
+    diff.only_left.sort();
+    diff.only_right.sort();
+    diff.changed.sort();
+    diff
+}
+
+/// Roles can repeat per permlevel; only the first rule for each role is kept.
+fn first_rule_per_role(doctype: &DocTypeInfo) -> Vec<&PermissionInfo> {
+    let mut rules: Vec<&PermissionInfo> = Vec::new();
+    for perm in &doctype.permissions {
+        if !rules.iter().any(|p| p.role == perm.role) {
+            rules.push(perm);
+        }
+    }
+    rules
+}
+
+/// Fields only in `left`, only in `right`, and in both with a different
+/// fieldtype, options or reqd.
+fn diff_fields(
+    left: &[FieldInfo],
+    right: &[FieldInfo],
+) -> (Vec<FieldInfo>, Vec<FieldInfo>, Vec<FieldDifference>) {
+    let left_fields: HashMap<&str, &FieldInfo> =
+        left.iter().map(|f| (f.fieldname.as_str(), f)).collect();
+    let right_fields: HashMap<&str, &FieldInfo> =
+        right.iter().map(|f| (f.fieldname.as_str(), f)).collect();
+
+    let mut changed_fields = Vec::new();
+    for field in left {
+        let Some(other) = right_fields.get(field.fieldname.as_str()) else {
+            continue;
+        };
+        let mut differences = Vec::new();
+        if field.fieldtype != other.fieldtype {
+            differences.push(format!(
+                "fieldtype: {} → {}",
+                field.fieldtype, other.fieldtype
+            ));
+        }
+        if field.options != other.options {
+            differences.push(format!(
+                "options: {} → {}",
+                field.options.as_deref().unwrap_or("-"),
+                other.options.as_deref().unwrap_or("-")
+            ));
+        }
+        if field.reqd.unwrap_or(0) != other.reqd.unwrap_or(0) {
+            differences.push(format!(
+                "reqd: {} → {}",
+                field.reqd.unwrap_or(0),
+                other.reqd.unwrap_or(0)
+            ));
+        }
+        if !differences.is_empty() {
+            changed_fields.push(FieldDifference {
+                fieldname: field.fieldname.clone(),
+                differences,
+            });
+        }
+    }
+
+    let only_left = left
+        .iter()
+        .filter(|f| !right_fields.contains_key(f.fieldname.as_str()))
+        .cloned()
+        .collect();
+    let only_right = right
+        .iter()
+        .filter(|f| !left_fields.contains_key(f.fieldname.as_str()))
+        .cloned()
+        .collect();
+    (only_left, only_right, changed_fields)
+}
+
+/// Levenshtein distance, used to suggest names for typos.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b_chars.len()]
+}
+
+/// Structural problems in a hand-edited DocType JSON file, all of them rather
+/// than just the first. Fails only when the file can't be read or parsed.
+pub fn validate_doctype_json(path: &Path) -> Result<Vec<String>, String> {
+    let content = read_json_text(path)?;
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid JSON in {}: {}", path.display(), e))?;
+    let Some(object) = json.as_object() else {
+        return Ok(vec!["top level is not a JSON object".to_string()]);
+    };
+
+    let mut problems = Vec::new();
+    if object.get("doctype").and_then(|v| v.as_str()) != Some("DocType") {
+        problems.push("\"doctype\" must be \"DocType\"".to_string());
+    }
+    for key in ["name", "module"] {
+        if object
+            .get(key)
+            .and_then(|v| v.as_str())
+            .is_none_or(|v| v.trim().is_empty())
+        {
+            problems.push(format!("missing \"{}\"", key));
+        }
+    }
+    let Some(fields) = object.get("fields").and_then(|v| v.as_array()) else {
+        problems.push("missing \"fields\" list".to_string());
+        return Ok(problems);
+    };
+
+    let mut seen = HashSet::new();
+    for (index, field) in fields.iter().enumerate() {
+        let text = |key: &str| field.get(key).and_then(|v| v.as_str()).unwrap_or("").trim();
+        let (fieldname, fieldtype) = (text("fieldname"), text("fieldtype"));
+        let label = if fieldname.is_empty() {
+            format!("field #{}", index + 1)
+        } else {
+            format!("field '{}'", fieldname)
+        };
+
+        if fieldname.is_empty() {
+            problems.push(format!("{} has no fieldname", label));
+        } else if !seen.insert(fieldname) {
+            problems.push(format!("duplicate fieldname '{}'", fieldname));
+        }
+        if fieldtype.is_empty() {
+            problems.push(format!("{} has no fieldtype", label));
+        }
+        let needs_options = matches!(fieldtype, "Link" | "Table" | "Table MultiSelect" | "Select");
+        if needs_options && text("options").is_empty() {
+            problems.push(format!(
+                "{} is a {} field without options",
+                label, fieldtype
+            ));
+        }
+    }
+
+    if let Some(order) = object.get("field_order").and_then(|v| v.as_array()) {
+        for entry in order {
+            match entry.as_str() {
+                Some(name) if seen.contains(name) => {}
+                Some(name) => problems.push(format!(
+                    "field_order lists '{}', which is not in fields",
+                    name
+                )),
+                None => problems.push(format!("field_order entry {} is not a string", entry)),
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Folder/file stem Frappe uses for a DocType name ("Sales Invoice" -> "sales_invoice").
+pub fn doctype_folder_name(name: &str) -> String {
+    name.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+/// Source of a top-level `name = ...` assignment in hooks.py, with its 1-based
+/// starting line. Brackets are matched so multi-line dicts/lists are captured.
+pub fn extract_hook_block(content: &str, name: &str) -> Option<(String, usize)> {
+    let assign_re = Regex::new(&format!(r"(?m)^{}\s*=\s*", regex::escape(name))).ok()?;
+    let found = assign_re.find(content)?;
+    let start = found.end();
+    let line = content[..start].matches('\n').count() + 1;
+
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut in_comment = false;
+    let mut end = content.len();
+
+    for (offset, c) in content[start..].char_indices() {
+        if in_comment {
+            if c != '\n' {
+                continue;
+            }
+            in_comment = false;
+        }
+
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '#' => in_comment = true,
+                '"' | '\'' => quote = Some(c),
+                '{' | '[' | '(' => depth += 1,
+                '}' | ']' | ')' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        end = start + offset + 1;
+                        break;
+                    }
+                }
+                '\n' if depth == 0 => {
+                    end = start + offset;
+                    break;
+                }
+                _ => {}
+            },
+        }
+    }
+
+    Some((content[start..end].to_string(), line))
+}
+
+/// All quoted string literals in a hook assignment.
+pub fn extract_hook_strings(content: &str, name: &str) -> Option<Vec<HookReference>> {
+    let (block, start_line) = extract_hook_block(content, name)?;
+    let string_re = Regex::new(r#""([^"\n]*)"|'([^'\n]*)'"#).ok()?;
+
+    Some(
+        string_re
+            .captures_iter(&block)
+            .filter_map(|captures| {
+                let value = captures.get(1).or(captures.get(2))?;
+                Some(HookReference {
+                    value: value.as_str().to_string(),
+                    line: start_line + block[..value.start()].matches('\n').count(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Searches files with the given extensions under `roots` for `pattern`.
+/// Uses ripgrep when it's installed and the built-in scanner otherwise; both
+/// skip hidden folders, `node_modules`, `__pycache__` and binary files.
+pub fn search_text(
+    roots: &[PathBuf],
+    pattern: &str,
+    extensions: &[&str],
+) -> Result<Vec<TextMatch>, String> {
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+    let roots: Vec<&PathBuf> = roots.iter().filter(|root| root.is_dir()).collect();
+    if roots.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut matches = ripgrep_search(&roots, pattern, extensions)
+        .unwrap_or_else(|| scan_text(&roots, &regex, extensions));
+    matches.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+    Ok(matches)
+}
+
+fn ripgrep_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        Command::new("rg")
+            .arg("--version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    })
+}
+
+/// None when ripgrep is missing or fails, so the caller can fall back.
+fn ripgrep_search(
+    roots: &[&PathBuf],
+    pattern: &str,
+    extensions: &[&str],
+) -> Option<Vec<TextMatch>> {
+    if !ripgrep_available() {
+        return None;
+    }
+
+    let mut command = Command::new("rg");
+    command.args([
+        "--json",
+        "--glob",
+        "!node_modules",
+        "--glob",
+        "!__pycache__",
+    ]);
+    for extension in extensions {
+        command.args(["--glob", &format!("*.{}", extension)]);
+    }
+    command.arg("--regexp").arg(pattern).args(roots);
+
+    let output = command.output().ok()?;
+    // 1 means no matches; 2 is an error, possibly with partial results
+    if output.status.code() == Some(2) {
+        return None;
+    }
+    Some(parse_ripgrep_json(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Match records from `rg --json` output. A record's `lines` can span several
+/// lines, so each submatch's line is counted from its byte offset. Paths or
+/// lines that aren't UTF-8 arrive base64-encoded as `bytes` and are skipped,
+/// as are the summary records ripgrep emits for binary files.
+fn parse_ripgrep_json(output: &str) -> Vec<TextMatch> {
+    let mut matches = Vec::new();
+
+    for record in output.lines() {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(record) else {
+            continue;
+        };
+        if value.get("type").and_then(|v| v.as_str()) != Some("match") {
+            continue;
+        }
+        let data = &value["data"];
+        let (Some(path), Some(lines), Some(first_line)) = (
+            data["path"]["text"].as_str(),
+            data["lines"]["text"].as_str(),
+            data["line_number"].as_u64(),
+        ) else {
+            continue;
+        };
+
+        for submatch in data["submatches"].as_array().into_iter().flatten() {
+            let Some(start) = submatch["start"].as_u64().map(|s| s as usize) else {
+                continue;
+            };
+            let Some(before) = lines.get(..start) else {
+                continue;
+            };
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            let line_end = lines[start..].find('\n').map_or(lines.len(), |i| start + i);
+
+            matches.push(TextMatch {
+                path: PathBuf::from(path),
+                line: first_line as usize + before.matches('\n').count(),
+                column: start - line_start + 1,
+                text: lines[line_start..line_end]
+                    .trim_end_matches('\r')
+                    .to_string(),
+            });
+        }
+    }
+
+    matches
+}
+
+fn scan_text(roots: &[&PathBuf], regex: &Regex, extensions: &[&str]) -> Vec<TextMatch> {
+    let mut matches = Vec::new();
+    let mut pending: Vec<PathBuf> = roots.iter().map(|root| root.to_path_buf()).collect();
+    let mut visited = HashSet::new();
+
+    while let Some(current) = pending.pop() {
+        if !first_visit(&mut visited, &current) {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if path.is_dir() {
+                if !(name.starts_with('.') || name == "__pycache__" || name == "node_modules") {
+                    pending.push(path);
+                }
+                continue;
+            }
+            let wanted = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| extensions.contains(&e));
+            if !wanted {
+                continue;
+            }
+
+            let Ok(bytes) = fs::read(&path) else {
+                continue;
+            };
+            // Same heuristic as ripgrep: a NUL byte means binary
+            if bytes.contains(&0) {
+                continue;
+            }
+            let content = String::from_utf8_lossy(&bytes);
+            for (idx, line) in content.lines().enumerate() {
+                for found in regex.find_iter(line) {
+                    matches.push(TextMatch {
+                        path: path.clone(),
+                        line: idx + 1,
+                        column: found.start() + 1,
+                        text: line.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+/// Best-effort conversion of a literal Python list/dict to JSON. Returns None
+/// for anything that doesn't survive the round trip.
+fn python_literal_to_json(source: &str) -> Option<serde_json::Value> {
+    let without_comments: String = source
+        .lines()
+        .map(strip_python_comment)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // Token by token, so quoted text is never mistaken for a keyword or a
+    // trailing comma
+    let mut converted = String::new();
+    let mut chars = without_comments.chars().peekable();
+    let mut pending_comma = false;
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            converted.push(c);
+            continue;
+        }
+        if std::mem::take(&mut pending_comma) && c != ']' && c != '}' {
+            converted.push(',');
+        }
+        match c {
+            ',' => pending_comma = true,
+            '\'' | '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next()? {
+                        '\\' => text.push(match chars.next()? {
+                            'n' => '\n',
+                            't' => '\t',
+                            escaped => escaped,
+                        }),
+                        quote if quote == c => break,
+                        other => text.push(other),
+                    }
+                }
+                converted.push_str(&serde_json::to_string(&text).ok()?);
+            }
+            _ if c.is_alphanumeric() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_' || next == '.') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                converted.push_str(match word.as_str() {
+                    "True" => "true",
+                    "False" => "false",
+                    "None" => "null",
+                    _ => &word,
+                });
+            }
+            _ => converted.push(c),
+        }
+    }
+
+    serde_json::from_str(&converted).ok()
+}
+
+fn strip_python_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (idx, c) in line.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..idx],
+            None => {}
+        }
+    }
+    line
+}
+
+fn is_dotted_path(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('.').collect();
+    parts.len() > 1
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+                && !part.starts_with(|c: char| c.is_ascii_digit())
+        })
+}
+
+fn find_file_named(dir: &Path, file_name: &str) -> bool {
+    find_file_named_in(dir, file_name, &mut HashSet::new())
+}
+
+fn find_file_named_in(dir: &Path, file_name: &str, visited: &mut HashSet<PathBuf>) -> bool {
+    if !first_visit(visited, dir) {
+        return false;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            find_file_named_in(&path, file_name, visited)
+        } else {
+            entry.file_name().to_str() == Some(file_name)
+        }
+    })
+}
+
+/// Symlinked app checkouts are reported at their real location so paths
+/// in results match what the editor has open.
+fn resolve_symlink(path: PathBuf) -> PathBuf {
+    match fs::symlink_metadata(&path) {
+        Ok(meta) if meta.file_type().is_symlink() => fs::canonicalize(&path).unwrap_or(path),
+        _ => path,
+    }
+}
+
+/// Records `dir` by its canonical path for recursive walks; false when it was
+/// already seen, which happens when a symlink points back up the tree.
+pub fn first_visit(visited: &mut HashSet<PathBuf>, dir: &Path) -> bool {
+    visited.insert(fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf()))
+}
+
+impl Default for FrappeAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn generate_field_suggestions(field_name: &str) -> Vec<(String, String)> {
+    let analyzer = FrappeAnalyzer::new();
+    let suggested_type = analyzer.suggest_field_type(field_name);
+
+    let mut suggestions = vec![(
+        suggested_type.clone(),
+        "Primary suggestion based on field name".to_string(),
+    )];
+
+    // Add common alternatives
+    match suggested_type.as_str() {
+        "Data" => {
+            suggestions.push((
+                "Small Text".to_string(),
+                "For longer text content".to_string(),
+            ));
+            suggestions.push(("Text".to_string(), "For very long text".to_string()));
+        }
+        "Link" => {
+            suggestions.push((
+                "Dynamic Link".to_string(),
+                "For variable DocType links".to_string(),
+            ));
+            suggestions.push(("Data".to_string(), "If not linking to DocType".to_string()));
+        }
+        "Float" => {
+            suggestions.push(("Currency".to_string(), "If represents money".to_string()));
+            suggestions.push(("Int".to_string(), "If whole numbers only".to_string()));
+        }
+        _ => {}
+    }
+
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("latte_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_app() {
+        let root = temp_dir("symlinked_app");
+        let checkout = temp_dir("symlinked_app_checkout");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::create_dir_all(root.join("apps")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let doctype_dir = checkout.join("shop/selling/doctype/shop_order");
+        fs::create_dir_all(&doctype_dir).unwrap();
+        fs::write(
+            doctype_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Selling", "fields": []}"#,
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(&checkout, root.join("apps/shop")).unwrap();
+        // A link back up the tree must not send walks into a loop
+        std::os::unix::fs::symlink(&checkout, checkout.join("shop/loop")).unwrap();
+        fs::write(checkout.join("shop/api.py"), "'Shop Order'\n").unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let app = &analyzer.get_project().unwrap().apps[0];
+        let real = fs::canonicalize(&checkout).unwrap();
+        assert_eq!(app.path, real);
+        assert!(app.doctypes[0].file_path.starts_with(&real));
+
+        let regex = Regex::new("Shop Order").unwrap();
+        let matches = scan_text(&[&app.module_path], &regex, &["py"]);
+        assert_eq!(matches.len(), 1);
+        assert!(!find_file_named(&app.module_path, "missing.js"));
+
+        fs::remove_dir_all(&root).unwrap();
+        fs::remove_dir_all(&checkout).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_directories_are_skipped() {
+        let root = temp_dir("skipped_paths");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let package = root.join("apps/shop/shop");
+        let order_dir = package.join("selling/doctype/shop_order");
+        fs::create_dir_all(&order_dir).unwrap();
+        fs::write(
+            order_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Selling", "fields": []}"#,
+        )
+        .unwrap();
+        // A `doctype` that can't be listed stands in for a permission-denied folder
+        fs::create_dir_all(package.join("stock")).unwrap();
+        fs::write(package.join("stock/doctype"), "").unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let project = analyzer.get_project().unwrap();
+        assert_eq!(project.apps[0].doctypes.len(), 1);
+        assert_eq!(project.skipped_paths.len(), 1);
+        assert_eq!(project.skipped_paths[0].0, package.join("stock/doctype"));
+        assert!(!project.skipped_paths[0].1.is_empty());
+        let timings = project.timings;
+        assert!(timings.total >= timings.apps + timings.sites + timings.default_site);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_malformed_metadata_is_reported() {
+        let root = temp_dir("parse_errors");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let doctype_root = root.join("apps/shop/shop/selling/doctype");
+        for (folder, json) in [
+            (
+                "shop_order",
+                r#"{"doctype": "DocType", "name": "Shop Order", "fields": []}"#,
+            ),
+            (
+                "shop_item",
+                r#"{"doctype": "DocType", "name": "Shop Item", "fields": [}"#,
+            ),
+        ] {
+            fs::create_dir_all(doctype_root.join(folder)).unwrap();
+            fs::write(
+                doctype_root.join(folder).join(format!("{}.json", folder)),
+                json,
+            )
+            .unwrap();
+        }
+        // Compiled doctype/__init__.py lands here; it isn't a DocType
+        fs::create_dir_all(doctype_root.join("__pycache__")).unwrap();
+        let report_dir = root.join("apps/shop/shop/selling/report/daily_sales");
+        fs::create_dir_all(&report_dir).unwrap();
+        fs::write(report_dir.join("daily_sales.json"), "").unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let project = analyzer.get_project().unwrap();
+        assert_eq!(project.apps[0].doctypes.len(), 1);
+
+        let failed: Vec<&PathBuf> = project.parse_errors.iter().map(|(path, _)| path).collect();
+        assert_eq!(
+            failed,
+            vec![
+                &doctype_root.join("shop_item/shop_item.json"),
+                &report_dir.join("daily_sales.json"),
+            ]
+        );
+        assert!(project.parse_errors[0]
+            .1
+            .starts_with("Invalid JSON format: expected value at line 1"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_discover_many_apps_in_parallel() {
+        let root = temp_dir("many_apps");
+        let app_names: Vec<String> = (0..24).rev().map(|i| format!("app_{:02}", i)).collect();
+
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), app_names.join("\n")).unwrap();
+
+        for app_name in &app_names {
+            let doctype_dir = root
+                .join("apps")
+                .join(app_name)
+                .join(app_name)
+                .join("core")
+                .join("doctype")
+                .join("thing");
+            fs::create_dir_all(&doctype_dir).unwrap();
+            fs::write(
+                doctype_dir.join("thing.json"),
+                r#"{"doctype": "DocType", "module": "Core", "fields": []}"#,
+            )
+            .unwrap();
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let apps = &analyzer.get_project().unwrap().apps;
+
+        let mut expected = app_names.clone();
+        expected.sort();
+        let names: Vec<String> = apps.iter().map(|app| app.name.clone()).collect();
+        assert_eq!(names, expected);
+        assert!(apps.iter().all(|app| app.doctypes.len() == 1));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_discover_app_installed_in_env() {
+        let root = temp_dir("env_apps");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop\nmissing").unwrap();
+
+        let package = root.join("env/lib/python3.11/site-packages/shop");
+        let doctype_dir = package.join("selling/doctype/shop_item");
+        fs::create_dir_all(&doctype_dir).unwrap();
+        fs::write(package.join("hooks.py"), "app_name = \"shop\"\n").unwrap();
+        fs::write(
+            doctype_dir.join("shop_item.json"),
+            r#"{"doctype": "DocType", "name": "Shop Item", "module": "Selling", "fields": []}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let apps = &analyzer.get_project().unwrap().apps;
+
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].name, "shop");
+        assert!(!apps[0].editable);
+        assert_eq!(apps[0].module_path, package);
+        assert_eq!(apps[0].doctypes.len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_missing_init_files() {
+        let root = temp_dir("missing_init");
+        let package = root.join("shop/shop");
+        let item_dir = package.join("selling/doctype/shop_item");
+        let report_dir = package.join("selling/report/sales_summary");
+        fs::create_dir_all(&item_dir).unwrap();
+        fs::create_dir_all(&report_dir).unwrap();
+        fs::create_dir_all(package.join("public/js")).unwrap();
+        for dir in [
+            package.clone(),
+            package.join("selling"),
+            package.join("selling/doctype"),
+            item_dir.clone(),
+            package.join("selling/report"),
+        ] {
+            fs::write(dir.join("__init__.py"), "").unwrap();
+        }
+
+        let analyzer = FrappeAnalyzer::new();
+        assert_eq!(
+            analyzer.missing_init_files(&root.join("shop"), "shop"),
+            vec![report_dir.clone()]
+        );
+
+        fs::write(report_dir.join("__init__.py"), "").unwrap();
+        assert!(analyzer
+            .missing_init_files(&root.join("shop"), "shop")
+            .is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_stale_bytecode_and_assets() {
+        let root = temp_dir("stale_build_files");
+        let package = root.join("apps/shop/shop");
+        let cache = package.join("selling/__pycache__");
+        fs::create_dir_all(&cache).unwrap();
+        fs::write(package.join("selling/api.py"), "").unwrap();
+        fs::write(cache.join("api.cpython-311.pyc"), "kept").unwrap();
+        fs::write(cache.join("legacy.cpython-311.pyc"), "orphan").unwrap();
+        fs::write(package.join("old_utils.pyc"), "sourceless").unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        let orphans = analyzer.orphaned_bytecode(&root.join("apps/shop"));
+        assert_eq!(
+            orphans,
+            vec![
+                StaleFile {
+                    path: package.join("old_utils.pyc"),
+                    size: 10,
+                },
+                StaleFile {
+                    path: cache.join("legacy.cpython-311.pyc"),
+                    size: 6,
+                },
+            ]
+        );
+
+        // sites/assets/<app> is normally a symlink to the app's public folder
+        let dist = root.join("sites/assets/shop/dist/js");
+        fs::create_dir_all(&dist).unwrap();
+        fs::create_dir_all(package.join("public")).unwrap();
+        fs::write(package.join("public/shop.bundle.js"), "source").unwrap();
+        fs::write(dist.join("shop.bundle.ABC.js"), "built").unwrap();
+        fs::write(dist.join("pos.bundle.DEF.js"), "built").unwrap();
+        fs::write(package.join("public/pos.bundle.js"), "source").unwrap();
+        fs::write(
+            root.join("sites/assets/assets.json"),
+            r#"{"shop.bundle.js": "/assets/shop/dist/js/shop.bundle.ABC.js",
+                "pos.bundle.js": "/assets/shop/dist/js/pos.bundle.DEF.js"}"#,
+        )
+        .unwrap();
+        let hour_ago = std::time::SystemTime::now() - Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(dist.join("shop.bundle.ABC.js"))
+            .unwrap()
+            .set_modified(hour_ago)
+            .unwrap();
+        fs::File::options()
+            .write(true)
+            .open(package.join("public/pos.bundle.js"))
+            .unwrap()
+            .set_modified(hour_ago)
+            .unwrap();
+
+        let outdated = analyzer.outdated_assets(&root);
+        assert_eq!(outdated.len(), 1);
+        assert_eq!(
+            outdated[0].path,
+            root.join("sites/assets/shop/dist/js/shop.bundle.ABC.js")
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_procfile() {
+        let root = temp_dir("procfile");
+        fs::write(
+            root.join("Procfile"),
+            "redis_cache: redis-server config/redis_cache.conf\n\
+             web: bench serve --port 8000\n\
+             \n\
+             # comment\n\
+             socketio: /usr/bin/node apps/frappe/socketio.js\n\
+             watch: bench watch\n",
+        )
+        .unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        let entries = analyzer.parse_procfile(&root);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["redis_cache", "web", "socketio", "watch"]);
+        assert_eq!(entries[1].command, "bench serve --port 8000");
+        assert_eq!(entries[1].port, Some(8000));
+        assert_eq!(entries[2].port, None);
+        assert!(entries[3].dev_only());
+        assert!(analyzer.parse_procfile(&root.join("missing")).is_empty());
+
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(
+            root.join("sites/common_site_config.json"),
+            r#"{"webserver_port": 8000, "socketio_port": 9000,
+                "redis_cache": "redis://127.0.0.1:13000", "developer_mode": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(analyzer.bench_ports(&root), vec![8000, 9000, 13000]);
+
+        let endpoints = analyzer.service_endpoints(&root);
+        let summary: Vec<(&str, &str, u16)> = endpoints
+            .iter()
+            .map(|e| (e.name.as_str(), e.host.as_str(), e.port))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("redis_cache", "127.0.0.1", 13000),
+                ("mariadb", "127.0.0.1", 3306),
+                ("web", "127.0.0.1", 8000),
+            ]
+        );
+
+        assert_eq!(analyzer.site_url(&root, None), "http://localhost:8000");
+        assert_eq!(
+            analyzer.site_url(&root, Some("127.0.0.1")),
+            "http://127.0.0.1:8000"
+        );
+        assert_eq!(
+            analyzer.site_url(&root, Some("shop.invalid")),
+            "http://localhost:8000"
+        );
+        fs::remove_file(root.join("Procfile")).unwrap();
+        fs::write(
+            root.join("sites/common_site_config.json"),
+            r#"{"webserver_port": 8010}"#,
+        )
+        .unwrap();
+        assert_eq!(analyzer.web_port(&root), 8010);
+        assert_eq!(analyzer.site_url(&root, None), "http://localhost:8010");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_extract_hook_strings() {
+        let hooks = r#"app_name = "demo"
+app_include_js = "/assets/demo/js/demo.js"
+doc_events = {
+    "Sales Invoice": {
+        "on_submit": "demo.events.invoice.on_submit",
+        "on_cancel": ["demo.events.invoice.on_cancel"],
+    }
+}
+"#;
+
+        let js = extract_hook_strings(hooks, "app_include_js").unwrap();
+        assert_eq!(js[0].value, "/assets/demo/js/demo.js");
+        assert_eq!(js[0].line, 2);
+
+        let events: Vec<HookReference> = extract_hook_strings(hooks, "doc_events")
+            .unwrap()
+            .into_iter()
+            .filter(|r| is_dotted_path(&r.value))
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].value, "demo.events.invoice.on_submit");
+        assert_eq!(events[0].line, 5);
+        assert_eq!(events[1].line, 6);
+    }
+
+    #[test]
+    fn test_lint_hooks_flags_missing_targets() {
+        let root = temp_dir("lint_hooks");
+        let package = root.join("apps").join("demo").join("demo");
+        fs::create_dir_all(package.join("events")).unwrap();
+        fs::create_dir_all(package.join("public").join("js")).unwrap();
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "demo\n").unwrap();
+        fs::write(package.join("events").join("__init__.py"), "").unwrap();
+        fs::write(
+            package.join("events").join("invoice.py"),
+            "def on_submit(doc, method):\n    pass\n",
+        )
+        .unwrap();
+        fs::write(package.join("public").join("js").join("demo.js"), "").unwrap();
+        fs::write(
+            package.join("hooks.py"),
+            r#"app_include_js = ["/assets/demo/js/demo.js", "/assets/demo/js/missing.js"]
+doc_events = {
+    "Sales Invoice": {
+        "on_submit": "demo.events.invoice.on_submit",
+        "on_cancel": "demo.events.invoice.on_cancel",
+        "validate": "demo.events.nowhere.validate",
+        "on_update": "erpnext.controllers.on_update",
+    }
+}
+"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let app = analyzer.get_project().unwrap().apps[0].clone();
+        let issues = analyzer.lint_hooks(&app).unwrap();
+
+        let references: Vec<&str> = issues.iter().map(|i| i.reference.as_str()).collect();
+        assert_eq!(
+            references,
+            vec![
+                "demo.events.invoice.on_cancel",
+                "demo.events.nowhere.validate",
+                "/assets/demo/js/missing.js",
+            ]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_python_literal_to_json() {
+        let fixtures = r#"[
+    "Custom Field",
+    {"dt": 'Property Setter', "filters": [["module", "=", "Demo"]]},  # setters
+]"#;
+        let value = python_literal_to_json(fixtures).unwrap();
+        assert_eq!(value[0], "Custom Field");
+        assert_eq!(value[1]["dt"], "Property Setter");
+
+        // Keywords and commas inside strings are left alone
+        let value = python_literal_to_json(
+            r#"{"label": "None of the above", "hint": 'True, False,]', "it": "it's", "x": None, "on": True,}"#,
+        )
+        .unwrap();
+        assert_eq!(value["label"], "None of the above");
+        assert_eq!(value["hint"], "True, False,]");
+        assert_eq!(value["it"], "it's");
+        assert_eq!(value["x"], serde_json::Value::Null);
+        assert_eq!(value["on"], true);
+        assert_eq!(
+            python_literal_to_json("[1, 2.5, -3, NoneType]"),
+            None,
+            "unknown names aren't JSON"
+        );
+    }
+
+    #[test]
+    fn test_parse_doctype_with_mismatched_filename() {
+        let root = temp_dir("mismatched_doctype");
+        let doctype_dir = root.join("my_thing");
+        fs::create_dir_all(&doctype_dir).unwrap();
+        fs::write(
+            doctype_dir.join("mything.json"),
+            r#"{"doctype": "DocType", "name": "My Thing", "module": "Test",
+                "fields": [{"fieldname": "title", "fieldtype": "Data"}]}"#,
+        )
+        .unwrap();
+        fs::write(doctype_dir.join("mything.py"), "").unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        let doctype = analyzer.parse_doctype(&doctype_dir, "my_thing").unwrap();
+        assert_eq!(doctype.module, "Test");
+        assert_eq!(doctype.fields.len(), 1);
+        assert_eq!(doctype.file_path, doctype_dir.join("mything.json"));
+        assert_eq!(
+            doctype.controller_path,
+            Some(doctype_dir.join("mything.py"))
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_rename_doctype_rewrites_links() {
+        let root = temp_dir("rename_doctype");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let doctypes = root.join("apps/shop/shop/core/doctype");
+        let item_dir = doctypes.join("shop_item");
+        let order_dir = doctypes.join("shop_order");
+        fs::create_dir_all(&item_dir).unwrap();
+        fs::create_dir_all(&order_dir).unwrap();
+        fs::write(
+            item_dir.join("shop_item.json"),
+            r#"{"doctype": "DocType", "name": "Shop Item", "module": "Core", "fields": []}"#,
+        )
+        .unwrap();
+        fs::write(
+            item_dir.join("shop_item.py"),
+            "class ShopItem(Document):\n    pass\n",
+        )
+        .unwrap();
+        fs::write(
+            item_dir.join("test_shop_item.py"),
+            "class TestShopItem(FrappeTestCase):\n    pass\n",
+        )
+        .unwrap();
+        fs::write(
+            order_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Core", "fields": [
+                {"fieldname": "item", "fieldtype": "Link", "options": "Shop Item"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        assert!(analyzer.rename_doctype("Shop Item", "Shop Order").is_err());
+
+        // The move into an existing, non-empty folder fails last, after the
+        // contents and file names have changed
+        let plan = analyzer.rename_doctype("Shop Item", "Store Item").unwrap();
+        let blocker = doctypes.join("store_item");
+        fs::create_dir_all(&blocker).unwrap();
+        fs::write(blocker.join("other.txt"), "").unwrap();
+        let error = plan.apply().unwrap_err();
+        assert!(error.contains("rolled back"), "{}", error);
+        let json = fs::read_to_string(item_dir.join("shop_item.json")).unwrap();
+        assert_eq!(
+            json,
+            r#"{"doctype": "DocType", "name": "Shop Item", "module": "Core", "fields": []}"#
+        );
+        assert!(item_dir.join("test_shop_item.py").exists());
+        assert!(!item_dir.join("test_store_item.py").exists());
+        assert!(fs::read_to_string(order_dir.join("shop_order.json"))
+            .unwrap()
+            .contains(r#""options": "Shop Item""#));
+        fs::remove_dir_all(&blocker).unwrap();
+
+        let plan = analyzer.rename_doctype("Shop Item", "Store Item").unwrap();
+        assert_eq!(plan.changes.len(), 4);
+        plan.apply().unwrap();
+
+        let new_dir = doctypes.join("store_item");
+        assert!(!item_dir.exists());
+        let json = fs::read_to_string(new_dir.join("store_item.json")).unwrap();
+        assert!(json.contains(r#""name": "Store Item""#));
+        let controller = fs::read_to_string(new_dir.join("store_item.py")).unwrap();
+        assert!(controller.contains("class StoreItem(Document)"));
+        let test = fs::read_to_string(new_dir.join("test_store_item.py")).unwrap();
+        assert!(test.contains("class TestStoreItem(FrappeTestCase)"));
+        let order = fs::read_to_string(order_dir.join("shop_order.json")).unwrap();
+        assert!(order.contains(r#""options": "Store Item""#));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_rename_doctype_rewrites_self_links() {
+        let root = temp_dir("rename_self_link");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let doctypes = root.join("apps/shop/shop/core/doctype");
+        let territory_dir = doctypes.join("shop_territory");
+        fs::create_dir_all(&territory_dir).unwrap();
+        fs::write(
+            territory_dir.join("shop_territory.json"),
+            r#"{"doctype": "DocType", "name": "Shop Territory", "module": "Core", "is_tree": 1,
+                "nsm_parent_field": "parent_shop_territory", "fields": [
+                {"fieldname": "parent_shop_territory", "fieldtype": "Link", "options": "Shop Territory"},
+                {"fieldname": "old_parent", "fieldtype": "Link", "options": "Shop Territory"},
+                {"fieldname": "title", "fieldtype": "Data", "options": "Shop Territory"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        analyzer
+            .rename_doctype("Shop Territory", "Sales Region")
+            .unwrap()
+            .apply()
+            .unwrap();
+
+        let value = read_json(&doctypes.join("sales_region/sales_region.json")).unwrap();
+        let options: Vec<&str> = value["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["options"].as_str().unwrap())
+            .collect();
+        // Data fields don't reference DocTypes, so their options stay
+        assert_eq!(
+            options,
+            vec!["Sales Region", "Sales Region", "Shop Territory"]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_scheduler_events() {
+        let root = temp_dir("scheduler_events");
+        let hooks_path = root.join("hooks.py");
+        fs::write(
+            &hooks_path,
+            r#"
+scheduler_events = {
+    "daily": ["shop.tasks.daily_report"],
+    "cron": {
+        "0/15 * * * *": [
+            "shop.tasks.sync_stock",  # keep inventory fresh
+        ],
+    },
+    "hourly": ["shop.tasks.cleanup"],
+}
+"#,
+        )
+        .unwrap();
+
+        let app = FrappeApp {
+            name: "shop".to_string(),
+            path: root.clone(),
+            module_path: root.clone(),
+            hooks_path,
+            doctypes: Vec::new(),
+            pages: Vec::new(),
+            reports: Vec::new(),
+            editable: true,
+        };
+
+        let jobs = FrappeAnalyzer::new().scheduler_events(&app).unwrap();
+        let summary: Vec<(&str, Option<&str>, &str)> = jobs
+            .iter()
+            .map(|j| (j.frequency.as_str(), j.cron.as_deref(), j.method.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("hourly", None, "shop.tasks.cleanup"),
+                ("daily", None, "shop.tasks.daily_report"),
+                ("cron", Some("0/15 * * * *"), "shop.tasks.sync_stock"),
+            ]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_generate_server_script() {
+        let root = temp_dir("generate_script");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let module_dir = root.join("apps/shop/shop/selling");
+        let item_dir = module_dir.join("doctype/shop_item");
+        fs::create_dir_all(&item_dir).unwrap();
+        fs::write(
+            item_dir.join("shop_item.json"),
+            r#"{"doctype": "DocType", "name": "Shop Item", "module": "Selling", "fields": []}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        assert!(analyzer
+            .generate_script(ScriptKind::Server, "Check Stock", "Missing", None)
+            .is_err());
+
+        let path = analyzer
+            .generate_script(ScriptKind::Server, "Check Stock", "Shop Item", None)
+            .unwrap();
+        assert_eq!(
+            path,
+            module_dir.join("server_script/check_stock/check_stock.json")
+        );
+        let record = read_json(&path).unwrap();
+        assert_eq!(record["reference_doctype"], "Shop Item");
+        assert_eq!(record["module"], "Selling");
+        assert_eq!(record["script_type"], "DocType Event");
+
+        assert!(analyzer
+            .generate_script(ScriptKind::Server, "Check Stock", "Shop Item", None)
+            .is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_ripgrep_json() {
+        let output = [
+            r#"{"type":"begin","data":{"path":{"text":"app/a.py"}}}"#,
+            r#"{"type":"match","data":{"path":{"text":"app/a.py"},"lines":{"text":"x = 'Item'; y = \"Item\"\n"},"line_number":3,"absolute_offset":10,"submatches":[{"match":{"text":"'Item'"},"start":4,"end":10},{"match":{"text":"\"Item\""},"start":16,"end":22}]}}"#,
+            r#"{"type":"match","data":{"path":{"text":"app/b.js"},"lines":{"text":"frappe.get_doc(\n  \"Item\")\n"},"line_number":7,"absolute_offset":0,"submatches":[{"match":{"text":"\"Item\""},"start":18,"end":24}]}}"#,
+            r#"{"type":"match","data":{"path":{"bytes":"L3RtcC//"},"lines":{"text":"'Item'\n"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"'Item'"},"start":0,"end":6}]}}"#,
+            r#"{"type":"end","data":{"path":{"text":"app/c.json"},"binary_offset":12,"stats":{}}}"#,
+        ]
+        .join("\n");
+
+        let matches = parse_ripgrep_json(&output);
+        assert_eq!(matches.len(), 3);
+        assert_eq!((matches[0].line, matches[0].column), (3, 5));
+        assert_eq!((matches[1].line, matches[1].column), (3, 17));
+        assert_eq!(matches[1].text, "x = 'Item'; y = \"Item\"");
+        assert_eq!(matches[2].path, PathBuf::from("app/b.js"));
+        assert_eq!((matches[2].line, matches[2].column), (8, 3));
+        assert_eq!(matches[2].text, "  \"Item\")");
+    }
+
+    #[test]
+    fn test_scan_text_skips_binary_and_ignored_dirs() {
+        let root = temp_dir("scan_text");
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::write(root.join("a.py"), "one\nget_doc('Item', 'Item')\n").unwrap();
+        fs::write(root.join("b.js"), b"'Item'\0binary").unwrap();
+        fs::write(root.join("c.txt"), "'Item'").unwrap();
+        fs::write(root.join("node_modules").join("d.js"), "'Item'").unwrap();
+
+        let regex = Regex::new(r#"["']Item["']"#).unwrap();
+        let matches = scan_text(&[&root], &regex, &["py", "js"]);
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[0].line, matches[0].column), (2, 9));
+        assert_eq!(matches[1].column, 17);
+
+        // Whichever backend runs, results agree
+        let found = search_text(
+            std::slice::from_ref(&root),
+            r#"["']Item["']"#,
+            &["py", "js"],
+        )
+        .unwrap();
+        assert_eq!(found, matches);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_extract_translatable_strings() {
+        let root = temp_dir("translations");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let package = root.join("apps/shop/shop");
+        fs::create_dir_all(package.join("public/js")).unwrap();
+        fs::create_dir_all(package.join("node_modules")).unwrap();
+        fs::write(
+            package.join("api.py"),
+            "from frappe import _\n\nfrappe.throw(_(\"Out of stock\"))\nmsg = _('Item {0}').format(x) + _(\"Out of stock\")\nname_(\"not me\")\n",
+        )
+        .unwrap();
+        fs::write(
+            package.join("public/js/cart.js"),
+            "frappe.msgprint(__(\"Cart is empty\"));\n",
+        )
+        .unwrap();
+        fs::write(package.join("node_modules/lib.js"), "__(\"vendored\")\n").unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let strings = analyzer.extract_translatable_strings("shop").unwrap();
+        let texts: Vec<&str> = strings.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Out of stock", "Item {0}", "Cart is empty"]);
+        assert_eq!(
+            (strings[0].path.clone(), strings[0].line),
+            (package.join("api.py"), 3)
+        );
+        assert!(analyzer.extract_translatable_strings("missing").is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_generate_web_form() {
+        let root = temp_dir("generate_web_form");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let module_dir = root.join("apps/shop/shop/selling");
+        let order_dir = module_dir.join("doctype/shop_order");
+        fs::create_dir_all(&order_dir).unwrap();
+        fs::write(
+            order_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Selling",
+                "fields": [{"fieldname": "customer", "fieldtype": "Data", "label": "Customer", "reqd": 1},
+                           {"fieldname": "details_tab", "fieldtype": "Tab Break"},
+                           {"fieldname": "internal_note", "fieldtype": "Text", "hidden": 1},
+                           {"fieldname": "delivery_date", "fieldtype": "Date"}]}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        assert!(analyzer.generate_web_form("Order Form", "Missing").is_err());
+
+        let form = analyzer
+            .generate_web_form("Order Form", "Shop Order")
+            .unwrap();
+        assert_eq!(form.route, "/order-form");
+        assert_eq!(form.fields, 2);
+        let json_path = module_dir.join("web_form/order_form/order_form.json");
+        assert_eq!(form.files[0], json_path);
+
+        let json = read_json(&json_path).unwrap();
+        assert_eq!(json["doc_type"], "Shop Order");
+        assert_eq!(json["web_form_fields"][0]["fieldname"], "customer");
+        assert_eq!(json["web_form_fields"][0]["reqd"], 1);
+        assert_eq!(json["web_form_fields"][1]["fieldname"], "delivery_date");
+        assert!(analyzer
+            .generate_web_form("Order Form", "Shop Order")
+            .is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_generate_number_card_and_dashboard() {
+        let root = temp_dir("generate_dashboard");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let module_dir = root.join("apps/shop/shop/selling");
+        let order_dir = module_dir.join("doctype/shop_order");
+        fs::create_dir_all(&order_dir).unwrap();
+        fs::write(
+            order_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Selling",
+                "fields": [{"fieldname": "customer", "fieldtype": "Data"},
+                           {"fieldname": "grand_total", "fieldtype": "Currency"}]}"#,
+        )
+        .unwrap();
+        let chart_dir = module_dir.join("dashboard_chart/monthly_sales");
+        fs::create_dir_all(&chart_dir).unwrap();
+        fs::write(
+            chart_dir.join("monthly_sales.json"),
+            r#"{"doctype": "Dashboard Chart", "name": "Monthly Sales", "module": "Selling"}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        assert!(analyzer
+            .generate_number_card("Orders", "Shop Order", "Median", None)
+            .is_err());
+        assert!(analyzer
+            .generate_number_card("Revenue", "Shop Order", "Sum", None)
+            .is_err());
+        assert!(analyzer
+            .generate_number_card("Revenue", "Shop Order", "Sum", Some("customer"))
+            .is_err());
+
+        let files = analyzer
+            .generate_number_card("Revenue", "Shop Order", "sum", Some("grand_total"))
+            .unwrap();
+        let card_path = module_dir.join("number_card/revenue/revenue.json");
+        assert_eq!(files, vec![card_path.clone()]);
+        let card = read_json(&card_path).unwrap();
+        assert_eq!(card["function"], "Sum");
+        assert_eq!(card["aggregate_function_based_on"], "grand_total");
+        assert_eq!(card["document_type"], "Shop Order");
+        assert_eq!(card["module"], "Selling");
+
+        assert!(analyzer.generate_dashboard("Sales", &[], &[]).is_err());
+        assert!(analyzer
+            .generate_dashboard("Sales", &["Missing Chart".to_string()], &[])
+            .is_err());
+        let files = analyzer
+            .generate_dashboard(
+                "Sales",
+                &["Monthly Sales".to_string()],
+                &["Revenue".to_string()],
+            )
+            .unwrap();
+        assert_eq!(files, vec![module_dir.join("dashboard/sales/sales.json")]);
+        let dashboard = read_json(&files[0]).unwrap();
+        assert_eq!(dashboard["module"], "Selling");
+        assert_eq!(dashboard["charts"][0]["chart"], "Monthly Sales");
+        assert_eq!(dashboard["cards"][0]["card"], "Revenue");
+        assert_eq!(dashboard["cards"][0]["doctype"], "Number Card Link");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_generate_notification_and_fixtures() {
+        let root = temp_dir("generate_notification");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let module_dir = root.join("apps/shop/shop/selling");
+        let order_dir = module_dir.join("doctype/shop_order");
+        fs::create_dir_all(&order_dir).unwrap();
+        fs::write(
+            order_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Selling",
+                "fields": [{"fieldname": "customer", "fieldtype": "Data"},
+                           {"fieldname": "delivery_date", "fieldtype": "Date"}],
+                "permissions": [{"role": "Sales User", "read": 1}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("apps/shop/shop/hooks.py"),
+            "fixtures = [\"Email Template\"]\n",
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        assert!(analyzer
+            .generate_notification("Order Shipped", "Shop Order", "Submit", None)
+            .is_err());
+        assert!(analyzer
+            .generate_notification("Order Shipped", "Shop Order", "Sometimes", None)
+            .is_err());
+        assert!(analyzer
+            .generate_notification("Order Shipped", "Shop Order", "value change", None)
+            .is_err());
+
+        let files = analyzer
+            .generate_notification("Delivery Due", "Shop Order", "days before", None)
+            .unwrap();
+        let dir = module_dir.join("notification/delivery_due");
+        assert_eq!(
+            files,
+            vec![
+                dir.join("delivery_due.json"),
+                dir.join("delivery_due.md"),
+                dir.join("delivery_due.py"),
+            ]
+        );
+        let notification = read_json(&files[0]).unwrap();
+        assert_eq!(notification["event"], "Days Before");
+        assert_eq!(notification["date_changed"], "delivery_date");
+        assert_eq!(notification["document_type"], "Shop Order");
+        assert_eq!(notification["subject"], "{{ doc.name }}: Delivery Due");
+
+        let template = analyzer.generate_email_template("Welcome", None).unwrap();
+        assert!(template.in_fixtures);
+        assert_eq!(
+            template.path,
+            root.join("apps/shop/shop/fixtures/email_template.json")
+        );
+        analyzer
+            .generate_email_template("Goodbye", Some("shop"))
+            .unwrap();
+        assert!(analyzer.generate_email_template("Welcome", None).is_err());
+        let records = read_json(&template.path).unwrap();
+        assert_eq!(records[1]["subject"], "Goodbye");
+
+        let role = analyzer.generate_role("Shop Clerk", true, None).unwrap();
+        assert_eq!(role.path, root.join("apps/shop/shop/fixtures/role.json"));
+        assert!(!role.in_fixtures);
+        let records = read_json(&role.path).unwrap();
+        assert_eq!(records[0]["role_name"], "Shop Clerk");
+        assert_eq!(records[0]["desk_access"], 1);
+        assert_eq!(records[0]["is_custom"], 1);
+        let error = analyzer
+            .generate_role("shop clerk", false, None)
+            .unwrap_err();
+        assert_eq!(error, "Role 'Shop Clerk' already exists");
+        assert!(analyzer.generate_role("Sales User", false, None).is_err());
+        assert!(analyzer.generate_role("Guest", false, None).is_err());
+
+        let (profile, unknown) = analyzer
+            .generate_role_profile(
+                "Shop Staff",
+                &[
+                    "Shop Clerk".to_string(),
+                    "Sales User".to_string(),
+                    "Cashier".to_string(),
+                ],
+                Some("shop"),
+            )
+            .unwrap();
+        assert_eq!(unknown, vec!["Cashier"]);
+        let records = read_json(&profile.path).unwrap();
+        assert_eq!(records[0]["role_profile"], "Shop Staff");
+        assert_eq!(records[0]["roles"][1]["role"], "Sales User");
+        assert!(analyzer
+            .generate_role_profile("Empty", &[" ".to_string()], None)
+            .is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_doctype_to_markdown() {
+        let field =
+            |fieldname: &str, fieldtype: &str, label: &str, options: Option<&str>| FieldInfo {
+                fieldname: fieldname.to_string(),
+                fieldtype: fieldtype.to_string(),
+                label: label.to_string(),
+                options: options.map(|o| o.to_string()),
+                reqd: (fieldname == "customer").then_some(1),
+                description: None,
+            };
+        let doctype = DocTypeInfo {
+            name: "Shop Order".to_string(),
+            module: "Selling".to_string(),
+            file_path: PathBuf::from("shop_order.json"),
+            controller_path: None,
+            client_script_path: None,
+            fields: vec![
+                field("customer", "Link", "Customer", Some("Customer")),
+                field("status", "Select", "Open | Closed", Some("Open\nClosed")),
+            ],
+            permissions: vec![PermissionInfo {
+                role: "Sales User".to_string(),
+                read: Some(1),
+                write: Some(1),
+                create: None,
+                delete: Some(0),
+                submit: None,
+            }],
+            links: vec![LinkInfo {
+                source_field: "customer".to_string(),
+                target_doctype: "Customer".to_string(),
+                link_type: "Link".to_string(),
+                is_child_table: false,
+            }],
+            states: vec![],
+        };
+
+        let md = FrappeAnalyzer::new().doctype_to_markdown(&doctype);
+        assert!(md.starts_with("# Shop Order\n\nModule: Selling\n"));
+        assert!(md.contains("| `customer` | Link | Customer | ✓ | Customer |\n"));
+        assert!(md.contains("| `status` | Select | Open \\| Closed |  | Open<br>Closed |\n"));
+        assert!(md.contains("| Sales User | ✓ | ✓ |  |  |\n"));
+        assert!(md.contains("- `customer` → Customer (Link)\n"));
+    }
+
+    #[test]
+    fn test_parse_doctype_states() {
+        let dir = temp_dir("doctype_states");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "module": "Selling", "fields": [],
+                "states": [{"title": "Draft", "color": "Gray"},
+                           {"title": "Shipped", "color": "Green"},
+                           {"title": "", "color": "Red"},
+                           {"title": "On Hold"}]}"#,
+        )
+        .unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        let doctype = analyzer.parse_doctype(&dir, "shop_order").unwrap();
+        let states: Vec<(&str, Option<&str>)> = doctype
+            .states
+            .iter()
+            .map(|state| (state.title.as_str(), state.color.as_deref()))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("Draft", Some("Gray")),
+                ("Shipped", Some("Green")),
+                ("On Hold", None)
+            ]
+        );
+
+        let md = analyzer.doctype_to_markdown(&doctype);
+        assert!(md.contains("\n## States\n\n| State | Color |\n| --- | --- |\n| Draft | Gray |\n"));
+        assert!(md.contains("| On Hold |  |\n"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_analyze_doctype_links() {
+        let field = |fieldname: &str, fieldtype: &str, options: &str| FieldInfo {
+            fieldname: fieldname.to_string(),
+            fieldtype: fieldtype.to_string(),
+            label: String::new(),
+            options: Some(options.to_string()),
+            reqd: None,
+            description: None,
+        };
+        let fields = vec![
+            field("customer", "Link", "Customer"),
+            field("items", "Table", "Shop Order Item"),
+            field("tags", "Table MultiSelect", "Shop Order Tag"),
+            field("notes", "Small Text", ""),
+        ];
+
+        let links = FrappeAnalyzer::new().analyze_doctype_links(&fields);
+        let summary: Vec<(&str, &str, &str, bool)> = links
+            .iter()
+            .map(|link| {
+                (
+                    link.source_field.as_str(),
+                    link.target_doctype.as_str(),
+                    link.link_type.as_str(),
+                    link.is_child_table,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("customer", "Customer", "Link", false),
+                ("items", "Shop Order Item", "Table", true),
+                ("tags", "Shop Order Tag", "Table MultiSelect", true),
+            ]
+        );
+
+        let doctype = DocTypeInfo {
+            name: "Shop Order".to_string(),
+            module: "Selling".to_string(),
+            file_path: PathBuf::from("shop_order.json"),
+            controller_path: None,
+            client_script_path: None,
+            fields,
+            permissions: vec![],
+            links,
+            states: vec![],
+        };
+        let md = FrappeAnalyzer::new().doctype_to_markdown(&doctype);
+        assert!(md.contains("- `customer` → Customer (Link)\n"));
+        assert!(md.contains("- `items` ⇒ Shop Order Item (Table)\n"));
+        assert!(md.contains("- `tags` ⇒ Shop Order Tag (Table MultiSelect)\n"));
+    }
+
+    #[test]
+    fn test_fields_to_csv() {
+        let field =
+            |fieldname: &str, label: &str, reqd: Option<i32>, options: Option<&str>| FieldInfo {
+                fieldname: fieldname.to_string(),
+                fieldtype: "Data".to_string(),
+                label: label.to_string(),
+                options: options.map(|o| o.to_string()),
+                reqd,
+                description: None,
+            };
+        let doctype = |name: &str, fields: Vec<FieldInfo>| DocTypeInfo {
+            name: name.to_string(),
+            module: "Selling".to_string(),
+            file_path: PathBuf::from("x.json"),
+            controller_path: None,
+            client_script_path: None,
+            fields,
+            permissions: vec![],
+            links: vec![],
+            states: vec![],
+        };
+        let order = doctype(
+            "Shop Order",
+            vec![
+                field("title", "Title, short", Some(1), None),
+                field("status", "The \"state\"", None, Some("Open\nClosed")),
+            ],
+        );
+        let item = doctype("Shop Item", vec![field("code", "Code", Some(0), None)]);
+
+        assert_eq!(
+            fields_to_csv(&[&order, &item]),
+            "doctype,fieldname,fieldtype,label,reqd,options\r\n\
+             Shop Order,title,Data,\"Title, short\",1,\r\n\
+             Shop Order,status,Data,\"The \"\"state\"\"\",0,\"Open\nClosed\"\r\n\
+             Shop Item,code,Data,Code,0,\r\n"
+        );
+        assert_eq!(
+            fields_to_csv(&[]),
+            "doctype,fieldname,fieldtype,label,reqd,options\r\n"
+        );
+    }
+
+    #[test]
+    fn test_validate_doctype_json() {
+        let root = temp_dir("validate_doctype_json");
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("shop_item.json");
+
+        fs::write(
+            &path,
+            r#"{"doctype": "DocType", "name": "Shop Item", "module": "Selling",
+                "field_order": ["title", "price", "colour"],
+                "fields": [
+                    {"fieldname": "title", "fieldtype": "Data"},
+                    {"fieldname": "price", "fieldtype": "Currency"},
+                    {"fieldname": "supplier", "fieldtype": "Link"},
+                    {"fieldname": "size", "fieldtype": "Select", "options": ""},
+                    {"fieldname": "title", "fieldtype": "Data"},
+                    {"fieldtype": "Int"}
+                ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            validate_doctype_json(&path).unwrap(),
+            vec![
+                "field 'supplier' is a Link field without options",
+                "field 'size' is a Select field without options",
+                "duplicate fieldname 'title'",
+                "field #6 has no fieldname",
+                "field_order lists 'colour', which is not in fields",
+            ]
+        );
+
+        fs::write(&path, r#"{"doctype": "DocType", "fields": []}"#).unwrap();
+        assert_eq!(
+            validate_doctype_json(&path).unwrap(),
+            vec!["missing \"name\"", "missing \"module\""]
+        );
+        fs::write(&path, "{\"fields\": [}").unwrap();
+        assert!(validate_doctype_json(&path).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_doctype_issues() {
+        let root = temp_dir("doctype_issues");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let doctype_dir = root.join("apps/shop/shop/selling/doctype");
+        let write = |name: &str, json: serde_json::Value| {
+            let dir = doctype_dir.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join(format!("{}.json", name)), json.to_string()).unwrap();
+        };
+        let links: Vec<_> = (0..=MAX_LINK_FIELDS)
+            .map(|i| {
+                serde_json::json!({
+                    "fieldname": format!("link_{}", i),
+                    "fieldtype": "Link",
+                    "search_index": (i == 0) as i32,
+                })
+            })
+            .collect();
+        write(
+            "busy_order",
+            serde_json::json!({"doctype": "DocType", "name": "Busy Order", "fields": links}),
+        );
+        write(
+            "order_line",
+            serde_json::json!({"doctype": "DocType", "name": "Order Line", "istable": 1,
+                "fields": [{"fieldname": "taxes", "fieldtype": "Table", "options": "Tax"}]}),
+        );
+        write(
+            "shop_settings",
+            serde_json::json!({"doctype": "DocType", "name": "Shop Settings", "issingle": 1,
+                "fields": [{"fieldname": "rules", "fieldtype": "Table", "options": "Rule"}]}),
+        );
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let app = &analyzer.get_project().unwrap().apps[0];
+        let mut issues = analyzer.doctype_issues(app);
+        issues.sort_by(|a, b| a.doctype.cmp(&b.doctype));
+
+        assert_eq!(issues.len(), 2);
+        assert!(!issues[0].error);
+        assert!(issues[0]
+            .message
+            .starts_with("21 Link fields (20 without search_index)"));
+        assert_eq!(issues[1].doctype, "Order Line");
+        assert!(issues[1].error);
+        assert!(issues[1].message.contains("'taxes'"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_generate_report() {
+        let root = temp_dir("generate_report");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+        let module_dir = root.join("apps/shop/shop/selling");
+        let order_dir = module_dir.join("doctype/shop_order");
+        fs::create_dir_all(&order_dir).unwrap();
+        fs::write(
+            order_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Selling", "fields": []}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        let query = analyzer
+            .generate_report("Open Orders", "Shop Order", ReportKind::Query)
+            .unwrap();
+        let dir = module_dir.join("report/open_orders");
+        assert_eq!(
+            query,
+            vec![
+                dir.join("__init__.py"),
+                dir.join("open_orders.json"),
+                dir.join("open_orders.js"),
+            ]
+        );
+        let record = read_json(&query[1]).unwrap();
+        assert_eq!(record["report_type"], "Query Report");
+        assert!(record["query"]
+            .as_str()
+            .unwrap()
+            .contains("FROM `tabShop Order`"));
+
+        let script = analyzer
+            .generate_report("Order Summary", "Shop Order", ReportKind::Script)
+            .unwrap();
+        let python = fs::read_to_string(script.last().unwrap()).unwrap();
+        assert!(python.contains("def execute(filters=None):"));
+        assert!(python.contains("return columns, data"));
+        assert!(read_json(&script[1]).unwrap().get("query").is_none());
+
+        assert!(analyzer
+            .generate_report("Open Orders", "Shop Order", ReportKind::Query)
+            .is_err());
+        assert!(ReportKind::parse("chart").is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_generate_field_suggestions() {
+        let amount = generate_field_suggestions("grand_amount");
+        assert_eq!(amount[0].0, "Currency");
+        assert_eq!(amount.len(), 1);
+
+        let qty = generate_field_suggestions("stock_qty");
+        let types: Vec<&str> = qty.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(types, vec!["Float", "Currency", "Int"]);
+
+        assert_eq!(generate_field_suggestions("customer_id")[0].0, "Link");
+        assert_eq!(generate_field_suggestions("is_active")[0].0, "Check");
+    }
+
+    #[test]
+    fn test_generate_api_method() {
+        let root = temp_dir("generate_api_method");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+        let package = root.join("apps/shop/shop");
+        fs::create_dir_all(&package).unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        let generated = analyzer
+            .generate_api_method("shop", "get_stock", &["item_code".to_string()])
+            .unwrap();
+        assert!(generated.created);
+        assert_eq!(generated.path, package.join("api.py"));
+        assert_eq!(generated.endpoint, "shop.api.get_stock");
+
+        let second = analyzer.generate_api_method("shop", "ping", &[]).unwrap();
+        assert!(!second.created);
+        let content = fs::read_to_string(&generated.path).unwrap();
+        assert!(content.starts_with("import frappe\n"));
+        assert!(content.contains("@frappe.whitelist()\ndef get_stock(item_code):\n"));
+        assert!(content.contains("\t\titem_code: TODO\n"));
+        assert!(content.contains("def ping():\n"));
+
+        assert!(analyzer.generate_api_method("shop", "ping", &[]).is_err());
+        assert!(analyzer.generate_api_method("shop", "class", &[]).is_err());
+        assert!(analyzer
+            .generate_api_method("shop", "get-stock", &[])
+            .is_err());
+        assert!(analyzer
+            .generate_api_method("missing", "ping", &[])
+            .is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_generate_workflow() {
+        let root = temp_dir("generate_workflow");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let module_dir = root.join("apps/shop/shop/selling");
+        let order_dir = module_dir.join("doctype/shop_order");
+        fs::create_dir_all(&order_dir).unwrap();
+        fs::write(
+            order_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Selling", "is_submittable": 1,
+                "field_order": ["customer"],
+                "fields": [{"fieldname": "customer", "fieldtype": "Data"}]}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        assert!(analyzer
+            .generate_workflow("Order Approval", "Missing")
+            .is_err());
+
+        let generated = analyzer
+            .generate_workflow("Order Approval", "Shop Order")
+            .unwrap();
+        assert!(generated.field_added);
+        assert_eq!(
+            generated.path,
+            module_dir.join("workflow/order_approval/order_approval.json")
+        );
+
+        let workflow = read_json(&generated.path).unwrap();
+        assert_eq!(workflow["document_type"], "Shop Order");
+        assert_eq!(workflow["states"][1]["state"], "Approved");
+        assert_eq!(workflow["states"][1]["doc_status"], "1");
+        assert_eq!(workflow["transitions"][0]["next_state"], "Approved");
+
+        let doctype = read_json(&order_dir.join("shop_order.json")).unwrap();
+        assert_eq!(doctype["fields"][1]["fieldname"], "workflow_state");
+        assert_eq!(doctype["field_order"][1], "workflow_state");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_reorder_fields() {
+        let root = temp_dir("reorder_fields");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let item_dir = root.join("apps/shop/shop/selling/doctype/shop_item");
+        fs::create_dir_all(&item_dir).unwrap();
+        let json_path = item_dir.join("shop_item.json");
+        fs::write(
+            &json_path,
+            r#"{"doctype": "DocType", "name": "Shop Item", "module": "Selling",
+                "field_order": ["title", "price", "stock"],
+                "fields": [
+                    {"fieldname": "title", "fieldtype": "Data"},
+                    {"fieldname": "price", "fieldtype": "Currency"},
+                    {"fieldname": "stock", "fieldtype": "Int"}
+                ]}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let order = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let error = analyzer
+            .reorder_fields("Shop Item", &order(&["title", "cost", "stock"]))
+            .unwrap_err();
+        assert!(error.contains("cost"));
+        let error = analyzer
+            .reorder_fields("Shop Item", &order(&["title", "price"]))
+            .unwrap_err();
+        assert!(error.contains("stock"));
+        assert!(analyzer
+            .reorder_fields("Shop Item", &order(&["title", "title", "price", "stock"]))
+            .is_err());
+
+        analyzer
+            .reorder_fields("Shop Item", &order(&["stock", "title", "price"]))
+            .unwrap();
+        let json = read_json(&json_path).unwrap();
+        assert_eq!(
+            json["field_order"],
+            serde_json::json!(["stock", "title", "price"])
+        );
+        assert_eq!(json["fields"][0]["fieldname"], "title");
+        assert!(!item_dir.join("shop_item.json.tmp").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_search_doctypes_with_filters() {
+        let root = temp_dir("search_filters");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop\nwarehouse").unwrap();
+
+        for (app, module, folder, name) in [
+            ("shop", "selling", "shop_item", "Shop Item"),
+            ("shop", "stock", "shop_stock_item", "Shop Stock Item"),
+            ("warehouse", "stock", "warehouse_item", "Warehouse Item"),
+        ] {
+            let dir = root
+                .join("apps")
+                .join(app)
+                .join(app)
+                .join(module)
+                .join("doctype")
+                .join(folder);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(format!("{}.json", folder)),
+                format!(
+                    r#"{{"doctype": "DocType", "name": "{}", "module": "{}", "fields": []}}"#,
+                    name,
+                    if module == "stock" {
+                        "Stock"
+                    } else {
+                        "Selling"
+                    }
+                ),
+            )
+            .unwrap();
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let names = |results: Vec<&DocTypeInfo>| {
+            let mut names: Vec<String> = results.iter().map(|dt| dt.name.clone()).collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(analyzer.search_doctypes_in("item", None, None).len(), 3);
+        assert_eq!(
+            names(analyzer.search_doctypes_in("item", Some("shop"), None)),
+            vec!["shop_item", "shop_stock_item"]
+        );
+        assert_eq!(
+            names(analyzer.search_doctypes_in("item", None, Some("stock"))),
+            vec!["shop_stock_item", "warehouse_item"]
+        );
+        assert_eq!(
+            names(analyzer.search_doctypes_in("", Some("warehouse"), Some("Stock"))),
+            vec!["warehouse_item"]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_bench_logs() {
+        let root = temp_dir("bench_logs");
+        fs::create_dir_all(root.join("logs")).unwrap();
+        let lines: Vec<String> = (1..=5000).map(|i| format!("request {}", i)).collect();
+        fs::write(root.join("logs/web.log"), lines.join("\n") + "\n").unwrap();
+        fs::write(root.join("logs/worker.error.log"), "").unwrap();
+        fs::write(root.join("secret.txt"), "").unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        let logs = analyzer.bench_logs(&root).unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].name, "web.log");
+        assert_eq!(logs[1].size, 0);
+
+        assert_eq!(
+            analyzer.tail_bench_log(&root, "web.log", 2).unwrap(),
+            vec!["request 4999", "request 5000"]
+        );
+        assert_eq!(
+            analyzer
+                .tail_bench_log(&root, "web.log", 6000)
+                .unwrap()
+                .len(),
+            5000
+        );
+        assert!(analyzer
+            .tail_bench_log(&root, "worker.error.log", 10)
+            .unwrap()
+            .is_empty());
+        assert!(analyzer.tail_bench_log(&root, "../secret.txt", 10).is_err());
+        assert!(analyzer.tail_bench_log(&root, "missing.log", 10).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_find_link_cycles() {
+        let root = temp_dir("link_cycles");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let doctypes = root.join("apps/shop/shop/selling/doctype");
+        for (folder, fields) in [
+            (
+                "shop_order",
+                r#"[{"fieldname": "invoice", "fieldtype": "Link", "options": "Shop Invoice"},
+                    {"fieldname": "customer", "fieldtype": "Link", "options": "Customer"},
+                    {"fieldname": "party", "fieldtype": "Dynamic Link", "options": "party_type"}]"#,
+            ),
+            (
+                "shop_invoice",
+                r#"[{"fieldname": "order", "fieldtype": "Link", "options": "Shop Order"}]"#,
+            ),
+            (
+                "shop_category",
+                r#"[{"fieldname": "parent_category", "fieldtype": "Link", "options": "Shop Category"}]"#,
+            ),
+        ] {
+            fs::create_dir_all(doctypes.join(folder)).unwrap();
+            fs::write(
+                doctypes.join(folder).join(format!("{}.json", folder)),
+                format!(
+                    r#"{{"doctype": "DocType", "module": "Selling", "fields": {}}}"#,
+                    fields
+                ),
+            )
+            .unwrap();
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        assert_eq!(
+            analyzer.find_link_cycles(),
+            vec![vec!["Shop Invoice".to_string(), "Shop Order".to_string()]]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_unused_doctype_candidates() {
+        let root = temp_dir("unused_doctypes");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let stub = "# Copyright (c) 2024, Shop and contributors\n\
+                    import frappe\n\
+                    from frappe.model.document import Document\n\n\n\
+                    class Stub(Document):\n\
+                    \t# begin: auto-generated types\n\
+                    \tfrom typing import TYPE_CHECKING\n\n\
+                    \tif TYPE_CHECKING:\n\
+                    \t\ttitle: DF.Data\n\
+                    \t# end: auto-generated types\n\n\
+                    \tdef validate(self):\n\
+                    \t\t\"\"\"Called before saving the document\"\"\"\n\
+                    \t\tpass\n";
+        let doctypes = root.join("apps/shop/shop/selling/doctype");
+        for (folder, fields, controller) in [
+            (
+                "shop_order",
+                r#"[{"fieldname": "customer", "fieldtype": "Link", "options": "Shop Customer"},
+                    {"fieldname": "items", "fieldtype": "Table", "options": "Shop Order Item"}]"#,
+                Some("class ShopOrder(Document):\n\tdef validate(self):\n\t\tself.total = 0\n"),
+            ),
+            ("shop_customer", "[]", Some(stub)),
+            ("shop_order_item", "[]", None),
+            (
+                "shop_category",
+                r#"[{"fieldname": "parent_category", "fieldtype": "Link", "options": "Shop Category"}]"#,
+                Some(stub),
+            ),
+            ("shop_legacy_log", "[]", None),
+        ] {
+            let dir = doctypes.join(folder);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(format!("{}.json", folder)),
+                format!(
+                    r#"{{"doctype": "DocType", "module": "Selling", "fields": {}}}"#,
+                    fields
+                ),
+            )
+            .unwrap();
+            if let Some(controller) = controller {
+                fs::write(dir.join(format!("{}.py", folder)), controller).unwrap();
+            }
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        let names: Vec<&str> = analyzer
+            .unused_doctype_candidates()
+            .iter()
+            .map(|dt| dt.name.as_str())
+            .collect();
+        // shop_order is unreferenced but has logic; a self link doesn't count
+        assert_eq!(names, vec!["shop_category", "shop_legacy_log"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_format_doctype_is_idempotent() {
+        let root = temp_dir("format_doctype");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let item_dir = root.join("apps/shop/shop/selling/doctype/shop_item");
+        fs::create_dir_all(&item_dir).unwrap();
+        let json_path = item_dir.join("shop_item.json");
+        fs::write(
+            &json_path,
+            "{\"name\": \"Shop Item\",   \"doctype\": \"DocType\",\n    \"module\": \"Selling\",  \n\"fields\": [{\"fieldtype\": \"Data\", \"fieldname\": \"title\"}]}\n",
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        assert!(analyzer.format_doctype("Shop Item").unwrap());
+        let formatted = fs::read_to_string(&json_path).unwrap();
+        assert!(formatted
+            .starts_with("{\n \"doctype\": \"DocType\",\n \"fields\": [\n  {\n   \"fieldname\""));
+        assert!(!formatted.lines().any(|line| line.ends_with(' ')));
+
+        assert!(!analyzer.format_doctype("Shop Item").unwrap());
+        assert_eq!(fs::read_to_string(&json_path).unwrap(), formatted);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_export_doctype_template() {
+        let root = temp_dir("doctype_template");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let order_dir = root.join("apps/shop/shop/selling/doctype/shop_order");
+        fs::create_dir_all(&order_dir).unwrap();
+        fs::write(
+            order_dir.join("shop_order.json"),
+            r#"{"doctype": "DocType", "name": "Shop Order", "module": "Selling",
+                "creation": "2024-01-01 00:00:00", "modified": "2024-02-01 00:00:00",
+                "modified_by": "Administrator", "owner": "Administrator",
+                "fields": [
+                    {"fieldname": "customer", "fieldtype": "Link", "options": "Customer",
+                     "name": "abc123", "parent": "Shop Order", "creation": "2024-01-01 00:00:00"},
+                    {"fieldname": "amended_from", "fieldtype": "Link", "options": "Shop Order"}
+                ],
+                "permissions": [{"role": "System Manager", "read": 1, "owner": "Administrator"}]}"#,
+        )
+        .unwrap();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+        assert!(analyzer.export_doctype_template("Missing").is_err());
+
+        let template: serde_json::Value =
+            serde_json::from_str(&analyzer.export_doctype_template("shop_order").unwrap()).unwrap();
+        assert_eq!(template["name"], TEMPLATE_DOCTYPE);
+        assert_eq!(template["module"], TEMPLATE_MODULE);
+        for key in ["creation", "modified", "modified_by", "owner"] {
+            assert!(template.get(key).is_none(), "{} was kept", key);
+        }
+        assert_eq!(
+            template["fields"][0],
+            serde_json::json!({"fieldname": "customer", "fieldtype": "Link", "options": "Customer"})
+        );
+        assert_eq!(template["fields"][1]["options"], TEMPLATE_DOCTYPE);
+        assert!(template["permissions"][0].get("owner").is_none());
+
+        assert!(analyzer
+            .duplicate_doctype("Missing", "Shop Quote", "Selling")
+            .is_err());
+        assert!(analyzer
+            .duplicate_doctype("Shop Order", "shop_order", "Selling")
+            .is_err());
+        let copy = analyzer
+            .duplicate_doctype("Shop Order", "Shop Quote", "Quotes")
+            .unwrap();
+        assert_eq!(copy.source, "Shop Order");
+        assert_eq!(copy.json["name"], "Shop Quote");
+        assert_eq!(copy.json["module"], "Quotes");
+        assert!(copy.json.get("modified").is_none());
+        assert_eq!(copy.json["fields"][0]["options"], "Customer");
+        assert!(copy.json["fields"][0].get("parent").is_none());
+        assert_eq!(copy.json["permissions"][0]["role"], "System Manager");
+        assert_eq!(copy.self_references, vec!["amended_from"]);
+
+        assert!(analyzer
+            .write_doctype("Shop Quote", "Quotes", &copy.json, "", "")
+            .is_err());
+        let files = analyzer
+            .write_doctype(
+                "Shop Quote",
+                "Selling",
+                &copy.json,
+                "# controller\n",
+                "// form\n",
+            )
+            .unwrap();
+        let quote_dir = root.join("apps/shop/shop/selling/doctype/shop_quote");
+        assert_eq!(
+            files,
+            vec![
+                quote_dir.join("__init__.py"),
+                quote_dir.join("shop_quote.json"),
+                quote_dir.join("shop_quote.py"),
+                quote_dir.join("shop_quote.js"),
+            ]
+        );
+        assert_eq!(read_json(&files[1]).unwrap()["name"], "Shop Quote");
+        assert_eq!(fs::read_to_string(&files[2]).unwrap(), "# controller\n");
+        assert_eq!(fs::read_to_string(&files[3]).unwrap(), "// form\n");
+        let again = analyzer.write_doctype("Shop Quote", "Selling", &copy.json, "", "");
+        assert!(again.unwrap_err().contains("already exists"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_diff_doctypes() {
+        let root = temp_dir("diff_doctypes");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let doctypes = root.join("apps/shop/shop/core/doctype");
+        for (folder, json) in [
+            (
+                "shop_item",
+                r#"{"doctype": "DocType", "module": "Core",
+                    "fields": [
+                        {"fieldname": "title", "fieldtype": "Data", "reqd": 1},
+                        {"fieldname": "price", "fieldtype": "Currency"}
+                    ],
+                    "permissions": [{"role": "System Manager", "read": 1, "write": 1}]}"#,
+            ),
+            (
+                "store_item",
+                r#"{"doctype": "DocType", "module": "Core",
+                    "fields": [
+                        {"fieldname": "title", "fieldtype": "Link", "options": "Item"},
+                        {"fieldname": "stock", "fieldtype": "Int"}
+                    ],
+                    "permissions": [
+                        {"role": "System Manager", "read": 1},
+                        {"role": "Stock User", "read": 1}
+                    ]}"#,
+            ),
+        ] {
+            fs::create_dir_all(doctypes.join(folder)).unwrap();
+            fs::write(doctypes.join(folder).join(format!("{}.json", folder)), json).unwrap();
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        let diff = analyzer.diff_doctypes("Shop Item", "store_item").unwrap();
+        assert_eq!(diff.only_left[0].fieldname, "price");
+        assert_eq!(diff.only_right[0].fieldname, "stock");
+        assert_eq!(
+            diff.changed_fields[0].differences,
+            vec!["fieldtype: Data → Link", "options: - → Item", "reqd: 1 → 0"]
+        );
+        assert_eq!(diff.only_right_roles, vec!["Stock User".to_string()]);
+        assert_eq!(diff.changed_roles[0].differences, vec!["write: 1 → 0"]);
+
+        let error = analyzer
+            .diff_doctypes("Shop Itme", "Store Item")
+            .unwrap_err();
+        assert!(error.contains("Did you mean: shop_item"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_doctype_changes() {
+        let root = temp_dir("doctype_changes");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        let folder = root.join("apps/shop/shop/core/doctype/shop_item");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(
+            folder.join("shop_item.json"),
+            r#"{"doctype": "DocType", "name": "Shop Item", "module": "Core",
+                "fields": [
+                    {"fieldname": "title", "fieldtype": "Data", "reqd": 1},
+                    {"fieldname": "stock", "fieldtype": "Int"}
+                ]}"#,
+        )
+        .unwrap();
+        let committed = r#"{"doctype": "DocType", "name": "Shop Item", "fields": [
+            {"fieldname": "title", "fieldtype": "Data"},
+            {"fieldname": "price", "fieldtype": "Currency"}
+        ]}"#;
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        let changes = analyzer.doctype_changes("Shop Item", committed).unwrap();
+        assert_eq!(changes.added[0].fieldname, "stock");
+        assert_eq!(changes.removed[0].fieldname, "price");
+        assert_eq!(changes.modified[0].differences, vec!["reqd: 0 → 1"]);
+        assert!(analyzer.doctype_changes("Shop Item", "{").is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_read_app_version() {
+        let root = temp_dir("app_version");
+        let app_path = root.join("shop");
+        fs::create_dir_all(app_path.join("shop")).unwrap();
+        fs::write(
+            app_path.join("shop").join("__init__.py"),
+            "# shop\n__version__ = '1.4.2'\n",
+        )
+        .unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        assert_eq!(
+            analyzer.read_app_version(&app_path, "shop"),
+            Some("1.4.2".to_string())
+        );
+        assert_eq!(
+            analyzer.read_app_version(&root.join("missing"), "missing"),
+            None
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_site_installed_apps() {
+        let root = temp_dir("site_installed_apps");
+        fs::create_dir_all(root.join("sites/shop.local")).unwrap();
+        fs::create_dir_all(root.join("sites/blog.local")).unwrap();
+        fs::write(root.join("apps.txt"), "frappe\nshop\nblog\n").unwrap();
+        fs::write(root.join("sites/shop.local/apps.txt"), "frappe\nshop\n").unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        assert_eq!(
+            analyzer.site_installed_apps(&root, "shop.local"),
+            vec!["frappe", "shop"]
+        );
+        assert_eq!(
+            analyzer.site_installed_apps(&root, "blog.local"),
+            vec!["frappe", "shop", "blog"]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_project_stats() {
+        let root = temp_dir("project_stats");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        for (module, doctype, fields) in [
+            ("selling", "quote", 2),
+            ("selling", "order", 1),
+            ("stock", "item", 0),
+        ] {
+            let dir = root
+                .join("apps/shop/shop")
+                .join(module)
+                .join("doctype")
+                .join(doctype);
+            fs::create_dir_all(&dir).unwrap();
+            let fields: Vec<String> = (0..fields)
+                .map(|i| format!(r#"{{"fieldname": "f{}", "fieldtype": "Data"}}"#, i))
+                .collect();
+            fs::write(
+                dir.join(format!("{}.json", doctype)),
+                format!(
+                    r#"{{"doctype": "DocType", "module": "{}", "fields": [{}]}}"#,
+                    module,
+                    fields.join(", ")
+                ),
+            )
+            .unwrap();
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        assert_eq!(analyzer.project_stats().apps, 0);
+        analyzer.analyze_project(&root).unwrap();
+
+        let stats = analyzer.project_stats();
+        assert_eq!(stats.apps, 1);
+        assert_eq!(stats.doctypes, 3);
+        assert_eq!(stats.fields, 3);
+        assert_eq!(
+            stats.modules,
+            vec![("selling".to_string(), 2), ("stock".to_string(), 1)]
+        );
+
+        let listed: Vec<(&str, &str)> = analyzer
+            .app_doctypes("shop")
+            .unwrap()
+            .iter()
+            .map(|dt| (dt.module.as_str(), dt.name.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("selling", "order"),
+                ("selling", "quote"),
+                ("stock", "item")
+            ]
+        );
+        assert_eq!(
+            analyzer.app_doctypes("blog").unwrap_err(),
+            "App 'blog' not found. Available apps: shop"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_permission_matrix() {
+        let root = temp_dir("permission_matrix");
+        fs::create_dir_all(root.join("sites")).unwrap();
+        fs::write(root.join("Procfile"), "").unwrap();
+        fs::write(root.join("apps.txt"), "shop").unwrap();
+
+        for (doctype, permissions) in [
+            (
+                "order",
+                r#"[{"role": "Sales User", "read": 1, "write": 1, "create": 1},
+                    {"role": "Sales User", "permlevel": 1, "read": 1},
+                    {"role": "Sales Manager", "read": 1, "write": 1, "submit": 1, "delete": 1},
+                    {"role": "Auditor", "write": 1}]"#,
+            ),
+            (
+                "quote",
+                r#"[{"role": "Sales User", "read": 1},
+                    {"role": "Auditor", "write": 1, "read": 0}]"#,
+            ),
+        ] {
+            let dir = root.join("apps/shop/shop/selling/doctype").join(doctype);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(format!("{}.json", doctype)),
+                format!(
+                    r#"{{"doctype": "DocType", "module": "Selling", "fields": [], "permissions": {}}}"#,
+                    permissions
+                ),
+            )
+            .unwrap();
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        analyzer.analyze_project(&root).unwrap();
+
+        let matrix = analyzer.permission_matrix(None).unwrap();
+        let rows: Vec<(&str, [usize; 5])> = matrix
+            .iter()
+            .map(|r| {
+                (
+                    r.role.as_str(),
+                    [r.read, r.write, r.create, r.delete, r.submit],
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Auditor", [0, 2, 0, 0, 0]),
+                ("Sales Manager", [1, 1, 0, 1, 1]),
+                ("Sales User", [2, 1, 1, 0, 0]),
+            ]
+        );
+        assert!(matrix[0].writes_without_read());
+        assert!(!matrix[2].writes_without_read());
+        assert_eq!(analyzer.permission_matrix(Some("shop")).unwrap(), matrix);
+        assert!(analyzer.permission_matrix(Some("blog")).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_set_site_config_value() {
+        let root = temp_dir("site_config");
+        let config_path = root.join("site_config.json");
+        fs::write(&config_path, r#"{"db_name": "_abc", "db_password": "pw"}"#).unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        assert_eq!(
+            analyzer
+                .set_site_config_value(&config_path, "developer_mode", "1")
+                .unwrap(),
+            serde_json::json!(1)
+        );
+        analyzer
+            .set_site_config_value(&config_path, "maintenance_mode", "false")
+            .unwrap();
+        analyzer
+            .set_site_config_value(&config_path, "host_name", "https://shop.test")
+            .unwrap();
+
+        let config = analyzer.read_site_config(&config_path).unwrap();
+        assert_eq!(config["developer_mode"], 1);
+        assert_eq!(config["maintenance_mode"], false);
+        assert_eq!(config["host_name"], "https://shop.test");
+        assert_eq!(config["db_password"], "pw");
+        assert!(!root.join("site_config.json.tmp").exists());
+
+        assert!(is_sensitive_config_key("db_password"));
+        assert!(!is_sensitive_config_key("db_name"));
+
+        let staging = serde_json::json!({"db_name": "shop", "db_password": "a", "developer_mode": 1, "redis_cache": "redis://cache"});
+        let dev = serde_json::json!({"db_name": "shop", "db_password": "b", "developer_mode": 0, "host_name": "localhost"});
+        let diff = diff_site_configs(staging.as_object().unwrap(), dev.as_object().unwrap());
+        assert_eq!(diff.only_left, vec!["redis_cache"]);
+        assert_eq!(diff.only_right, vec!["host_name"]);
+        assert_eq!(diff.changed, vec!["db_password", "developer_mode"]);
+        assert!(diff_site_configs(&config, &config).is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_doctype_with_bom_and_invalid_utf8() {
+        let root = temp_dir("bom_doctype");
+        let bom_dir = root.join("bom_doc");
+        let latin_dir = root.join("latin_doc");
+        fs::create_dir_all(&bom_dir).unwrap();
+        fs::create_dir_all(&latin_dir).unwrap();
+
+        let mut bom_json = b"\xEF\xBB\xBF".to_vec();
+        bom_json.extend_from_slice(br#"{"doctype": "DocType", "module": "Core", "fields": []}"#);
+        fs::write(bom_dir.join("bom_doc.json"), bom_json).unwrap();
+
+        // "Caf\xe9" is Latin-1, not UTF-8
+        let mut latin_json = br#"{"doctype": "DocType", "module": "Caf"#.to_vec();
+        latin_json.extend_from_slice(b"\xe9\", \"fields\": []}");
+        fs::write(latin_dir.join("latin_doc.json"), latin_json).unwrap();
+
+        let analyzer = FrappeAnalyzer::new();
+        let bom = analyzer.parse_doctype(&bom_dir, "bom_doc").unwrap();
+        assert_eq!(bom.module, "Core");
+        let error = analyzer.parse_doctype(&latin_dir, "latin_doc").unwrap_err();
+        assert!(error.contains("not valid UTF-8"), "{}", error);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
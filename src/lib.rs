@@ -1,24 +1,104 @@
-use std::collections::HashMap;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use zed_extension_api::{
-    self as zed, register_extension, Extension, Result, SlashCommand,
-    SlashCommandArgumentCompletion, SlashCommandOutput, Worktree,
+    register_extension, Extension, Result, SlashCommand, SlashCommandArgumentCompletion,
+    SlashCommandOutput, Worktree,
 };
 
+mod command_args;
 mod frappe_utils;
 mod process_manager;
+mod settings;
 mod test_runner;
 
-use frappe_utils::FrappeAnalyzer;
-use process_manager::ProcessManager;
-use test_runner::TestRunner;
+use command_args::CommandArgs;
+use frappe_utils::{FrappeAnalyzer, ReportKind, ScriptKind};
+use process_manager::{MigrateOutcome, NewAppMetadata, ProcessManager, ProcessStatus};
+use settings::{LatteSettings, LoadedSettings};
+use test_runner::{
+    ui_progress_key, SharedTestProgress, TestReportFormat, TestRunner, TestSelection, TestWatcher,
+};
+
+// Commands whose output can grow with the project; these accept `--lines <n>`
+const PAGINATED_COMMANDS: &[&str] = &[
+    "frappe-analyze-project",
+    "frappe-search-doctype",
+    "frappe-app-doctypes",
+    "frappe-doctype-permissions-matrix",
+    "frappe-grep-fields",
+    "frappe-doctype-references",
+    "frappe-link-cycles",
+    "frappe-find-unused-doctypes",
+    "frappe-extract-translations",
+    "frappe-diff-doctype",
+    "frappe-lint-hooks",
+    "frappe-scheduler-events",
+    "frappe-list-processes",
+    "frappe-grep-logs",
+];
+
+// Run by `/frappe-console --preload` before the first prompt, via PYTHONSTARTUP
+const CONSOLE_PRELOAD: &str = "import frappe
+import frappe.db
+frappe.flags.in_test = False
+";
 
 struct LatteExtension {
-    cached_frappe_config: Option<FrappeConfig>,
     frappe_analyzer: FrappeAnalyzer,
-    process_manager: ProcessManager,
+    analyzed_project: Mutex<Option<FrappeAnalyzer>>,
+    process_manager: Arc<ProcessManager>,
+    test_progress: SharedTestProgress,
+    test_watchers: Mutex<HashMap<String, TestWatcher>>,
+    settings: Mutex<LoadedSettings>,
+    settings_error: Mutex<Option<String>>,
+    bench_override: Mutex<Option<String>>,
+}
+
+/// DocType kind switches accepted by `frappe-new-doctype`.
+#[derive(Debug, Clone, Default)]
+struct DocTypeFlags {
+    single: bool,
+    submittable: bool,
+    tree: bool,
+    istable: bool,
+}
+
+impl DocTypeFlags {
+    fn from_args(parsed: &CommandArgs) -> Result<Self, String> {
+        let flags = DocTypeFlags {
+            single: parsed.has("single"),
+            submittable: parsed.has("submittable"),
+            tree: parsed.has("tree"),
+            istable: parsed.has("istable"),
+        };
+
+        let conflicts = [
+            (flags.single && flags.istable, "--single", "--istable"),
+            (flags.single && flags.tree, "--single", "--tree"),
+            (flags.istable && flags.tree, "--istable", "--tree"),
+        ];
+        if let Some((_, a, b)) = conflicts.iter().find(|(conflict, _, _)| *conflict) {
+            return Err(format!("{} and {} cannot be combined", a, b));
+        }
+        Ok(flags)
+    }
+
+    fn enabled(&self) -> Vec<&'static str> {
+        [
+            (self.single, "single"),
+            (self.submittable, "submittable"),
+            (self.tree, "tree"),
+            (self.istable, "istable"),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, name)| *name)
+        .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -32,16 +112,21 @@ struct FrappeConfig {
 impl Extension for LatteExtension {
     fn new() -> Self {
         Self {
-            cached_frappe_config: None,
             frappe_analyzer: FrappeAnalyzer::new(),
-            process_manager: ProcessManager::new(),
+            analyzed_project: Mutex::new(None),
+            process_manager: Arc::new(ProcessManager::new()),
+            test_progress: Arc::new(Mutex::new(HashMap::new())),
+            test_watchers: Mutex::new(HashMap::new()),
+            settings: Mutex::new(LoadedSettings::default()),
+            settings_error: Mutex::new(None),
+            bench_override: Mutex::new(None),
         }
     }
 
     fn complete_slash_command_argument(
         &self,
         command: SlashCommand,
-        args: Vec<String>,
+        _args: Vec<String>,
     ) -> Result<Vec<SlashCommandArgumentCompletion>, String> {
         match command.name.as_str() {
             "frappe-new-app" => Ok(vec![SlashCommandArgumentCompletion {
@@ -66,6 +151,18 @@ impl Extension for LatteExtension {
                     run_command: false,
                 },
             ]),
+            "frappe-install-app" => Ok(vec![
+                SlashCommandArgumentCompletion {
+                    label: "app_name".to_string(),
+                    new_text: "app_name".to_string(),
+                    run_command: false,
+                },
+                SlashCommandArgumentCompletion {
+                    label: "site_name.local".to_string(),
+                    new_text: "site_name.local".to_string(),
+                    run_command: false,
+                },
+            ]),
             _ => Ok(vec![]),
         }
     }
@@ -77,175 +174,197 @@ impl Extension for LatteExtension {
         worktree: Option<&Worktree>,
     ) -> Result<SlashCommandOutput, String> {
         let worktree = worktree.ok_or("No worktree provided".to_string())?;
-        match command.name.as_str() {
-            "frappe-bench-start" => self.run_bench_command("start", &[], worktree),
+        let args = self.apply_bench_override(args, worktree)?;
+
+        // A broken .latte.json fails every command except the one that shows it
+        if let Err(e) = self.load_settings(worktree) {
+            if command.name != "frappe-show-config" {
+                return Err(e);
+            }
+        }
+
+        // Processes started before an extension restart are re-adopted here,
+        // since the bench isn't known until the first command runs
+        if let Some(config) = self.detect_frappe_workspace(worktree) {
+            self.process_manager.restore_registry(&config.bench_path);
+        }
+
+        let paginated = PAGINATED_COMMANDS.contains(&command.name.as_str());
+        let (args, max_lines) = if paginated {
+            Self::take_lines_arg(args)?
+        } else {
+            (args, None)
+        };
+
+        let output = match command.name.as_str() {
+            "frappe-bench-start" => {
+                let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                self.run_bench_command("start", &args, "frappe-bench-start", worktree)
+            }
             "frappe-bench-stop" => self.stop_bench_process(worktree),
-            "frappe-bench-migrate" => self.run_bench_command("migrate", &[], worktree),
-            "frappe-bench-build" => self.run_bench_command("build", &[], worktree),
+            "frappe-bench" => self.run_bench_passthrough(&args, worktree),
+            "frappe-bench-migrate" => self.run_migrate(&args, worktree),
+            "frappe-migrate-report" => self.migrate_report(&args),
+            "frappe-clear-cache" => self.clear_cache(&args, worktree),
+            "frappe-reindex-search" => {
+                self.reindex_search(args.first().map(|s| s.as_str()), worktree)
+            }
+            "frappe-bench-build" => self.run_build(&args, worktree),
             "frappe-new-app" => {
                 if args.is_empty() {
                     return Err("App name is required".to_string());
                 }
-                self.run_bench_command("new-app", &[&args[0]], worktree)
+                self.create_new_app(&args, worktree)
             }
             "frappe-new-site" => {
                 if args.is_empty() {
                     return Err("Site name is required".to_string());
                 }
-                self.run_bench_command("new-site", &[&args[0]], worktree)
+                self.run_bench_command("new-site", &[&args[0]], "frappe-new-site", worktree)
             }
-            "frappe-console" => self.open_frappe_console(worktree),
-            "frappe-mariadb" => self.open_mariadb_repl(worktree),
-            "frappe-new-doctype" => {
-                if args.len() < 2 {
-                    return Err("DocType name and module are required".to_string());
+            "frappe-install-app" => {
+                if args.is_empty() {
+                    return Err("App name is required".to_string());
                 }
-                self.generate_doctype(&args[0], &args[1], worktree)
+                self.install_app(&args[0], args.get(1).map(|s| s.as_str()), worktree)
             }
+            "frappe-console" => self.open_frappe_console(&args, worktree),
+            "frappe-mariadb" => self.open_mariadb_repl(worktree),
+            "frappe-db-query" => self.run_db_query(&args, worktree),
+            "frappe-exec" => self.exec_python(&args, worktree),
+            "frappe-new-doctype" => self.generate_doctype(&args, worktree),
             "frappe-new-page" => {
                 if args.is_empty() {
                     return Err("Page name is required".to_string());
                 }
                 self.generate_page(&args[0], worktree)
             }
-            "frappe-new-report" => {
+            "frappe-new-report" => self.generate_report(&args, worktree),
+            "frappe-new-server-script" => self.generate_script(ScriptKind::Server, &args, worktree),
+            "frappe-new-client-script" => self.generate_script(ScriptKind::Client, &args, worktree),
+            "frappe-new-workflow" => self.generate_workflow(&args, worktree),
+            "frappe-new-api" => self.generate_api_method(&args, worktree),
+            "frappe-new-notification" => self.generate_notification(&args, worktree),
+            "frappe-new-web-form" => self.generate_web_form(&args, worktree),
+            "frappe-new-number-card" => self.generate_number_card(&args, worktree),
+            "frappe-new-dashboard" => self.generate_dashboard(&args, worktree),
+            "frappe-new-email-template" => self.generate_email_template(&args, worktree),
+            "frappe-new-role" => self.generate_role(&args, worktree),
+            "frappe-new-role-profile" => self.generate_role_profile(&args, worktree),
+            "frappe-run-tests" => {
+                let parsed = CommandArgs::parse(&args, &["format", "test", "module"])?;
+                let format = parsed
+                    .value("format")
+                    .map(TestReportFormat::parse)
+                    .transpose()?
+                    .unwrap_or(TestReportFormat::Human);
+                let selection = TestSelection {
+                    failfast: parsed.has("failfast"),
+                    module: parsed.value("module").map(|s| s.to_string()),
+                    test: parsed.value("test").map(|s| s.to_string()),
+                };
+                self.run_tests(
+                    parsed.get(0).unwrap_or("frappe"),
+                    format,
+                    &selection,
+                    parsed.has("test-site"),
+                    worktree,
+                )
+            }
+            "frappe-watch-tests" => {
                 if args.is_empty() {
-                    return Err("Report name is required".to_string());
+                    return Err("App name is required".to_string());
                 }
-                self.generate_report(&args[0], worktree)
+                self.watch_tests(&args[0], worktree)
             }
-            "frappe-run-tests" => {
-                let app = args.get(0).map(|s| s.as_str()).unwrap_or("frappe");
-                self.run_tests(app, worktree)
+            "frappe-run-ui-tests" => {
+                let app = args.first().ok_or("App name is required".to_string())?;
+                self.run_ui_tests(app, worktree)
+            }
+            "frappe-test-progress" => self.test_progress(args.first().map(|s| s.as_str())),
+            "frappe-watch-status" => self.watch_status(args.first().map(|s| s.as_str())),
+            "frappe-search-doctype" => self.search_doctypes(&args, worktree),
+            "frappe-goto-doctype" => {
+                if args.is_empty() {
+                    return Err("DocType name is required".to_string());
+                }
+                self.goto_doctype(&args.join(" "), worktree)
             }
-            "frappe-search-doctype" => {
-                let query = args.get(0).map(|s| s.as_str()).unwrap_or("");
-                self.search_doctypes(query, worktree)
+            "frappe-grep-fields" => self.grep_fields(&args, worktree),
+            "frappe-suggest-fieldtype" => self.suggest_fieldtypes(&args),
+            "frappe-doctype-references" => self.doctype_references(&args, worktree),
+            "frappe-link-cycles" => self.link_cycles(worktree),
+            "frappe-find-unused-doctypes" => self.find_unused_doctypes(worktree),
+            "frappe-extract-translations" => self.extract_translations(&args, worktree),
+            "frappe-diff-doctype" => self.diff_doctypes(&args, worktree),
+            "frappe-doctype-changes" => self.doctype_changes(&args, worktree),
+            "frappe-format-doctype" => self.format_doctype(&args, worktree),
+            "frappe-reorder-fields" => self.reorder_fields(&args, worktree),
+            "frappe-validate-doctype-json" => self.validate_doctype_json(&args, worktree),
+            "frappe-doctype-template" => self.doctype_template(&args, worktree),
+            "frappe-regen-controller" => self.regen_controller(&args, worktree),
+            "frappe-doc-export" => self.doc_export(&args, worktree),
+            "frappe-doctype-fields-csv" => self.doctype_fields_csv(&args, worktree),
+            "frappe-rename-doctype" => self.rename_doctype(&args, worktree),
+            "frappe-lint-hooks" => self.lint_hooks(args.first().map(|s| s.as_str()), worktree),
+            "frappe-scheduler-events" => {
+                self.scheduler_events(args.first().map(|s| s.as_str()), worktree)
             }
+            "frappe-site-config" => self.site_config(&args, worktree),
+            "frappe-diff-sites" => self.diff_sites(&args, worktree),
+            "frappe-maintenance-mode" => self.maintenance_mode(&args, worktree),
+            "frappe-list-apps" => self.list_apps(worktree),
+            "frappe-app-doctypes" => self.app_doctypes(&args, worktree),
+            "frappe-doctype-permissions-matrix" => {
+                self.permissions_matrix(args.first().map(|s| s.as_str()), worktree)
+            }
+            "frappe-procfile" => self.show_procfile(&args, worktree),
+            "frappe-git-status" => self.git_status(worktree),
             "frappe-analyze-project" => self.analyze_current_project(worktree),
-            "frappe-list-processes" => self.list_running_processes(),
+            "frappe-stats" => self.show_stats(worktree),
+            "frappe-show-config" => self.show_config(),
+            "frappe-doctor" => self.doctor(&args, worktree),
+            "frappe-health" => self.health_check(worktree),
+            "frappe-open-site" => self.open_site(args.first().map(|s| s.as_str()), worktree),
+            "frappe-frappe-logs" => self.frappe_logs(&args, worktree),
+            "frappe-list-processes" => self.list_running_processes(&args),
+            "frappe-grep-logs" => self.grep_logs(&args),
+            "frappe-save-logs" => self.save_logs(&args, worktree),
             "frappe-stop-all" => self.stop_all_processes(),
+            "frappe-kill-port" => self.kill_port(&args, worktree),
             _ => Err(format!("Unknown command: {}", command.name)),
-        }
+        };
+
+        let settings = self.settings();
+        let style = settings.output_style;
+        output
+            .map(|output| {
+                let text = if paginated {
+                    Self::paginate(&output.text, max_lines.unwrap_or(settings.max_output_lines))
+                } else {
+                    output.text
+                };
+                SlashCommandOutput {
+                    text: style.apply(&text),
+                    ..output
+                }
+            })
+            .map_err(|e| style.apply(&e))
     }
 }
 
 impl LatteExtension {
-    fn name(&self) -> &'static str {
-        "Latte"
-    }
-
-    fn slash_commands(&self) -> Vec<SlashCommand> {
-        vec![
-            SlashCommand {
-                name: "frappe-bench-start".to_string(),
-                description: "Start the Frappe bench development server".to_string(),
-                tooltip_text: "Runs 'bench start' and streams logs to Bench panel".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-bench-stop".to_string(),
-                description: "Stop the running Frappe bench process".to_string(),
-                tooltip_text: "Gracefully stops the bench development server".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-bench-migrate".to_string(),
-                description: "Run database migrations".to_string(),
-                tooltip_text: "Executes 'bench migrate' to update database schema".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-bench-build".to_string(),
-                description: "Build assets and translations".to_string(),
-                tooltip_text: "Runs 'bench build' to compile assets".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-new-app".to_string(),
-                description: "Create a new Frappe app".to_string(),
-                tooltip_text: "Scaffolds a new app with 'bench new-app <name>'".to_string(),
-                requires_argument: true,
-            },
-            SlashCommand {
-                name: "frappe-new-site".to_string(),
-                description: "Create a new Frappe site".to_string(),
-                tooltip_text: "Creates a new site with 'bench new-site <name>'".to_string(),
-                requires_argument: true,
-            },
-            SlashCommand {
-                name: "frappe-console".to_string(),
-                description: "Open Frappe console".to_string(),
-                tooltip_text: "Opens an interactive Python console for the site".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-mariadb".to_string(),
-                description: "Open MariaDB/MySQL console".to_string(),
-                tooltip_text: "Opens database console connected to current site".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-new-doctype".to_string(),
-                description: "Generate a new DocType".to_string(),
-                tooltip_text: "Creates DocType JSON, controller, and client script files"
-                    .to_string(),
-                requires_argument: true,
-            },
-            SlashCommand {
-                name: "frappe-new-page".to_string(),
-                description: "Generate a new Page".to_string(),
-                tooltip_text: "Scaffolds page files (.py, .js, .json)".to_string(),
-                requires_argument: true,
-            },
-            SlashCommand {
-                name: "frappe-new-report".to_string(),
-                description: "Generate a new Report".to_string(),
-                tooltip_text: "Creates report files and boilerplate".to_string(),
-                requires_argument: true,
-            },
-            SlashCommand {
-                name: "frappe-run-tests".to_string(),
-                description: "Run tests for an app".to_string(),
-                tooltip_text: "Executes tests and shows results in diagnostics".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-search-doctype".to_string(),
-                description: "Search DocTypes across all apps".to_string(),
-                tooltip_text: "Find DocTypes by name or module".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-analyze-project".to_string(),
-                description: "Analyze Frappe project structure".to_string(),
-                tooltip_text: "Scan and index all apps, DocTypes, and dependencies".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-list-processes".to_string(),
-                description: "List running Frappe processes".to_string(),
-                tooltip_text: "Show all bench processes and their status".to_string(),
-                requires_argument: false,
-            },
-            SlashCommand {
-                name: "frappe-stop-all".to_string(),
-                description: "Stop all running processes".to_string(),
-                tooltip_text: "Gracefully stop all bench and related processes".to_string(),
-                requires_argument: false,
-            },
-        ]
-    }
-
     fn detect_frappe_workspace(&self, worktree: &Worktree) -> Option<FrappeConfig> {
-        let worktree_path = worktree.root_path();
+        let worktree_path = self.bench_root(worktree);
         let worktree_path_buf = PathBuf::from(&worktree_path);
         let apps_txt = worktree_path_buf.join("apps.txt");
         let sites_dir = worktree_path_buf.join("sites");
-        let procfile = worktree_path_buf.join("Procfile");
 
         if apps_txt.exists() && sites_dir.exists() {
-            let default_site = self.get_default_site(&worktree_path_buf);
+            let default_site = self
+                .settings()
+                .default_site
+                .or_else(|| self.get_default_site(&worktree_path_buf));
 
             Some(FrappeConfig {
                 bench_path: worktree_path,
@@ -258,6 +377,126 @@ impl LatteExtension {
         }
     }
 
+    /// Strips `--bench <path>` from the arguments and, when present, makes that
+    /// path the bench for this command instead of the worktree root.
+    fn apply_bench_override(
+        &self,
+        args: Vec<String>,
+        worktree: &Worktree,
+    ) -> Result<Vec<String>, String> {
+        let mut remaining = Vec::with_capacity(args.len());
+        let mut bench_path = None;
+        let mut iter = args.into_iter();
+
+        while let Some(arg) = iter.next() {
+            if arg == "--bench" {
+                bench_path = Some(iter.next().ok_or("--bench requires a path".to_string())?);
+            } else if let Some(path) = arg.strip_prefix("--bench=") {
+                bench_path = Some(path.to_string());
+            } else {
+                remaining.push(arg);
+            }
+        }
+
+        // Relative paths are taken from the worktree root
+        let bench_path = bench_path.map(|path| {
+            Path::new(&worktree.root_path())
+                .join(path)
+                .to_string_lossy()
+                .to_string()
+        });
+
+        if let Some(path) = &bench_path {
+            if !self.frappe_analyzer.is_frappe_workspace(Path::new(path)) {
+                return Err(format!(
+                    "--bench {} is not a Frappe bench (expected apps.txt, sites/ and a Procfile)",
+                    path
+                ));
+            }
+        }
+
+        *self.bench_override.lock().unwrap() = bench_path;
+        Ok(remaining)
+    }
+
+    /// Pulls `--lines <n>` out of the arguments of a paginated command.
+    fn take_lines_arg(args: Vec<String>) -> Result<(Vec<String>, Option<usize>), String> {
+        let mut remaining = Vec::with_capacity(args.len());
+        let mut lines = None;
+        let mut iter = args.into_iter();
+
+        while let Some(arg) = iter.next() {
+            let value = if arg == "--lines" {
+                iter.next().ok_or("--lines requires a number".to_string())?
+            } else if let Some(value) = arg.strip_prefix("--lines=") {
+                value.to_string()
+            } else {
+                remaining.push(arg);
+                continue;
+            };
+            lines = Some(
+                value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or(format!(
+                        "--lines must be a positive number, got '{}'",
+                        value
+                    ))?,
+            );
+        }
+
+        Ok((remaining, lines))
+    }
+
+    /// Keeps the first `max_lines` lines of `text` and notes how many were cut.
+    fn paginate(text: &str, max_lines: usize) -> String {
+        let total = text.lines().count();
+        if total <= max_lines {
+            return text.to_string();
+        }
+
+        let mut page = text.lines().take(max_lines).collect::<Vec<_>>().join("\n");
+        page.push_str(&format!(
+            "\n… and {} more lines (use --lines to show more)\n",
+            total - max_lines
+        ));
+        page
+    }
+
+    fn bench_root(&self, worktree: &Worktree) -> String {
+        self.bench_override
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| worktree.root_path())
+    }
+
+    fn load_settings(&self, worktree: &Worktree) -> Result<(), String> {
+        let root_path = worktree.root_path();
+        let bench_root = self.bench_root(worktree);
+        match LatteSettings::load(&[Path::new(&root_path), Path::new(&bench_root)]) {
+            Ok(loaded) => {
+                self.process_manager.configure(&loaded.settings);
+                *self.settings.lock().unwrap() = loaded;
+                *self.settings_error.lock().unwrap() = None;
+                Ok(())
+            }
+            Err(e) => {
+                *self.settings_error.lock().unwrap() = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    fn settings(&self) -> LatteSettings {
+        self.settings.lock().unwrap().settings.clone()
+    }
+
+    fn command_timeout(&self) -> Duration {
+        Duration::from_secs(self.settings().command_timeout_secs)
+    }
+
     fn get_default_site(&self, bench_path: &Path) -> Option<String> {
         let common_site_path = bench_path.join("sites").join("common_site_config.json");
         if let Ok(content) = fs::read_to_string(&common_site_path) {
@@ -270,56 +509,84 @@ impl LatteExtension {
         None
     }
 
+    /// `initiator` is the slash command recorded on the started process.
     fn run_bench_command(
         &self,
         cmd: &str,
         args: &[&str],
+        initiator: &str,
         worktree: &Worktree,
     ) -> Result<SlashCommandOutput, String> {
         let config = self
             .detect_frappe_workspace(worktree)
             .ok_or("Not a Frappe workspace".to_string())?;
+        self.process_manager
+            .ensure_bench_executable(&config.bench_path)?;
 
         let args_vec: Vec<String> = args.iter().map(|s| s.to_string()).collect();
 
         let process_id = match cmd {
-            "start" => self
-                .process_manager
-                .start_bench_dev_server(&config.bench_path),
-            "migrate" => self
-                .process_manager
-                .run_bench_migrate(&config.bench_path, config.default_site.as_deref()),
-            "build" => self.process_manager.run_bench_build(&config.bench_path),
-            "new-app" => {
-                if let Some(app_name) = args.first() {
-                    self.process_manager
-                        .create_new_app(&config.bench_path, app_name)
-                } else {
-                    return Err("App name is required".to_string());
+            "start" => {
+                let force = args.contains(&"--force");
+                let keepalive = args.contains(&"--keepalive");
+                if let Some(id) = self.process_manager.bench_process_for(&config.bench_path) {
+                    if !force {
+                        return Ok(SlashCommandOutput {
+                            text: format!(
+                                "ℹ️ bench start is already running for this bench (Process ID: {})\nUse /frappe-bench-start --force to start another anyway.",
+                                id
+                            ),
+                            sections: vec![],
+                        });
+                    }
                 }
+                self.process_manager.start_bench_dev_server(
+                    &config.bench_path,
+                    force,
+                    keepalive,
+                    initiator,
+                )
             }
             "new-site" => {
                 if let Some(site_name) = args.first() {
                     self.process_manager
-                        .create_new_site(&config.bench_path, site_name)
+                        .create_new_site(&config.bench_path, site_name, initiator)
                 } else {
                     return Err("Site name is required".to_string());
                 }
             }
             _ => self.process_manager.start_bench_process(
                 format!("bench_{}_{}", cmd, chrono::Utc::now().timestamp()),
+                initiator,
                 &config.bench_path,
                 cmd,
                 args_vec,
             ),
         };
 
+        let what = match (cmd, args.first()) {
+            ("start", _) if args.contains(&"--keepalive") => format!(
+                "start with keepalive (up to {} restart(s) after a crash)",
+                self.process_manager.keepalive_restarts()
+            ),
+            _ => cmd.to_string(),
+        };
         match process_id {
             Ok(id) => {
-                let text = format!(
-                    "✅ Started bench {} (Process ID: {})\nBench Path: {}\nCheck logs for details.",
-                    cmd, id, config.bench_path
+                let mut text = format!(
+                    "✅ Started bench {} (Process ID: {})\nBench Path: {}\n",
+                    what, id, config.bench_path
                 );
+                if cmd == "start" {
+                    text.push_str(&format!(
+                        "URL: {}\n",
+                        self.frappe_analyzer.site_url(
+                            Path::new(&config.bench_path),
+                            config.default_site.as_deref()
+                        )
+                    ));
+                }
+                text.push_str("Check logs for details.");
                 Ok(SlashCommandOutput {
                     text,
                     sections: vec![],
@@ -329,84 +596,124 @@ impl LatteExtension {
         }
     }
 
-    fn stop_bench_process(&self, _worktree: &Worktree) -> Result<SlashCommandOutput, String> {
-        if let Some(bench_process_id) = self.process_manager.get_bench_process_id() {
-            match self.process_manager.stop_process(&bench_process_id) {
-                Ok(()) => Ok(SlashCommandOutput {
-                    text: "✅ Bench process stopped successfully".to_string(),
-                    sections: vec![],
-                }),
-                Err(e) => Err(format!("Failed to stop bench process: {}", e)),
-            }
-        } else {
-            Ok(SlashCommandOutput {
-                text: "ℹ️ No bench process is currently running".to_string(),
-                sections: vec![],
-            })
-        }
-    }
-
-    fn open_frappe_console(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+    fn run_migrate(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
         let config = self
             .detect_frappe_workspace(worktree)
             .ok_or("Not a Frappe workspace".to_string())?;
 
-        let site = config
-            .default_site
-            .unwrap_or_else(|| "localhost".to_string());
-
-        match self.process_manager.open_console(&config.bench_path, &site) {
-            Ok(process_id) => Ok(SlashCommandOutput {
-                text: format!(
-                    "🔧 Opening Frappe console for site: {} (Process ID: {})\nType your Python commands in the console.",
-                    site, process_id
-                ),
-                sections: vec![],
-            }),
-            Err(e) => Err(format!("Failed to open console: {}", e)),
+        let parsed = CommandArgs::parse(args, &[])?;
+        let all_sites = parsed.has("all-sites");
+        if parsed.has("dry-run") {
+            if all_sites {
+                return Err("--dry-run checks the default site only; drop --all-sites".to_string());
+            }
+            return self.pending_patches(&config);
         }
-    }
-
-    fn open_mariadb_repl(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
-        let config = self
-            .detect_frappe_workspace(worktree)
-            .ok_or("Not a Frappe workspace".to_string())?;
+        self.process_manager
+            .ensure_bench_executable(&config.bench_path)?;
+        let skip_failing = parsed.has("skip-failing");
+        let flags = if skip_failing {
+            "--skip-failing"
+        } else {
+            "none"
+        };
+        let (site, scope) = self.site_scope(&config, all_sites)?;
 
-        let site = config
-            .default_site
-            .unwrap_or_else(|| "localhost".to_string());
+        let process_id = self
+            .process_manager
+            .run_bench_migrate(
+                &config.bench_path,
+                site.as_deref(),
+                skip_failing,
+                "frappe-bench-migrate",
+            )
+            .map_err(|e| format!("Failed to start bench migrate: {}", e))?;
 
-        match self.process_manager.open_mariadb(&config.bench_path, &site) {
-            Ok(process_id) => Ok(SlashCommandOutput {
+        let outcome = self
+            .process_manager
+            .wait_for_exit(&process_id, self.command_timeout())
+            .and_then(|_| self.process_manager.migrate_outcome(&process_id));
+        let Some(outcome) = outcome else {
+            return Ok(SlashCommandOutput {
                 text: format!(
-                    "🗄️ Opening MariaDB console for site: {} (Process ID: {})\nYou can now run SQL queries directly.",
-                    site, process_id
+                    "⏳ bench migrate is still running after {}s (Process ID: {})\n{}\nFlags: {}\nRun /frappe-migrate-report {} for the summary once it finishes.",
+                    self.command_timeout().as_secs(),
+                    process_id,
+                    scope,
+                    flags,
+                    process_id
                 ),
                 sections: vec![],
-            }),
-            Err(e) => Err(format!("Failed to open MariaDB console: {}", e)),
-        }
-    }
+            });
+        };
 
-    fn generate_doctype(
-        &self,
-        doctype_name: &str,
-        module: &str,
-        worktree: &Worktree,
-    ) -> Result<SlashCommandOutput, String> {
-        let config = self
-            .detect_frappe_workspace(worktree)
-            .ok_or("Not a Frappe workspace".to_string())?;
+        let mut text = format!(
+            "{} bench migrate {} (Process ID: {})\n{}\nFlags: {}\n\n",
+            if outcome.succeeded { "✅" } else { "❌" },
+            if outcome.succeeded {
+                "finished"
+            } else {
+                "failed"
+            },
+            process_id,
+            scope,
+            flags
+        );
+        text.push_str(&Self::migrate_summary(&outcome));
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
 
-        let doctype_json = self.create_doctype_json(doctype_name, module);
-        let controller_py = self.create_doctype_controller(doctype_name, module);
-        let client_js = self.create_doctype_client_script(doctype_name);
+    fn migrate_report(&self, args: &[String]) -> Result<SlashCommandOutput, String> {
+        let process_id = match args.first() {
+            Some(id) => id.clone(),
+            None => self
+                .process_manager
+                .latest_migrate()
+                .ok_or("No bench migrate has been run yet".to_string())?,
+        };
+        let Some(outcome) = self.process_manager.migrate_outcome(&process_id) else {
+            return match self.process_manager.get_process_info(&process_id) {
+                Some(info)
+                    if info.command.contains("migrate")
+                        && matches!(
+                            info.status,
+                            ProcessStatus::Starting | ProcessStatus::Running
+                        ) =>
+                {
+                    Ok(SlashCommandOutput {
+                        text: format!(
+                            "⏳ bench migrate is still running (Process ID: {})\nCheck logs for progress.",
+                            process_id
+                        ),
+                        sections: vec![],
+                    })
+                }
+                _ => Err(format!(
+                    "{} is not a bench migrate started by Latte",
+                    process_id
+                )),
+            };
+        };
 
-        let snake_case = doctype_name.to_lowercase().replace(" ", "_");
-        let text = format!(
-            "Generated DocType: {}\nModule: {}\nFiles created:\n- {}.json\n- {}.py\n- {}.js",
-            doctype_name, module, snake_case, snake_case, snake_case
+        let mut text = format!(
+            "{} bench migrate {} (Process ID: {})\n\n",
+            if outcome.succeeded { "✅" } else { "❌" },
+            if outcome.succeeded {
+                "finished"
+            } else {
+                "failed"
+            },
+            process_id
         );
+        text.push_str(&Self::migrate_summary(&outcome));
 
         Ok(SlashCommandOutput {
             text,
@@ -414,342 +721,3947 @@ impl LatteExtension {
         })
     }
 
-    fn generate_page(
+    /// Phase timings, patch outcomes and errors of a finished migrate.
+    fn migrate_summary(outcome: &MigrateOutcome) -> String {
+        let (phases, report) = (&outcome.phases, &outcome.report);
+        let mut text = String::new();
+        if !phases.is_empty() {
+            text.push_str("Phases:\n");
+            let several_sites = phases.iter().any(|phase| phase.site != phases[0].site);
+            for phase in phases {
+                let site = match (&phase.site, several_sites) {
+                    (Some(site), true) => format!("{}: ", site),
+                    _ => String::new(),
+                };
+                text.push_str(&format!(
+                    "  {} {}{} ({:.1}s){}\n",
+                    if phase.succeeded { "✅" } else { "❌" },
+                    site,
+                    phase.kind.label(),
+                    phase.duration.as_secs_f64(),
+                    if phase.succeeded {
+                        ""
+                    } else {
+                        " ← failed here"
+                    }
+                ));
+            }
+            text.push('\n');
+        }
+        text.push_str(&format!(
+            "Patches: {} applied, {} skipped, {} failed\n",
+            report.applied.len(),
+            report.skipped.len(),
+            report.failed.len()
+        ));
+        for patch in &report.applied {
+            text.push_str(&format!("  ✅ {}\n", patch));
+        }
+        for patch in &report.skipped {
+            text.push_str(&format!("  ⚠️ {} (skipped)\n", patch));
+        }
+        for patch in &report.failed {
+            text.push_str(&format!("  ❌ {}\n", patch));
+        }
+
+        if !report.errors.is_empty() {
+            text.push_str("\nErrors:\n");
+            for error in &report.errors {
+                text.push_str(&format!(
+                    "  {}:{} {}\n",
+                    error.file_path, error.line_number, error.message
+                ));
+            }
+        } else if !outcome.succeeded {
+            let reason = outcome.last_line.as_deref().unwrap_or("no output");
+            text.push_str(&format!("\nError: {}\n", reason));
+        }
+        text
+    }
+
+    /// The `--site` value for a site-scoped bench command and a line naming
+    /// the affected sites. `all_sites` passes `--site all`, which bench
+    /// expands to every site in the bench.
+    fn site_scope(
         &self,
-        page_name: &str,
+        config: &FrappeConfig,
+        all_sites: bool,
+    ) -> Result<(Option<String>, String), String> {
+        if !all_sites {
+            let scope = format!(
+                "Site: {}",
+                config.default_site.as_deref().unwrap_or("(bench default)")
+            );
+            return Ok((config.default_site.clone(), scope));
+        }
+
+        let sites: Vec<String> = self
+            .frappe_analyzer
+            .discover_sites(Path::new(&config.bench_path))?
+            .into_iter()
+            .map(|site| site.name)
+            .collect();
+        if sites.is_empty() {
+            return Err(format!("No sites found in {}/sites", config.bench_path));
+        }
+        let scope = format!("Sites: all {} ({})", sites.len(), sites.join(", "));
+        Ok((Some("all".to_string()), scope))
+    }
+
+    fn run_build(
+        &self,
+        args: &[String],
         worktree: &Worktree,
     ) -> Result<SlashCommandOutput, String> {
         let config = self
             .detect_frappe_workspace(worktree)
             .ok_or("Not a Frappe workspace".to_string())?;
+        let parsed = CommandArgs::parse(args, &[])?;
+        let app = match parsed.positional() {
+            [] => None,
+            [app] => Some(app.as_str()),
+            _ => return Err("Usage: /frappe-bench-build [app] [--all-sites]".to_string()),
+        };
+        self.process_manager
+            .ensure_bench_executable(&config.bench_path)?;
 
-        let snake_case = page_name.to_lowercase().replace(" ", "_");
-        let text = format!(
-            "Generated Page: {}\nFiles created:\n- {}.py\n- {}.js\n- {}.json",
-            page_name, snake_case, snake_case, snake_case
-        );
+        if let Some(app) = app {
+            let listed = self
+                .frappe_analyzer
+                .read_apps_txt(Path::new(&config.bench_path))
+                .unwrap_or_default();
+            if !listed.iter().any(|a| a == app) && !Path::new(&config.apps_path).join(app).is_dir()
+            {
+                return Err(format!(
+                    "App '{}' not found in apps.txt or {}",
+                    app, config.apps_path
+                ));
+            }
+            if let Some(version) = self.process_manager.bench_version(&config.bench_path) {
+                if !version.supports_app_build() {
+                    return Err(format!(
+                        "bench {} does not support `bench build --app`; upgrade bench or run /frappe-bench-build without an app",
+                        version.raw
+                    ));
+                }
+            }
+        }
+
+        // Builds are bench-wide; without --all-sites no --site is passed
+        let (site, scope) = if parsed.has("all-sites") {
+            let (site, scope) = self.site_scope(&config, true)?;
+            (site, Some(scope))
+        } else {
+            (None, None)
+        };
+        let process_id = self
+            .process_manager
+            .run_bench_build(
+                &config.bench_path,
+                app,
+                site.as_deref(),
+                "frappe-bench-build",
+            )
+            .map_err(|e| format!("Failed to start bench build: {}", e))?;
 
+        let mut text = format!(
+            "✅ Started bench build{} (Process ID: {})\nBench Path: {}\n",
+            app.map(|app| format!(" for app {}", app))
+                .unwrap_or_default(),
+            process_id,
+            config.bench_path
+        );
+        if let Some(scope) = scope {
+            text.push_str(&format!("{}\n", scope));
+        }
+        text.push_str("Check logs for details.");
         Ok(SlashCommandOutput {
             text,
             sections: vec![],
         })
     }
 
-    fn generate_report(
+    fn clear_cache(
         &self,
-        report_name: &str,
+        args: &[String],
         worktree: &Worktree,
     ) -> Result<SlashCommandOutput, String> {
         let config = self
             .detect_frappe_workspace(worktree)
             .ok_or("Not a Frappe workspace".to_string())?;
+        let parsed = CommandArgs::parse(args, &[])?;
+        self.process_manager
+            .ensure_bench_executable(&config.bench_path)?;
 
-        let snake_case = report_name.to_lowercase().replace(" ", "_");
-        let text = format!(
-            "Generated Report: {}\nFiles created:\n- {}.py\n- {}.js\n- {}.json",
-            report_name, snake_case, snake_case, snake_case
-        );
+        let (site, scope) = self.site_scope(&config, parsed.has("all-sites"))?;
+        let site = site.ok_or("No default site configured".to_string())?;
+        let process_id = self
+            .process_manager
+            .run_clear_cache(&config.bench_path, &site, "frappe-clear-cache")
+            .map_err(|e| format!("Failed to start bench clear-cache: {}", e))?;
 
         Ok(SlashCommandOutput {
-            text,
+            text: format!(
+                "✅ Started bench clear-cache (Process ID: {})\n{}\nCheck logs for details.",
+                process_id, scope
+            ),
             sections: vec![],
         })
     }
 
-    fn run_tests(&self, app: &str, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+    fn reindex_search(
+        &self,
+        site: Option<&str>,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
         let config = self
             .detect_frappe_workspace(worktree)
             .ok_or("Not a Frappe workspace".to_string())?;
+        let site = match site {
+            Some(site) => site.to_string(),
+            None => config
+                .default_site
+                .clone()
+                .ok_or("No default site configured".to_string())?,
+        };
+        if !Path::new(&config.sites_path).join(&site).is_dir() {
+            return Err(format!(
+                "Site '{}' not found in {}",
+                site, config.sites_path
+            ));
+        }
+        self.process_manager
+            .ensure_bench_executable(&config.bench_path)?;
 
-        let site = config
-            .default_site
-            .unwrap_or_else(|| "localhost".to_string());
-
-        let test_runner = TestRunner::new(config.bench_path.clone(), site);
+        let subcommand = "build-search-index";
+        let process_id = self
+            .process_manager
+            .run_reindex_search(&config.bench_path, &site, "frappe-reindex-search")
+            .map_err(|e| format!("Failed to start bench {}: {}", subcommand, e))?;
 
-        match test_runner.run_app_tests(app) {
+        let Some(info) = self
+            .process_manager
+            .wait_for_exit(&process_id, self.command_timeout())
+        else {
+            return Ok(SlashCommandOutput {
+                text: format!(
+                    "⏳ bench {} is still running after {}s (Process ID: {})\nSite: {}\nCheck logs for progress.",
+                    subcommand,
+                    self.command_timeout().as_secs(),
+                    process_id,
+                    site
+                ),
+                sections: vec![],
+            });
+        };
+
+        let lines: Vec<String> = info
+            .output_lines
+            .iter()
+            .chain(&info.error_lines)
+            .cloned()
+            .collect();
+        let succeeded = info.status == ProcessStatus::Stopped;
+        if !succeeded && lines.iter().any(|line| line.contains("No such command")) {
+            return Err(format!(
+                "This bench has no `{}` command, so the website search index can't be rebuilt here (Process ID: {})",
+                subcommand, process_id
+            ));
+        }
+        let mut text = format!(
+            "{} bench {} {} (Process ID: {})\nSite: {}\n",
+            if succeeded { "✅" } else { "❌" },
+            subcommand,
+            if succeeded { "finished" } else { "failed" },
+            process_id,
+            site
+        );
+        match process_manager::indexed_document_count(&lines) {
+            Some(count) => text.push_str(&format!("Indexed documents: {}\n", count)),
+            None if succeeded => text.push_str("Indexed documents: not reported by bench\n"),
+            None => {}
+        }
+        if !succeeded {
+            let reason = info
+                .error_lines
+                .last()
+                .or(info.output_lines.last())
+                .cloned()
+                .unwrap_or_else(|| "no output".to_string());
+            text.push_str(&format!("\nError: {}\n", reason));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    /// `bench migrate` has no dry-run, so compare patches.txt against the
+    /// site's Patch Log instead.
+    fn pending_patches(&self, config: &FrappeConfig) -> Result<SlashCommandOutput, String> {
+        let site = config
+            .default_site
+            .clone()
+            .ok_or("No default site configured".to_string())?;
+
+        let apps = self
+            .frappe_analyzer
+            .read_apps_txt(Path::new(&config.bench_path))?;
+        let patches: Vec<(String, String)> = apps
+            .iter()
+            .flat_map(|app| {
+                self.frappe_analyzer
+                    .read_patches(&Path::new(&config.apps_path).join(app), app)
+                    .into_iter()
+                    .map(move |patch| (app.clone(), patch))
+            })
+            .collect();
+
+        let process_id = self
+            .process_manager
+            .run_db_query(
+                &config.bench_path,
+                &site,
+                "select patch from `tabPatch Log`",
+                "frappe-bench-migrate",
+            )
+            .map_err(|e| format!("Failed to read Patch Log: {}", e))?;
+        let applied: Option<HashSet<String>> = self
+            .process_manager
+            .wait_for_exit(&process_id, self.command_timeout())
+            .filter(|info| info.status == ProcessStatus::Stopped)
+            .map(|info| info.output_lines.into_iter().skip(1).collect());
+
+        let mut text = format!(
+            "📋 Dry run for site {} (nothing was executed)\nFlags: --dry-run\n\n",
+            site
+        );
+        let pending: Vec<&(String, String)> = match &applied {
+            Some(applied) => patches
+                .iter()
+                .filter(|(_, patch)| !applied.contains(patch))
+                .collect(),
+            None => {
+                text.push_str("⚠️ Could not read the Patch Log, listing every patch\n");
+                patches.iter().collect()
+            }
+        };
+
+        if pending.is_empty() {
+            text.push_str("✅ No pending patches");
+        } else {
+            text.push_str(&format!("Pending patches ({}):\n", pending.len()));
+            for (app, patch) in pending {
+                text.push_str(&format!("  {}: {}\n", app, patch));
+            }
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn create_new_app(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+
+        let parsed = CommandArgs::parse(args, &["title", "publisher", "email", "description"])?;
+        let app_name = parsed.get(0).ok_or("App name is required".to_string())?;
+
+        let mut metadata = NewAppMetadata {
+            title: parsed.value("title").map(|s| s.to_string()),
+            description: parsed.value("description").map(|s| s.to_string()),
+            publisher: parsed.value("publisher").map(|s| s.to_string()),
+            email: parsed.value("email").map(|s| s.to_string()),
+        };
+
+        metadata.fill_defaults(Path::new(&config.bench_path));
+        let process_id = self
+            .process_manager
+            .create_new_app(&config.bench_path, app_name, &metadata, "frappe-new-app")
+            .map_err(|e| format!("Failed to start bench new-app: {}", e))?;
+
+        let mut text = format!(
+            "✅ Started bench new-app {} (Process ID: {})\nBench Path: {}\n",
+            app_name, process_id, config.bench_path
+        );
+        text.push_str(&format!(
+            "Title: {}\n",
+            metadata
+                .title
+                .clone()
+                .unwrap_or_else(|| NewAppMetadata::default_title(app_name))
+        ));
+        if let Some(publisher) = &metadata.publisher {
+            text.push_str(&format!("Publisher: {}\n", publisher));
+        }
+        if let Some(email) = &metadata.email {
+            text.push_str(&format!("Email: {}\n", email));
+        }
+        if let Some(version) = self.process_manager.bench_version(&config.bench_path) {
+            if !version.supports_new_app_prompts() {
+                text.push_str(&format!(
+                    "⚠️ bench {} predates the license, workflow and branch prompts; only title, description, publisher and email were answered.\n",
+                    version.raw
+                ));
+            }
+        }
+        text.push_str("Check logs for details.");
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn grep_logs(&self, args: &[String]) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["process"])?;
+        let pattern = parsed.positional().join(" ");
+        if pattern.is_empty() {
+            return Err("Search pattern is required".to_string());
+        }
+
+        let process_id = match parsed.value("process") {
+            Some(id) => id.to_string(),
+            None => self
+                .process_manager
+                .get_bench_process_id()
+                .ok_or("No bench process is running; pass --process <id>".to_string())?,
+        };
+
+        let matches = self.process_manager.search_logs(&process_id, &pattern)?;
+        if matches.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: format!("ℹ️ No log lines in {} match /{}/", process_id, pattern),
+                sections: vec![],
+            });
+        }
+
+        let mut text = format!(
+            "🔎 {} matching lines in {} for /{}/\n\n",
+            matches.len(),
+            process_id,
+            pattern
+        );
+        for log in &matches {
+            let time: chrono::DateTime<chrono::Local> = log.timestamp.into();
+            text.push_str(&format!(
+                "{} {}\n",
+                time.format("%H:%M:%S"),
+                process_manager::format_log_line(log)
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn save_logs(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let (process_id, path) = match parsed.positional() {
+            [path] => (
+                self.process_manager
+                    .get_bench_process_id()
+                    .ok_or("No bench process is running; pass a process id".to_string())?,
+                path,
+            ),
+            [process_id, path] => (process_id.clone(), path),
+            _ => return Err("Usage: /frappe-save-logs [process_id] <path> [--raw]".to_string()),
+        };
+        if self.process_manager.get_process_info(&process_id).is_none() {
+            return Err(format!("Process {} not found", process_id));
+        }
+
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let bench_path = Path::new(&config.bench_path);
+        let relative = Path::new(path);
+        let target = if relative.is_absolute() {
+            relative.to_path_buf()
+        } else {
+            bench_path.join(relative)
+        };
+        if !target.starts_with(bench_path)
+            || relative
+                .components()
+                .any(|c| c == std::path::Component::ParentDir)
+        {
+            return Err(format!("{} is outside the bench", path));
+        }
+
+        let content = if parsed.has("raw") {
+            self.process_manager
+                .get_process_logs(&process_id)
+                .into_iter()
+                .map(|log| (log.content + "\n").repeat(log.repeat_count as usize))
+                .collect()
+        } else {
+            self.process_manager.format_logs_for_display(&process_id)
+        };
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&target, &content)
+            .map_err(|e| format!("Could not write {}: {}", target.display(), e))?;
+
+        Ok(SlashCommandOutput {
+            text: format!(
+                "💾 Saved {} logs ({} bytes) to {}",
+                process_id,
+                content.len(),
+                target.display()
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn kill_port(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let port: u16 = parsed
+            .get(0)
+            .and_then(|p| p.parse().ok())
+            .ok_or("Usage: /frappe-kill-port <port> [--force]".to_string())?;
+
+        let Some(pid) = process_manager::find_pid_by_port(port) else {
+            return Ok(SlashCommandOutput {
+                text: format!("ℹ️ Nothing is listening on port {}", port),
+                sections: vec![],
+            });
+        };
+        let command_line = process_manager::process_command_line(pid)
+            .unwrap_or_else(|| "(command line unavailable)".to_string());
+
+        let bench_ports = self
+            .detect_frappe_workspace(worktree)
+            .map(|config| {
+                self.frappe_analyzer
+                    .bench_ports(Path::new(&config.bench_path))
+            })
+            .unwrap_or_default();
+        if !bench_ports.contains(&port) && !parsed.has("force") {
+            return Ok(SlashCommandOutput {
+                text: format!(
+                    "⚠️ Port {} is not one of this bench's ports ({})\nPID {}: {}\n\nRun /frappe-kill-port {} --force to kill it anyway.",
+                    port,
+                    bench_ports
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    pid,
+                    command_line,
+                    port
+                ),
+                sections: vec![],
+            });
+        }
+
+        process_manager::terminate_pid(pid)?;
+        Ok(SlashCommandOutput {
+            text: format!(
+                "✅ Killed PID {} on port {}\nCommand: {}",
+                pid, port, command_line
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn stop_bench_process(&self, _worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        if let Some(bench_process_id) = self.process_manager.get_bench_process_id() {
+            match self.process_manager.stop_process(&bench_process_id) {
+                Ok(()) => Ok(SlashCommandOutput {
+                    text: "✅ Bench process stopped successfully".to_string(),
+                    sections: vec![],
+                }),
+                Err(e) => Err(format!("Failed to stop bench process: {}", e)),
+            }
+        } else {
+            Ok(SlashCommandOutput {
+                text: "ℹ️ No bench process is currently running".to_string(),
+                sections: vec![],
+            })
+        }
+    }
+
+    fn install_app(
+        &self,
+        app_name: &str,
+        site: Option<&str>,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+
+        let site = site
+            .map(|s| s.to_string())
+            .or(config.default_site.clone())
+            .ok_or("Site name is required (no default site configured)".to_string())?;
+
+        let listed_apps = self
+            .frappe_analyzer
+            .read_apps_txt(Path::new(&config.bench_path))
+            .unwrap_or_default();
+        if !listed_apps.iter().any(|a| a == app_name)
+            && !Path::new(&config.apps_path).join(app_name).is_dir()
+        {
+            return Err(format!(
+                "App '{}' not found in apps.txt or {}",
+                app_name, config.apps_path
+            ));
+        }
+
+        if !Path::new(&config.sites_path).join(&site).is_dir() {
+            return Err(format!(
+                "Site '{}' not found in {}",
+                site, config.sites_path
+            ));
+        }
+
+        let process_id = self
+            .process_manager
+            .install_app(&config.bench_path, &site, app_name, "frappe-install-app")
+            .map_err(|e| format!("Failed to install app: {}", e))?;
+
+        let text = match self
+            .process_manager
+            .wait_for_exit(&process_id, self.command_timeout())
+        {
+            Some(info)
+                if info
+                    .output_lines
+                    .iter()
+                    .chain(info.error_lines.iter())
+                    .any(|line| line.to_lowercase().contains("already installed")) =>
+            {
+                format!(
+                    "ℹ️ App {} is already installed on site {} (Process ID: {})",
+                    app_name, site, process_id
+                )
+            }
+            Some(info) if info.status == ProcessStatus::Failed => {
+                let reason = info
+                    .error_lines
+                    .last()
+                    .or(info.output_lines.last())
+                    .cloned()
+                    .unwrap_or_else(|| "no output".to_string());
+                return Err(format!(
+                    "Failed to install {} on {} (Process ID: {}): {}",
+                    app_name, site, process_id, reason
+                ));
+            }
+            Some(_) => format!(
+                "✅ Installed {} on site {} (Process ID: {})",
+                app_name, site, process_id
+            ),
+            None => format!(
+                "✅ Installing {} on site {} (Process ID: {})\nCheck logs for progress.",
+                app_name, site, process_id
+            ),
+        };
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn open_frappe_console(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let parsed = CommandArgs::parse(args, &[])?;
+
+        let site = config
+            .default_site
+            .unwrap_or_else(|| "localhost".to_string());
+
+        let preload = parsed.has("preload");
+        let started = if preload {
+            self.process_manager.open_console_with_preload(
+                &config.bench_path,
+                &site,
+                CONSOLE_PRELOAD,
+                "frappe-console",
+            )
+        } else {
+            self.process_manager
+                .open_console(&config.bench_path, &site, "frappe-console")
+        };
+
+        match started {
+            Ok(process_id) => Ok(SlashCommandOutput {
+                text: format!(
+                    "🔧 Opening Frappe console for site: {} (Process ID: {}){}\nType your Python commands in the console.",
+                    site,
+                    process_id,
+                    if preload {
+                        "\nPreloaded: frappe, frappe.db"
+                    } else {
+                        ""
+                    },
+                ),
+                sections: vec![],
+            }),
+            Err(e) => Err(format!("Failed to open console: {}", e)),
+        }
+    }
+
+    fn exec_python(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let code = args.join(" ");
+        let code = code.trim();
+        if code.is_empty() {
+            return Err("Python code or a dotted function path is required".to_string());
+        }
+
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let site = config
+            .default_site
+            .clone()
+            .ok_or("No default site configured".to_string())?;
+
+        let dotted = Regex::new(r"^[A-Za-z_]\w*(\.[A-Za-z_]\w*)+$").unwrap();
+        let (mode, process_id) = if dotted.is_match(code) {
+            (
+                "bench execute",
+                self.process_manager
+                    .run_execute(&config.bench_path, &site, code, "frappe-exec"),
+            )
+        } else {
+            (
+                "bench console",
+                self.process_manager.run_console_script(
+                    &config.bench_path,
+                    &site,
+                    code,
+                    "frappe-exec",
+                ),
+            )
+        };
+        let process_id = process_id.map_err(|e| format!("Failed to run {}: {}", mode, e))?;
+
+        let info = self
+            .process_manager
+            .wait_for_exit(&process_id, self.command_timeout())
+            .ok_or_else(|| {
+                let _ = self.process_manager.stop_process(&process_id);
+                format!(
+                    "{} did not finish within {}s",
+                    mode,
+                    self.command_timeout().as_secs()
+                )
+            })?;
+
+        // IPython echoes its prompts when fed from a pipe
+        let prompt = Regex::new(r"^(In \[\d+\]: |\s*\.\.\.: )+").unwrap();
+        let stdout: Vec<String> = info
+            .output_lines
+            .iter()
+            .map(|line| prompt.replace(line, "").to_string())
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let mut output = stdout.join("\n");
+        if !info.error_lines.is_empty() {
+            output.push_str(&format!("\n\nstderr:\n{}", info.error_lines.join("\n")));
+        }
+
+        let all_lines: Vec<String> = info
+            .output_lines
+            .iter()
+            .chain(&info.error_lines)
+            .cloned()
+            .collect();
+        if let Some(exception) = process_manager::python_exception(&all_lines) {
+            return Err(format!(
+                "Python raised {} on {}\n\n{}",
+                exception,
+                site,
+                output.trim()
+            ));
+        }
+        if info.status == ProcessStatus::Failed {
+            return Err(format!("{} failed on {}\n\n{}", mode, site, output.trim()));
+        }
+
+        Ok(SlashCommandOutput {
+            text: format!(
+                "✅ {} on {}\n\n{}",
+                mode,
+                site,
+                if output.trim().is_empty() {
+                    "(no output)"
+                } else {
+                    output.trim()
+                }
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn run_bench_passthrough(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        // Arguments belong to bench, so only --force is picked out
+        let force = args.iter().any(|arg| arg == "--force");
+        let mut rest = args.iter().filter(|arg| *arg != "--force").cloned();
+        let Some(subcommand) = rest.next() else {
+            return Err("Usage: /frappe-bench <subcommand> [args...]".to_string());
+        };
+        process_manager::check_bench_subcommand(&subcommand, force)?;
+
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        self.process_manager
+            .ensure_bench_executable(&config.bench_path)?;
+
+        let args: Vec<String> = rest.collect();
+        let id = self
+            .process_manager
+            .start_bench_process(
+                format!("bench_{}_{}", subcommand, chrono::Utc::now().timestamp()),
+                "frappe-bench",
+                &config.bench_path,
+                &subcommand,
+                args.clone(),
+            )
+            .map_err(|e| format!("Failed to start bench {}: {}", subcommand, e))?;
+
+        let mut command_line = format!("bench {}", subcommand);
+        for arg in &args {
+            command_line.push(' ');
+            command_line.push_str(arg);
+        }
+        Ok(SlashCommandOutput {
+            text: format!(
+                "✅ Started {} (Process ID: {})\nBench Path: {}\nCheck logs for details.",
+                command_line, id, config.bench_path
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn run_db_query(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        // SQL is passed through verbatim, so only --force is picked out
+        let force = args.iter().any(|arg| arg == "--force");
+        let sql = args
+            .iter()
+            .filter(|arg| *arg != "--force")
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if sql.trim().is_empty() {
+            return Err("SQL query is required".to_string());
+        }
+
+        if let Some(statement) = process_manager::destructive_statement(&sql) {
+            if !force {
+                return Err(format!(
+                    "Refusing to run destructive statement: {}\nAdd --force to run it anyway.",
+                    statement
+                ));
+            }
+        }
+
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let site = config
+            .default_site
+            .clone()
+            .ok_or("No default site configured".to_string())?;
+
+        let process_id = self
+            .process_manager
+            .run_db_query(&config.bench_path, &site, &sql, "frappe-db-query")
+            .map_err(|e| format!("Failed to run query: {}", e))?;
+
+        let info = self
+            .process_manager
+            .wait_for_exit(&process_id, self.command_timeout())
+            .ok_or_else(|| {
+                let _ = self.process_manager.stop_process(&process_id);
+                format!(
+                    "Query did not finish within {}s",
+                    self.command_timeout().as_secs()
+                )
+            })?;
+
+        if let Some(error) = info
+            .error_lines
+            .iter()
+            .find(|line| line.starts_with("ERROR"))
+        {
+            return Err(format!("Query failed on {}: {}", site, error));
+        }
+        if info.status == ProcessStatus::Failed {
+            return Err(format!(
+                "Query failed on {}: {}",
+                site,
+                info.error_lines
+                    .last()
+                    .cloned()
+                    .unwrap_or_else(|| "no output".to_string())
+            ));
+        }
+
+        let text = if info.output_lines.is_empty() {
+            format!("✅ Query OK on {} (no rows returned)", site)
+        } else {
+            format!(
+                "🗄️ {} ({} rows)\n\n{}",
+                site,
+                info.output_lines.len() - 1,
+                Self::format_tsv_table(&info.output_lines)
+            )
+        };
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    /// Aligns MariaDB batch output (tab-separated, header first) into columns.
+    fn format_tsv_table(lines: &[String]) -> String {
+        const MAX_ROWS: usize = 100;
+        let rows: Vec<Vec<&str>> = lines
+            .iter()
+            .take(MAX_ROWS + 1)
+            .map(|line| line.split('\t').collect())
+            .collect();
+
+        let mut widths: Vec<usize> = Vec::new();
+        for row in &rows {
+            for (i, cell) in row.iter().enumerate() {
+                let width = cell.chars().count();
+                match widths.get_mut(i) {
+                    Some(w) => *w = (*w).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+
+        let mut table = String::new();
+        for (index, row) in rows.iter().enumerate() {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| format!("{:<width$}", cell, width = widths[i]))
+                .collect();
+            table.push_str(cells.join(" | ").trim_end());
+            table.push('\n');
+            if index == 0 {
+                let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+                table.push_str(&rule.join("-+-"));
+                table.push('\n');
+            }
+        }
+
+        if lines.len() > MAX_ROWS + 1 {
+            table.push_str(&format!(
+                "... and {} more rows\n",
+                lines.len() - MAX_ROWS - 1
+            ));
+        }
+
+        table
+    }
+
+    fn open_mariadb_repl(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+
+        let site = config
+            .default_site
+            .unwrap_or_else(|| "localhost".to_string());
+
+        match self
+            .process_manager
+            .open_mariadb(&config.bench_path, &site, "frappe-mariadb") {
+            Ok(process_id) => Ok(SlashCommandOutput {
+                text: format!(
+                    "🗄️ Opening MariaDB console for site: {} (Process ID: {})\nYou can now run SQL queries directly.",
+                    site, process_id
+                ),
+                sections: vec![],
+            }),
+            Err(e) => Err(format!("Failed to open MariaDB console: {}", e)),
+        }
+    }
+
+    fn generate_doctype(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["from"])?;
+        let [doctype_name, module] = parsed.positional() else {
+            return Err("DocType name and module are required".to_string());
+        };
+        let flags = DocTypeFlags::from_args(&parsed)?;
+
+        self.detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+
+        if let Some(source) = parsed.value("from") {
+            if let Some(flag) = flags.enabled().first() {
+                return Err(format!(
+                    "--from copies {}'s settings and can't be combined with --{}",
+                    source, flag
+                ));
+            }
+            return self.duplicate_doctype(source, doctype_name, module, worktree);
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let files = analyzer.write_doctype(
+            doctype_name,
+            module,
+            &self.create_doctype_json(doctype_name, module, &flags),
+            &self.create_doctype_controller(doctype_name, module),
+            &self.create_doctype_client_script(doctype_name),
+        )?;
+
+        let snake_case = doctype_name.to_lowercase().replace(" ", "_");
+        let mut text = format!(
+            "Generated DocType: {}\nModule: {}\nFiles created:",
+            doctype_name, module
+        );
+        for file in &files {
+            text.push_str(&format!("\n- {}", Self::absolute_path(file)));
+        }
+        let enabled = flags.enabled();
+        if !enabled.is_empty() {
+            text.push_str(&format!("\nFlags: {}", enabled.join(", ")));
+        }
+        if flags.submittable {
+            text.push_str(
+                "\n\nℹ️ Submittable: documents go Draft (docstatus 0) → Submitted (1) → Cancelled (2). \
+                 Use on_submit/on_cancel in the controller, or /frappe-new-workflow for approval states.",
+            );
+        }
+        if flags.tree {
+            text.push_str(&format!(
+                "\n\nℹ️ Tree: the controller should extend frappe.utils.nestedset.NestedSet; parent_{} links each node to its parent.",
+                snake_case
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn duplicate_doctype(
+        &self,
+        source: &str,
+        doctype_name: &str,
+        module: &str,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let duplicate = analyzer.duplicate_doctype(source, doctype_name, module)?;
+        let files = analyzer.write_doctype(
+            doctype_name,
+            module,
+            &duplicate.json,
+            &self.create_doctype_controller(doctype_name, module),
+            &self.create_doctype_client_script(doctype_name),
+        )?;
+
+        let count = |key: &str| {
+            duplicate
+                .json
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map_or(0, |items| items.len())
+        };
+        let mut text = format!(
+            "Generated DocType: {} (copied from {})\nModule: {}\nFields: {}, Permissions: {}\nFiles created:\n",
+            doctype_name,
+            duplicate.source,
+            module,
+            count("fields"),
+            count("permissions")
+        );
+        for file in &files {
+            text.push_str(&format!("- {}\n", Self::absolute_path(file)));
+        }
+        if !duplicate.self_references.is_empty() {
+            text.push_str(&format!(
+                "\n⚠️ These fields still point at {}: {}\nChange their options to {} if they should reference the new DocType instead.",
+                duplicate.source,
+                duplicate.self_references.join(", "),
+                doctype_name
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_page(
+        &self,
+        page_name: &str,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        self.detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+
+        let snake_case = page_name.to_lowercase().replace(" ", "_");
+        let text = format!(
+            "Generated Page: {}\nFiles created:\n- {}.py\n- {}.js\n- {}.json",
+            page_name, snake_case, snake_case, snake_case
+        );
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_report(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["type"])?;
+        let [name, doctype] = parsed.positional() else {
+            return Err(
+                "Report name and DocType are required, e.g. \"Open Orders\" \"Sales Order\" --type query"
+                    .to_string(),
+            );
+        };
+        let kind = parsed
+            .value("type")
+            .map(ReportKind::parse)
+            .transpose()?
+            .unwrap_or(ReportKind::Script);
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let files = analyzer.generate_report(name, doctype, kind)?;
+
+        let mut text = format!(
+            "✅ Generated {}: {}\nDocType: {}\nFiles created:\n",
+            kind.report_type(),
+            name,
+            doctype
+        );
+        for file in &files {
+            text.push_str(&format!("- {}\n", Self::absolute_path(file)));
+        }
+        text.push_str(match kind {
+            ReportKind::Query => "\nℹ️ Edit the `query` in the JSON, then run `bench migrate`.",
+            ReportKind::Script => "\nℹ️ Fill in `get_data` in the .py, then run `bench migrate`.",
+        });
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_script(
+        &self,
+        kind: ScriptKind,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["event"])?;
+        let (Some(name), Some(doctype), None) = (parsed.get(0), parsed.get(1), parsed.get(2))
+        else {
+            return Err(format!(
+                "{} name and DocType are required, e.g. \"My Script\" \"Sales Invoice\"",
+                kind.doctype()
+            ));
+        };
+        if kind == ScriptKind::Client && parsed.value("event").is_some() {
+            return Err("--event only applies to Server Scripts".to_string());
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let path = analyzer.generate_script(kind, name, doctype, parsed.value("event"))?;
+
+        Ok(SlashCommandOutput {
+            text: format!(
+                "✅ Generated {}: {}\nDocType: {}\nFile created:\n- {}\n\nℹ️ Run `bench migrate` to import it.",
+                kind.doctype(),
+                name,
+                doctype,
+                Self::absolute_path(&path)
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn generate_workflow(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let [name, doctype] = parsed.positional() else {
+            return Err(
+                "Workflow name and DocType are required, e.g. \"Order Approval\" \"Sales Order\""
+                    .to_string(),
+            );
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let generated = analyzer.generate_workflow(name, doctype)?;
+
+        let mut text = format!(
+            "✅ Generated Workflow: {}\nDocType: {}\nStates: Draft → Approved\nFile created:\n- {}\n",
+            name,
+            doctype,
+            Self::absolute_path(&generated.path)
+        );
+        if generated.field_added {
+            text.push_str(&format!(
+                "\n➕ Added Select field workflow_state to {}\n",
+                Self::absolute_path(&generated.doctype_path)
+            ));
+        }
+        text.push_str("\nℹ️ Run `bench migrate` to import it.");
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_notification(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["field"])?;
+        let [name, doctype, event] = parsed.positional() else {
+            return Err(
+                "Notification name, DocType and event are required, e.g. \"Order Submitted\" \"Sales Order\" Submit"
+                    .to_string(),
+            );
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let files = analyzer.generate_notification(name, doctype, event, parsed.value("field"))?;
+
+        let mut text = format!(
+            "✅ Generated Notification: {}\nDocType: {}\nEvent: {}\nFiles created:\n",
+            name, doctype, event
+        );
+        for file in &files {
+            text.push_str(&format!("- {}\n", Self::absolute_path(file)));
+        }
+        text.push_str(
+            "\nℹ️ Recipients default to the document owner. Run `bench migrate` to import it.",
+        );
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_web_form(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let [name, doctype] = parsed.positional() else {
+            return Err(
+                "Web Form name and DocType are required, e.g. \"Job Application\" \"Job Applicant\""
+                    .to_string(),
+            );
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let form = analyzer.generate_web_form(name, doctype)?;
+
+        let mut text = format!(
+            "✅ Generated Web Form: {}\nDocType: {} ({} fields)\nRoute: {}\nFiles created:\n",
+            name, doctype, form.fields, form.route
+        );
+        for file in &form.files {
+            text.push_str(&format!("- {}\n", Self::absolute_path(file)));
+        }
+        text.push_str(
+            "\nℹ️ The form requires login; set login_required to 0 to accept guests. Run `bench migrate` to import it.",
+        );
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_number_card(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["field"])?;
+        let [name, doctype, function] = parsed.positional() else {
+            return Err(
+                "Number Card name, DocType and function are required, e.g. \"Open Orders\" \"Sales Order\" Count"
+                    .to_string(),
+            );
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let files =
+            analyzer.generate_number_card(name, doctype, function, parsed.value("field"))?;
+
+        let mut text = format!(
+            "✅ Generated Number Card: {}\nDocType: {}\nFunction: {}{}\nFiles created:\n",
+            name,
+            doctype,
+            function,
+            parsed
+                .value("field")
+                .map(|field| format!(" of {}", field))
+                .unwrap_or_default()
+        );
+        for file in &files {
+            text.push_str(&format!("- {}\n", Self::absolute_path(file)));
+        }
+        text.push_str("\nℹ️ Run `bench migrate` to import it, then add it to a Dashboard with /frappe-new-dashboard.");
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_dashboard(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["charts", "cards"])?;
+        let [name] = parsed.positional() else {
+            return Err(
+                "Dashboard name is required, e.g. \"Sales\" --charts \"Monthly Sales\" --cards \"Open Orders\""
+                    .to_string(),
+            );
+        };
+        let list = |key: &str| -> Vec<String> {
+            parsed
+                .value(key)
+                .into_iter()
+                .flat_map(|value| value.split(','))
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+        let (charts, cards) = (list("charts"), list("cards"));
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let files = analyzer.generate_dashboard(name, &charts, &cards)?;
+
+        let mut text = format!(
+            "✅ Generated Dashboard: {}\nCharts: {}\nCards: {}\nFiles created:\n",
+            name,
+            if charts.is_empty() {
+                "none".to_string()
+            } else {
+                charts.join(", ")
+            },
+            if cards.is_empty() {
+                "none".to_string()
+            } else {
+                cards.join(", ")
+            },
+        );
+        for file in &files {
+            text.push_str(&format!("- {}\n", Self::absolute_path(file)));
+        }
+        text.push_str("\nℹ️ Run `bench migrate` to import it.");
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_email_template(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["app"])?;
+        let [name] = parsed.positional() else {
+            return Err("Email Template name is required, e.g. \"Welcome Email\"".to_string());
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let generated = analyzer.generate_email_template(name, parsed.value("app"))?;
+
+        let mut text = format!(
+            "✅ Generated Email Template: {}\nFile updated:\n- {}\n",
+            name,
+            Self::absolute_path(&generated.path)
+        );
+        Self::push_fixture_hint(&mut text, "Email Template", generated.in_fixtures);
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_role(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["app"])?;
+        let [name] = parsed.positional() else {
+            return Err("Role name is required, e.g. \"Shop Clerk\"".to_string());
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let generated =
+            analyzer.generate_role(name, !parsed.has("no-desk-access"), parsed.value("app"))?;
+
+        let mut text = format!(
+            "✅ Generated Role: {}\nFile updated:\n- {}\n",
+            name,
+            Self::absolute_path(&generated.path)
+        );
+        Self::push_fixture_hint(&mut text, "Role", generated.in_fixtures);
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn generate_role_profile(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["app"])?;
+        let [name, roles] = parsed.positional() else {
+            return Err(
+                "Role Profile name and roles are required, e.g. \"Shop Staff\" \"Shop Clerk,Sales User\""
+                    .to_string(),
+            );
+        };
+        let roles: Vec<String> = roles.split(',').map(|role| role.to_string()).collect();
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let (generated, unknown) =
+            analyzer.generate_role_profile(name, &roles, parsed.value("app"))?;
+
+        let mut text = format!(
+            "✅ Generated Role Profile: {}\nFile updated:\n- {}\n",
+            name,
+            Self::absolute_path(&generated.path)
+        );
+        if !unknown.is_empty() {
+            text.push_str(&format!(
+                "\n⚠️ Unknown role{}: {}. Create {} with /frappe-new-role first.",
+                if unknown.len() == 1 { "" } else { "s" },
+                unknown.join(", "),
+                if unknown.len() == 1 { "it" } else { "them" }
+            ));
+        }
+        Self::push_fixture_hint(&mut text, "Role Profile", generated.in_fixtures);
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn push_fixture_hint(text: &mut String, doctype: &str, in_fixtures: bool) {
+        if in_fixtures {
+            text.push_str("\nℹ️ Run `bench migrate` to import it.");
+        } else {
+            text.push_str(&format!(
+                "\n⚠️ Add \"{}\" to `fixtures` in hooks.py so `bench migrate` imports it.",
+                doctype
+            ));
+        }
+    }
+
+    fn generate_api_method(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let [app, method, params @ ..] = parsed.positional() else {
+            return Err("App and method name are required, e.g. my_app get_stock".to_string());
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let generated = analyzer.generate_api_method(app, method, params)?;
+
+        Ok(SlashCommandOutput {
+            text: format!(
+                "✅ {} {}()\nFile {}:\n- {}\n\n🌐 Callable at /api/method/{}\n",
+                if generated.created {
+                    "Created"
+                } else {
+                    "Added"
+                },
+                method,
+                if generated.created {
+                    "created"
+                } else {
+                    "updated"
+                },
+                Self::absolute_path(&generated.path),
+                generated.endpoint
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn run_tests(
+        &self,
+        app: &str,
+        format: TestReportFormat,
+        selection: &TestSelection,
+        test_site: bool,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+
+        let test_runner = self.test_runner(&config);
+
+        let mut steps = Vec::new();
+        let result = if test_site {
+            test_runner.run_tests_on_temp_site(app, selection, &mut steps)
+        } else {
+            test_runner.run_selected_tests(app, selection)
+        };
+        let steps_text: String = steps.iter().map(|step| format!("{}\n", step)).collect();
+
+        match result {
+            Ok(test_suite) if format != TestReportFormat::Human => {
+                // Machine-readable reports are returned as-is so they can be copied out
+                let diagnostics = test_runner.extract_diagnostics(&test_suite.results);
+                let _ = test_runner.write_diagnostics_file(&diagnostics);
+                Ok(SlashCommandOutput {
+                    text: test_runner.format_test_report(&test_suite, format),
+                    sections: vec![],
+                })
+            }
             Ok(test_suite) => {
                 let summary = test_runner.format_test_summary(&test_suite);
                 let diagnostics = test_runner.extract_diagnostics(&test_suite.results);
 
-                let mut text = format!("🧪 Test Results for app: {}\n\n", app);
-                text.push_str(&summary);
+                let mut text = format!("🧪 Test Results for app: {}\n", app);
+                if !selection.is_empty() {
+                    text.push_str(&format!("⚙️ Flags: {}\n", selection.describe()));
+                }
+                text.push('\n');
+                if !steps_text.is_empty() {
+                    text.push_str(&steps_text);
+                    text.push('\n');
+                }
+                text.push_str(&summary);
+
+                if !diagnostics.is_empty() {
+                    text.push_str(&format!(
+                        "\n📋 {} diagnostics generated: {}",
+                        diagnostics.len(),
+                        test_runner.format_diagnostic_counts(&diagnostics)
+                    ));
+                }
+
+                match test_runner.write_diagnostics_file(&diagnostics) {
+                    Ok(path) => {
+                        text.push_str(&format!("\n📄 Diagnostics written to {}", path.display()))
+                    }
+                    Err(e) => text.push_str(&format!("\n⚠️ {}", e)),
+                }
+
+                Ok(SlashCommandOutput {
+                    text,
+                    sections: vec![],
+                })
+            }
+            Err(e) if steps_text.is_empty() => Err(format!("Failed to run tests: {}", e)),
+            Err(_) => Err(format!(
+                "Failed to run tests on a temporary site:\n{}",
+                steps_text
+            )),
+        }
+    }
+
+    fn watch_tests(&self, app: &str, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+
+        let watch_dir = Path::new(&config.apps_path).join(app);
+        if !watch_dir.is_dir() {
+            return Err(format!("App '{}' not found in {}", app, config.apps_path));
+        }
+
+        let mut watchers = self.test_watchers.lock().unwrap();
+        if watchers.contains_key(app) {
+            return Ok(SlashCommandOutput {
+                text: format!("ℹ️ Already watching tests for app: {}", app),
+                sections: vec![],
+            });
+        }
+
+        let test_runner = self.test_runner(&config);
+        watchers.insert(
+            app.to_string(),
+            TestWatcher::start(test_runner, app, watch_dir.clone()),
+        );
+
+        Ok(SlashCommandOutput {
+            text: format!(
+                "👀 Watching {} for changes\nTests for {} will re-run after .py files are saved. Use /frappe-watch-status for results.",
+                watch_dir.display(),
+                app
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn run_ui_tests(&self, app: &str, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+
+        if !Path::new(&config.apps_path).join(app).is_dir() {
+            return Err(format!("App '{}' not found in {}", app, config.apps_path));
+        }
+        if let Some(version) = self.process_manager.bench_version(&config.bench_path) {
+            if !version.supports_ui_tests() {
+                return Err(format!(
+                    "bench {} does not support run-ui-tests (requires 5.0+)",
+                    version.raw
+                ));
+            }
+        }
+
+        let key = ui_progress_key(app);
+        if let Some(progress) = self.test_progress.lock().unwrap().get(&key) {
+            if !progress.finished {
+                return Ok(SlashCommandOutput {
+                    text: format!(
+                        "ℹ️ UI tests for {} are already running. Use /frappe-test-progress {}",
+                        app, app
+                    ),
+                    sections: vec![],
+                });
+            }
+        }
+
+        // UI suites take minutes, so run them off the command thread and let
+        // /frappe-test-progress report on them
+        let test_runner = self.test_runner(&config);
+        let app_name = app.to_string();
+        std::thread::spawn(move || {
+            if let Ok(suite) = test_runner.run_ui_tests(&app_name) {
+                let diagnostics = test_runner.extract_diagnostics(&suite.results);
+                let _ = test_runner.write_diagnostics_file(&diagnostics);
+            }
+        });
+
+        Ok(SlashCommandOutput {
+            text: format!(
+                "🚀 Started UI tests for app: {}\nUse /frappe-test-progress {} to follow the run; failures are written to .latte_diagnostics.json when it finishes.",
+                app, app
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn test_runner(&self, config: &FrappeConfig) -> TestRunner {
+        let site = config
+            .default_site
+            .clone()
+            .unwrap_or_else(|| "localhost".to_string());
+
+        TestRunner::new(config.bench_path.clone(), site)
+            .with_bench_version(self.process_manager.bench_version(&config.bench_path))
+            .with_process_manager(Arc::clone(&self.process_manager))
+            .with_progress(Arc::clone(&self.test_progress))
+    }
+
+    fn test_progress(&self, app: Option<&str>) -> Result<SlashCommandOutput, String> {
+        let mut runs: Vec<_> = self
+            .test_progress
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|progress| app.map(|a| a == progress.suite.app).unwrap_or(true))
+            .cloned()
+            .collect();
+        runs.sort_by(|a, b| a.suite.name.cmp(&b.suite.name));
+
+        if runs.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: match app {
+                    Some(app) => format!("ℹ️ No test runs recorded for app: {}", app),
+                    None => "ℹ️ No test runs recorded".to_string(),
+                },
+                sections: vec![],
+            });
+        }
+
+        let mut text = String::new();
+        for run in runs {
+            if let Some(error) = &run.error {
+                text.push_str(&format!("❌ {}: {}\n", run.suite.name, error));
+            } else if run.finished {
+                text.push_str("🏁 Finished\n");
+                text.push_str(&TestRunner::summarize(&run.suite));
+            } else {
+                let elapsed = run
+                    .started
+                    .elapsed()
+                    .unwrap_or(std::time::Duration::ZERO)
+                    .as_secs();
+                text.push_str(&format!(
+                    "⏳ {} running for {}s: {} done\n",
+                    run.suite.name, elapsed, run.suite.total_tests
+                ));
+                text.push_str(&format!(
+                    "   ✅ {}  ❌ {}  🔥 {}  ⏭️ {}\n",
+                    run.suite.passed, run.suite.failed, run.suite.errors, run.suite.skipped
+                ));
+                if let Some(last) = run.suite.results.last() {
+                    text.push_str(&format!("   Last: {} ({})\n", last.test_name, last.status));
+                }
+            }
+            text.push('\n');
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn watch_status(&self, app: Option<&str>) -> Result<SlashCommandOutput, String> {
+        let watchers = self.test_watchers.lock().unwrap();
+
+        let mut states: Vec<_> = watchers
+            .iter()
+            .filter(|(name, _)| app.map(|a| a == name.as_str()).unwrap_or(true))
+            .map(|(_, watcher)| watcher.state())
+            .collect();
+        states.sort_by(|a, b| a.app.cmp(&b.app));
+
+        if states.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: match app {
+                    Some(app) => format!("ℹ️ No test watcher is running for app: {}", app),
+                    None => "ℹ️ No test watchers are running".to_string(),
+                },
+                sections: vec![],
+            });
+        }
+
+        let mut text = String::new();
+        for state in states {
+            text.push_str(&format!("👀 {} ({} runs)\n", state.app, state.runs));
+            if state.is_running {
+                text.push_str("   ⏳ Tests are running...\n");
+            }
+            if let Some(error) = &state.last_error {
+                text.push_str(&format!("   ❌ Last run failed: {}\n", error));
+            }
+
+            match &state.latest {
+                Some(suite) => text.push_str(&TestRunner::summarize(suite)),
+                None => text.push_str("   No completed runs yet\n"),
+            }
+            text.push('\n');
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn create_doctype_json(
+        &self,
+        name: &str,
+        module: &str,
+        flags: &DocTypeFlags,
+    ) -> serde_json::Value {
+        let snake_case = name.to_lowercase().replace(" ", "_");
+        let mut fields: Vec<serde_json::Value> = self
+            .generate_smart_fields(name)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (fieldname, fieldtype, label))| {
+                serde_json::json!({
+                    "fieldname": fieldname,
+                    "fieldtype": fieldtype,
+                    "label": label,
+                    "reqd": if i == 0 { 1 } else { 0 },
+                })
+            })
+            .collect();
+
+        if flags.tree {
+            let parent_field = format!("parent_{}", snake_case);
+            fields.push(serde_json::json!({
+                "fieldname": parent_field,
+                "fieldtype": "Link",
+                "label": format!("Parent {}", name),
+                "options": name,
+            }));
+            fields.push(serde_json::json!({
+                "default": "0",
+                "fieldname": "is_group",
+                "fieldtype": "Check",
+                "label": "Is Group",
+            }));
+            for fieldname in ["lft", "rgt"] {
+                fields.push(serde_json::json!({
+                    "fieldname": fieldname,
+                    "fieldtype": "Int",
+                    "hidden": 1,
+                    "label": fieldname.to_uppercase(),
+                    "no_copy": 1,
+                    "read_only": 1,
+                }));
+            }
+            fields.push(serde_json::json!({
+                "fieldname": "old_parent",
+                "fieldtype": "Link",
+                "hidden": 1,
+                "label": "Old Parent",
+                "options": name,
+            }));
+        }
+        if flags.submittable {
+            fields.push(serde_json::json!({
+                "fieldname": "amended_from",
+                "fieldtype": "Link",
+                "label": "Amended From",
+                "no_copy": 1,
+                "options": name,
+                "print_hide": 1,
+                "read_only": 1,
+            }));
+        }
+
+        let field_order: Vec<serde_json::Value> = fields
+            .iter()
+            .map(|field| field["fieldname"].clone())
+            .collect();
+
+        let mut doctype = serde_json::json!({
+            "actions": [],
+            "allow_rename": 1,
+            "creation": "2024-01-01 00:00:00.000000",
+            "doctype": "DocType",
+            "editable_grid": 1,
+            "engine": "InnoDB",
+            "field_order": field_order,
+            "fields": fields,
+            "index_web_pages_for_search": 1,
+            "links": [],
+            "modified": "2024-01-01 00:00:00.000000",
+            "modified_by": "Administrator",
+            "module": module,
+            "name": name,
+            "owner": "Administrator",
+            "permissions": [],
+            "sort_field": "modified",
+            "sort_order": "DESC",
+            "states": [],
+            "track_changes": 1,
+        });
+        let object = doctype.as_object_mut().expect("DocType JSON is an object");
+
+        // Singles and child tables have no documents of their own to name
+        if !flags.single && !flags.istable {
+            object.insert("naming_rule".to_string(), "By fieldname".into());
+        }
+        // Child tables take their permissions from the parent
+        if !flags.istable {
+            let mut permission = serde_json::json!({
+                "create": 1,
+                "email": 1,
+                "print": 1,
+                "read": 1,
+                "role": "System Manager",
+                "share": 1,
+                "write": 1,
+            });
+            if !flags.single {
+                for right in ["delete", "export", "report"] {
+                    permission[right] = 1.into();
+                }
+            }
+            if flags.submittable {
+                for right in ["submit", "cancel", "amend"] {
+                    permission[right] = 1.into();
+                }
+            }
+            object.insert("permissions".to_string(), serde_json::json!([permission]));
+        }
+        if flags.single {
+            object.insert("issingle".to_string(), 1.into());
+        }
+        if flags.istable {
+            object.insert("istable".to_string(), 1.into());
+        }
+        if flags.submittable {
+            object.insert("is_submittable".to_string(), 1.into());
+        }
+        if flags.tree {
+            object.insert("is_tree".to_string(), 1.into());
+            object.insert(
+                "nsm_parent_field".to_string(),
+                format!("parent_{}", snake_case).into(),
+            );
+        }
+
+        doctype
+    }
+
+    fn generate_smart_fields(&self, doctype_name: &str) -> Vec<(String, String, String)> {
+        let mut fields = Vec::new();
+
+        let name_field = if doctype_name.to_lowercase().contains("item") {
+            (
+                "item_name".to_string(),
+                "Data".to_string(),
+                "Item Name".to_string(),
+            )
+        } else {
+            ("title".to_string(), "Data".to_string(), "Title".to_string())
+        };
+        fields.push(name_field);
+
+        let doctype_lower = doctype_name.to_lowercase();
+
+        if doctype_lower.contains("customer") || doctype_lower.contains("supplier") {
+            fields.push((
+                "contact_person".to_string(),
+                "Data".to_string(),
+                "Contact Person".to_string(),
+            ));
+            fields.push(("email".to_string(), "Data".to_string(), "Email".to_string()));
+            fields.push((
+                "phone".to_string(),
+                "Phone".to_string(),
+                "Phone".to_string(),
+            ));
+        }
+
+        if doctype_lower.contains("transaction")
+            || doctype_lower.contains("order")
+            || doctype_lower.contains("invoice")
+        {
+            fields.push(("date".to_string(), "Date".to_string(), "Date".to_string()));
+            fields.push((
+                "total_amount".to_string(),
+                "Currency".to_string(),
+                "Total Amount".to_string(),
+            ));
+            fields.push((
+                "status".to_string(),
+                "Select".to_string(),
+                "Status".to_string(),
+            ));
+        }
+
+        if doctype_lower.contains("item") || doctype_lower.contains("product") {
+            fields.push((
+                "item_code".to_string(),
+                "Data".to_string(),
+                "Item Code".to_string(),
+            ));
+            fields.push((
+                "description".to_string(),
+                "Text".to_string(),
+                "Description".to_string(),
+            ));
+            fields.push((
+                "unit_price".to_string(),
+                "Currency".to_string(),
+                "Unit Price".to_string(),
+            ));
+        }
+
+        fields.push((
+            "is_active".to_string(),
+            "Check".to_string(),
+            "Is Active".to_string(),
+        ));
+        fields.push((
+            "remarks".to_string(),
+            "Text".to_string(),
+            "Remarks".to_string(),
+        ));
+
+        fields
+    }
+
+    fn create_doctype_controller(&self, name: &str, _module: &str) -> String {
+        format!(
+            r#"# Copyright (c) 2024, Frappe Technologies and contributors
+# For license information, please see license.txt
+
+import frappe
+from frappe.model.document import Document
+
+
+class {}(Document):
+    def validate(self):
+        """Called before saving the document"""
+        pass
+
+    def before_save(self):
+        """Called before saving the document"""
+        pass
+
+    def after_insert(self):
+        """Called after inserting the document"""
+        pass
+
+    def on_update(self):
+        """Called after updating the document"""
+        pass
+
+    def on_cancel(self):
+        """Called when cancelling the document"""
+        pass
+
+    def on_trash(self):
+        """Called before deleting the document"""
+        pass
+"#,
+            frappe_utils::doctype_class_name(name)
+        )
+    }
+
+    fn create_doctype_client_script(&self, name: &str) -> String {
+        format!(
+            r#"// Copyright (c) 2024, Frappe Technologies and contributors
+// For license information, please see license.txt
+
+frappe.ui.form.on('{}', {{
+    refresh: function(frm) {{
+        // Called when form is refreshed
+    }},
+
+    onload: function(frm) {{
+        // Called when form is loaded
+    }},
+
+    before_save: function(frm) {{
+        // Called before saving the document
+    }},
+
+    after_save: function(frm) {{
+        // Called after saving the document
+    }},
+
+    validate: function(frm) {{
+        // Called during validation
+    }}
+}});
+"#,
+            name
+        )
+    }
+
+    fn search_doctypes(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["app", "module"])?;
+        let query = parsed.positional().join(" ");
+        let app = parsed.value("app");
+        let module = parsed.value("module");
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str); // borrow from the owned String
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let results = analyzer.search_doctypes_in(&query, app, module);
+
+        if results.is_empty() {
+            let mut text = format!("No DocTypes found matching '{}'", query);
+            if let Some(app) = app {
+                text.push_str(&format!(" in app '{}'", app));
+            }
+            if let Some(module) = module {
+                text.push_str(&format!(" in module '{}'", module));
+            }
+            text.push_str("\nNarrow or widen the search with --app <name> and --module <name>.");
+            return Ok(SlashCommandOutput {
+                text,
+                sections: vec![],
+            });
+        }
+
+        let mut text = format!("Found {} DocTypes matching '{}':\n\n", results.len(), query);
+        for doctype in results.iter().take(10) {
+            text.push_str(&format!(
+                "• {} (Module: {})\n  Fields: {}\n{}\n",
+                doctype.name,
+                doctype.module,
+                doctype.fields.len(),
+                Self::format_doctype_paths(doctype)
+            ));
+        }
+
+        if results.len() > 10 {
+            text.push_str(&format!("... and {} more results\n", results.len() - 10));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn suggest_fieldtypes(&self, args: &[String]) -> Result<SlashCommandOutput, String> {
+        if args.is_empty() {
+            return Err("At least one field name is required".to_string());
+        }
+
+        let mut text = String::new();
+        for field_name in args {
+            let suggestions = frappe_utils::generate_field_suggestions(field_name);
+            text.push_str(&format!("🔤 {}\n", field_name));
+            for (index, (fieldtype, reason)) in suggestions.iter().enumerate() {
+                let marker = if index == 0 { "✅" } else { "  •" };
+                text.push_str(&format!("  {} {}: {}\n", marker, fieldtype, reason));
+            }
+            text.push('\n');
+        }
+
+        Ok(SlashCommandOutput {
+            text: text.trim_end().to_string(),
+            sections: vec![],
+        })
+    }
+
+    fn link_cycles(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let cycles = analyzer.find_link_cycles();
+        if cycles.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: "✅ No circular DocType links".to_string(),
+                sections: vec![],
+            });
+        }
+
+        let mut text = format!("🔁 Circular DocType links ({}):\n", cycles.len());
+        for cycle in &cycles {
+            text.push_str(&format!("  • {} → {}\n", cycle.join(" → "), cycle[0]));
+        }
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn find_unused_doctypes(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let candidates = analyzer.unused_doctype_candidates();
+        if candidates.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: "✅ Every DocType is linked to or has controller logic".to_string(),
+                sections: vec![],
+            });
+        }
+
+        let mut text = format!(
+            "🧹 Unused DocType candidates ({}) — verify before deleting:\n",
+            candidates.len()
+        );
+        for doctype in &candidates {
+            text.push_str(&format!(
+                "  • {} ({}) {}\n",
+                doctype.name,
+                doctype.module,
+                doctype.file_path.display()
+            ));
+        }
+        text.push_str(
+            "\nNo Link or Table field points at these and their controllers are empty stubs. \
+             Uses from Python/JS code, Dynamic Links, reports and single settings DocTypes aren't detected.\n",
+        );
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn extract_translations(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let [app] = args else {
+            return Err("Usage: /frappe-extract-translations <app>".to_string());
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let strings = analyzer.extract_translatable_strings(app)?;
+        if strings.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: format!("No translatable strings found in {}", app),
+                sections: vec![],
+            });
+        }
+
+        let mut text = format!(
+            "🌐 {} translatable string{} in {}:\n\n",
+            strings.len(),
+            if strings.len() == 1 { "" } else { "s" },
+            app
+        );
+        for string in &strings {
+            text.push_str(&format!(
+                "{}:{}  {}\n",
+                string.path.display(),
+                string.line,
+                string.text
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn doctype_references(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let doctype = args.join(" ");
+        if doctype.trim().is_empty() {
+            return Err("DocType name is required".to_string());
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let matches = analyzer.grep_doctype_references(doctype.trim())?;
+        if matches.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: format!("No references to '{}' found", doctype.trim()),
+                sections: vec![],
+            });
+        }
+
+        let mut text = format!(
+            "🔗 {} reference{} to '{}':\n\n",
+            matches.len(),
+            if matches.len() == 1 { "" } else { "s" },
+            doctype.trim()
+        );
+        for found in &matches {
+            text.push_str(&format!(
+                "{}:{}:{}  {}\n",
+                found.path.display(),
+                found.line,
+                found.column,
+                found.text.trim()
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn diff_doctypes(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let (Some(left), Some(right), None) = (parsed.get(0), parsed.get(1), parsed.get(2)) else {
+            return Err("Usage: /frappe-diff-doctype \"DocType A\" \"DocType B\"".to_string());
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let diff = analyzer.diff_doctypes(left, right)?;
+        let mut text = format!("🔀 {} ↔ {}\n\n", diff.left, diff.right);
+        if diff.is_empty() {
+            text.push_str("✅ Fields and permissions are identical\n");
+            return Ok(SlashCommandOutput {
+                text,
+                sections: vec![],
+            });
+        }
+
+        let sections = [
+            (format!("Only in {}", diff.left), &diff.only_left),
+            (format!("Only in {}", diff.right), &diff.only_right),
+        ];
+        for (title, fields) in sections {
+            if !fields.is_empty() {
+                text.push_str(&format!("➖ {} ({}):\n", title, fields.len()));
+                for field in fields {
+                    text.push_str(&format!("  • {} ({})\n", field.fieldname, field.fieldtype));
+                }
+                text.push('\n');
+            }
+        }
+
+        if !diff.changed_fields.is_empty() {
+            text.push_str(&format!(
+                "✏️ Changed fields ({}):\n",
+                diff.changed_fields.len()
+            ));
+            for field in &diff.changed_fields {
+                text.push_str(&format!(
+                    "  • {}: {}\n",
+                    field.fieldname,
+                    field.differences.join(", ")
+                ));
+            }
+            text.push('\n');
+        }
+
+        if !diff.only_left_roles.is_empty()
+            || !diff.only_right_roles.is_empty()
+            || !diff.changed_roles.is_empty()
+        {
+            text.push_str("🔐 Permissions:\n");
+            for role in &diff.only_left_roles {
+                text.push_str(&format!("  • {} only in {}\n", role, diff.left));
+            }
+            for role in &diff.only_right_roles {
+                text.push_str(&format!("  • {} only in {}\n", role, diff.right));
+            }
+            for role in &diff.changed_roles {
+                text.push_str(&format!(
+                    "  • {}: {}\n",
+                    role.fieldname,
+                    role.differences.join(", ")
+                ));
+            }
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn doctype_changes(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let name = args.join(" ");
+        if name.trim().is_empty() {
+            return Err("Usage: /frappe-doctype-changes \"DocType\"".to_string());
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let path = analyzer
+            .find_doctype(name.trim())
+            .map(|dt| dt.file_path.clone())
+            .ok_or_else(|| format!("DocType '{}' not found", name.trim()))?;
+        let committed = process_manager::git_show_head(&path)?;
+        let changes = analyzer.doctype_changes(name.trim(), &committed)?;
+
+        let mut text = format!(
+            "🔀 {} changes since HEAD\n📄 {}\n\n",
+            changes.doctype,
+            changes.path.display()
+        );
+        if changes.is_empty() {
+            text.push_str("✅ No field changes\n");
+            return Ok(SlashCommandOutput {
+                text,
+                sections: vec![],
+            });
+        }
+
+        let sections = [
+            ("➕ Added", &changes.added),
+            ("➖ Removed", &changes.removed),
+        ];
+        for (title, fields) in sections {
+            if !fields.is_empty() {
+                text.push_str(&format!("{} ({}):\n", title, fields.len()));
+                for field in fields {
+                    text.push_str(&format!("  • {} ({})\n", field.fieldname, field.fieldtype));
+                }
+                text.push('\n');
+            }
+        }
+
+        if !changes.modified.is_empty() {
+            text.push_str(&format!("✏️ Modified ({}):\n", changes.modified.len()));
+            for field in &changes.modified {
+                text.push_str(&format!(
+                    "  • {}: {}\n",
+                    field.fieldname,
+                    field.differences.join(", ")
+                ));
+            }
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn validate_doctype_json(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let target = args.join(" ");
+        let target = target.trim();
+        if target.is_empty() {
+            return Err("Usage: /frappe-validate-doctype-json <path | \"DocType\">".to_string());
+        }
+
+        // Relative paths are taken from the bench root
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        let path = if target.ends_with(".json") {
+            root_path.join(target)
+        } else {
+            let mut analyzer = FrappeAnalyzer::new();
+            if analyzer.analyze_project(root_path).is_err() {
+                return Err("Failed to analyze Frappe project".to_string());
+            }
+            analyzer
+                .find_doctype(target)
+                .map(|dt| dt.file_path.clone())
+                .ok_or(format!("DocType '{}' not found", target))?
+        };
+
+        let problems = frappe_utils::validate_doctype_json(&path)?;
+        let mut text = format!("📄 {}\n", path.display());
+        if problems.is_empty() {
+            text.push_str("✅ No structural problems found\n");
+        } else {
+            text.push_str(&format!("❌ {} problem(s):\n", problems.len()));
+            for problem in &problems {
+                text.push_str(&format!("  • {}\n", problem));
+            }
+        }
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn format_doctype(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let name = parsed.positional().join(" ");
+        let all = parsed.has("all");
+        if name.is_empty() != all {
+            return Err("Usage: /frappe-format-doctype \"DocType\" or --all".to_string());
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        if !all {
+            let text = if analyzer.format_doctype(&name)? {
+                format!("✅ Reformatted {}", name)
+            } else {
+                format!("✅ {} is already formatted", name)
+            };
+            return Ok(SlashCommandOutput {
+                text,
+                sections: vec![],
+            });
+        }
+
+        // Apps installed into the environment aren't the user's to rewrite
+        let names: Vec<String> = analyzer
+            .get_project()
+            .iter()
+            .flat_map(|project| project.apps.iter())
+            .filter(|app| app.editable)
+            .flat_map(|app| app.doctypes.iter())
+            .map(|doctype| doctype.name.clone())
+            .collect();
+        let mut changed = Vec::new();
+        for name in &names {
+            if analyzer.format_doctype(name)? {
+                changed.push(name);
+            }
+        }
+
+        let mut text = format!(
+            "✅ Checked {} DocType{}, reformatted {}\n",
+            names.len(),
+            if names.len() == 1 { "" } else { "s" },
+            changed.len()
+        );
+        for name in changed {
+            text.push_str(&format!("  • {}\n", name));
+        }
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn doc_export(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["all"])?;
+        let name = parsed.positional().join(" ");
+        let dir = parsed.value("all");
+        if name.is_empty() == dir.is_none() {
+            return Err("Usage: /frappe-doc-export \"DocType\" or --all <dir>".to_string());
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let Some(dir) = dir else {
+            let doctype = analyzer
+                .find_doctype(&name)
+                .ok_or(format!("DocType '{}' not found", name))?;
+            return Ok(SlashCommandOutput {
+                text: analyzer.doctype_to_markdown(doctype),
+                sections: vec![],
+            });
+        };
+
+        // Relative paths are taken from the bench root
+        let target = root_path.join(dir);
+        fs::create_dir_all(&target)
+            .map_err(|e| format!("Could not create {}: {}", target.display(), e))?;
+        let doctypes: Vec<_> = analyzer
+            .get_project()
+            .iter()
+            .flat_map(|project| project.apps.iter())
+            .flat_map(|app| app.doctypes.iter())
+            .collect();
+        for doctype in &doctypes {
+            let path = target.join(format!(
+                "{}.md",
+                frappe_utils::doctype_folder_name(&doctype.name)
+            ));
+            fs::write(&path, analyzer.doctype_to_markdown(doctype))
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        }
+
+        Ok(SlashCommandOutput {
+            text: format!(
+                "✅ Wrote {} DocType page{} to {}",
+                doctypes.len(),
+                if doctypes.len() == 1 { "" } else { "s" },
+                target.display()
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn regen_controller(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let name = parsed.positional().join(" ");
+        if name.is_empty() {
+            return Err("Usage: /frappe-regen-controller \"DocType\" [--force]".to_string());
+        }
+        let force = parsed.has("force");
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+        let doctype = analyzer
+            .find_doctype(&name)
+            .ok_or(format!("DocType '{}' not found", name))?;
+
+        // The folder name is snake_case; the class and form names come from
+        // the DocType's own name in its JSON
+        let content = fs::read_to_string(&doctype.file_path)
+            .map_err(|e| format!("Could not read {}: {}", doctype.file_path.display(), e))?;
+        let json: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid JSON in {}: {}", doctype.file_path.display(), e))?;
+        let doctype_name = json
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&doctype.name)
+            .to_string();
+
+        let stem = doctype.file_path.with_extension("");
+        let files = [
+            (
+                stem.with_extension("py"),
+                self.create_doctype_controller(&doctype_name, &doctype.module),
+            ),
+            (
+                stem.with_extension("js"),
+                self.create_doctype_client_script(&doctype_name),
+            ),
+        ];
+
+        let mut text = format!("🔧 Regenerating sources for {}\n\n", doctype_name);
+        let mut skipped = 0;
+        for (path, source) in &files {
+            if path.exists() && !force {
+                skipped += 1;
+                text.push_str(&format!("  ⏭️ {} exists, left as is\n", path.display()));
+                continue;
+            }
+            let existed = path.exists();
+            fs::write(path, source)
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+            text.push_str(&format!(
+                "  ✅ {} {}\n",
+                if existed { "Overwrote" } else { "Wrote" },
+                path.display()
+            ));
+        }
+        if skipped > 0 {
+            text.push_str("\nPass --force to overwrite existing files.");
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn doctype_fields_csv(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["all"])?;
+        let name = parsed.positional().join(" ");
+        let file = parsed.value("all");
+        if name.is_empty() == file.is_none() {
+            return Err(
+                "Usage: /frappe-doctype-fields-csv \"DocType\" or --all <file>".to_string(),
+            );
+        }
 
-                if !diagnostics.is_empty() {
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let Some(file) = file else {
+            let doctype = analyzer
+                .find_doctype(&name)
+                .ok_or(format!("DocType '{}' not found", name))?;
+            return Ok(SlashCommandOutput {
+                text: frappe_utils::fields_to_csv(&[doctype]),
+                sections: vec![],
+            });
+        };
+
+        // Relative paths are taken from the bench root
+        let target = root_path.join(file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+        }
+        let doctypes: Vec<_> = analyzer
+            .get_project()
+            .iter()
+            .flat_map(|project| project.apps.iter())
+            .flat_map(|app| app.doctypes.iter())
+            .collect();
+        fs::write(&target, frappe_utils::fields_to_csv(&doctypes))
+            .map_err(|e| format!("Could not write {}: {}", target.display(), e))?;
+
+        let fields: usize = doctypes.iter().map(|dt| dt.fields.len()).sum();
+        Ok(SlashCommandOutput {
+            text: format!(
+                "✅ Wrote {} field{} from {} DocType{} to {}",
+                fields,
+                if fields == 1 { "" } else { "s" },
+                doctypes.len(),
+                if doctypes.len() == 1 { "" } else { "s" },
+                target.display()
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn doctype_template(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let (name, path) = match parsed.positional() {
+            [name] => (name, None),
+            [name, path] => (name, Some(path)),
+            _ => return Err("Usage: /frappe-doctype-template \"DocType\" [path]".to_string()),
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let template = analyzer.export_doctype_template(name)?;
+        let text = match path {
+            Some(path) => {
+                // Relative paths are taken from the bench root
+                let target = root_path.join(path);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+                }
+                fs::write(&target, &template)
+                    .map_err(|e| format!("Could not write {}: {}", target.display(), e))?;
+                format!(
+                    "✅ Wrote {} template to {}\nReplace {} and {} before importing it.",
+                    name,
+                    target.display(),
+                    frappe_utils::TEMPLATE_DOCTYPE,
+                    frappe_utils::TEMPLATE_MODULE
+                )
+            }
+            None => format!("📋 {} template\n\n{}", name, template),
+        };
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn reorder_fields(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let Some((doctype, order)) = parsed.positional().split_first() else {
+            return Err("Usage: /frappe-reorder-fields \"DocType\" field1 field2 ...".to_string());
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        analyzer.reorder_fields(doctype, order)?;
+        let path = analyzer
+            .find_doctype(doctype)
+            .map(|dt| Self::absolute_path(&dt.file_path))
+            .unwrap_or_default();
+
+        Ok(SlashCommandOutput {
+            text: format!(
+                "✅ Reordered {} fields of {}\n📄 {}\nNew order: {}",
+                order.len(),
+                doctype,
+                path,
+                order.join(", ")
+            ),
+            sections: vec![],
+        })
+    }
+
+    fn rename_doctype(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let (Some(old), Some(new), None) = (parsed.get(0), parsed.get(1), parsed.get(2)) else {
+            return Err(
+                "Usage: /frappe-rename-doctype \"Old Name\" \"New Name\" [--dry-run]".to_string(),
+            );
+        };
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let plan = analyzer.rename_doctype(old, new)?;
+        let dry_run = parsed.has("dry-run");
+        if !dry_run {
+            plan.apply()?;
+        }
+
+        let mut text = if dry_run {
+            format!(
+                "📋 Dry run: renaming '{}' to '{}' would touch {} files:\n\n",
+                plan.old_name,
+                plan.new_name,
+                plan.changes.len()
+            )
+        } else {
+            format!(
+                "✅ Renamed '{}' to '{}' ({} files touched):\n\n",
+                plan.old_name,
+                plan.new_name,
+                plan.changes.len()
+            )
+        };
+
+        for change in &plan.changes {
+            match (&change.renamed_to, &change.content) {
+                (Some(to), Some(_)) => text.push_str(&format!(
+                    "  ✏️ {} → {}\n",
+                    change.path.display(),
+                    to.display()
+                )),
+                (Some(to), None) => text.push_str(&format!(
+                    "  📦 {} → {}\n",
+                    change.path.display(),
+                    to.display()
+                )),
+                (None, _) => text.push_str(&format!("  🔗 {}\n", change.path.display())),
+            }
+        }
+
+        if dry_run {
+            text.push_str("\nRun again without --dry-run to apply.\n");
+        } else {
+            text.push_str(&format!(
+                "\nℹ️ Only source files were changed. Rename the record on your site with \
+                 `frappe.rename_doc(\"DocType\", \"{}\", \"{}\")` before running `bench migrate`.\n",
+                plan.old_name, plan.new_name
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn grep_fields(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["type", "page"])?;
+        let query = parsed.positional().join(" ");
+        let fieldtype = parsed.value("type");
+        let page = match parsed.value("page") {
+            Some(page) => page
+                .parse::<usize>()
+                .ok()
+                .filter(|p| *p > 0)
+                .ok_or(format!("Invalid page number: {}", page))?,
+            None => 1,
+        };
+
+        if query.is_empty() && fieldtype.is_none() {
+            return Err("A field name substring or --type is required".to_string());
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let results = analyzer.grep_fields(&query, fieldtype);
+        let filter_desc = match fieldtype {
+            Some(fieldtype) => format!("'{}' (type: {})", query, fieldtype),
+            None => format!("'{}'", query),
+        };
+
+        if results.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: format!("No fields found matching {}", filter_desc),
+                sections: vec![],
+            });
+        }
+
+        let page_size = 10;
+        let start = (page - 1) * page_size;
+        if start >= results.len() {
+            return Err(format!(
+                "Page {} is out of range ({} results)",
+                page,
+                results.len()
+            ));
+        }
+
+        let mut text = format!(
+            "Found {} fields matching {}:\n\n",
+            results.len(),
+            filter_desc
+        );
+        for (doctype, field) in results.iter().skip(start).take(page_size) {
+            text.push_str(&format!(
+                "• {}.{} ({})\n  Label: {}\n  Path: {}\n\n",
+                doctype.name,
+                field.fieldname,
+                field.fieldtype,
+                field.label,
+                doctype.file_path.display()
+            ));
+        }
+
+        let shown = start + page_size;
+        if results.len() > shown {
+            text.push_str(&format!(
+                "... and {} more results (use --page {})\n",
+                results.len() - shown,
+                page + 1
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn lint_hooks(
+        &self,
+        app_filter: Option<&str>,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let project = analyzer
+            .get_project()
+            .ok_or("Failed to get project information".to_string())?;
+        let apps: Vec<_> = project
+            .apps
+            .iter()
+            .filter(|app| app_filter.map(|name| app.name == name).unwrap_or(true))
+            .collect();
+
+        if apps.is_empty() {
+            return Err(format!("App '{}' not found", app_filter.unwrap_or("")));
+        }
+
+        let mut text = "🪝 hooks.py Lint\n\n".to_string();
+        let mut total_issues = 0;
+        for app in apps {
+            match analyzer.lint_hooks(app) {
+                Ok(issues) if issues.is_empty() => {
+                    text.push_str(&format!("✅ {}: no issues\n", app.name));
+                }
+                Ok(issues) => {
+                    total_issues += issues.len();
+                    text.push_str(&format!("❌ {} ({} issues)\n", app.name, issues.len()));
+                    for issue in issues {
+                        text.push_str(&format!(
+                            "  • [{}] {}\n    {}:{}\n",
+                            issue.hook,
+                            issue.message,
+                            app.hooks_path.display(),
+                            issue.line
+                        ));
+                    }
+                }
+                Err(e) => text.push_str(&format!("⚠️ {}: {}\n", app.name, e)),
+            }
+        }
+
+        text.push_str(&format!("\n📋 {} issues found\n", total_issues));
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn scheduler_events(
+        &self,
+        app_filter: Option<&str>,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let project = analyzer
+            .get_project()
+            .ok_or("Failed to get project information".to_string())?;
+        let apps: Vec<_> = project
+            .apps
+            .iter()
+            .filter(|app| app_filter.map(|name| app.name == name).unwrap_or(true))
+            .collect();
+
+        if apps.is_empty() {
+            return Err(format!("App '{}' not found", app_filter.unwrap_or("")));
+        }
+
+        let mut text = "⏰ Scheduled Jobs\n\n".to_string();
+        let mut total_jobs = 0;
+        for app in apps {
+            match analyzer.scheduler_events(app) {
+                Ok(jobs) if jobs.is_empty() => {
+                    text.push_str(&format!("ℹ️ {}: no scheduler_events\n\n", app.name));
+                }
+                Ok(jobs) => {
+                    total_jobs += jobs.len();
+                    text.push_str(&format!("📦 {} ({} jobs)\n", app.name, jobs.len()));
+
+                    let mut current: Option<(&str, Option<&str>)> = None;
+                    for job in &jobs {
+                        let group = (job.frequency.as_str(), job.cron.as_deref());
+                        if current != Some(group) {
+                            match group.1 {
+                                Some(expression) => {
+                                    text.push_str(&format!("  cron \"{}\":\n", expression))
+                                }
+                                None => text.push_str(&format!("  {}:\n", group.0)),
+                            }
+                            current = Some(group);
+                        }
+                        text.push_str(&format!("    • {}\n", job.method));
+                    }
+                    text.push('\n');
+                }
+                Err(e) => text.push_str(&format!("⚠️ {}: {}\n\n", app.name, e)),
+            }
+        }
+
+        text.push_str(&format!("📋 {} scheduled jobs\n", total_jobs));
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn goto_doctype(&self, name: &str, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+        if analyzer.analyze_project(root_path).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+
+        let doctype = analyzer
+            .find_doctype(name)
+            .ok_or(format!("DocType '{}' not found", name))?;
+
+        // The controller is usually what people want to open, so it goes first
+        let primary = doctype
+            .controller_path
+            .as_ref()
+            .unwrap_or(&doctype.file_path);
+
+        let text = format!(
+            "📄 {} (Module: {})\n{}\n\n{}",
+            doctype.name,
+            doctype.module,
+            Self::absolute_path(primary),
+            Self::format_doctype_paths(doctype)
+        );
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn format_doctype_paths(doctype: &frappe_utils::DocTypeInfo) -> String {
+        let optional = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|p| Self::absolute_path(p))
+                .unwrap_or_else(|| "(missing)".to_string())
+        };
+
+        format!(
+            "  Controller: {}\n  Client Script: {}\n  JSON: {}\n",
+            optional(&doctype.controller_path),
+            optional(&doctype.client_script_path),
+            Self::absolute_path(&doctype.file_path)
+        )
+    }
+
+    fn absolute_path(path: &Path) -> String {
+        fs::canonicalize(path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .display()
+            .to_string()
+    }
+
+    fn site_config(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let show_secrets = parsed.has("show-secrets");
+        let (site, key, value) = match parsed.positional() {
+            [site] => (site, None, None),
+            [site, key] => (site, Some(key.as_str()), None),
+            [site, key, value] => (site, Some(key.as_str()), Some(value.as_str())),
+            _ => {
+                return Err(
+                    "Usage: /frappe-site-config <site> [key] [value] [--show-secrets]".to_string(),
+                )
+            }
+        };
+
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let config_path = Path::new(&config.sites_path)
+            .join(site)
+            .join("site_config.json");
+        if !config_path.is_file() {
+            return Err(format!(
+                "Site '{}' not found in {}",
+                site, config.sites_path
+            ));
+        }
+
+        let display = |key: &str, value: &serde_json::Value| {
+            if !show_secrets && frappe_utils::is_sensitive_config_key(key) {
+                serde_json::Value::String("********".to_string())
+            } else {
+                value.clone()
+            }
+        };
+
+        let text = match (key, value) {
+            (None, _) => {
+                let site_config = self.frappe_analyzer.read_site_config(&config_path)?;
+                let shown: serde_json::Map<String, serde_json::Value> = site_config
+                    .iter()
+                    .map(|(k, v)| (k.clone(), display(k, v)))
+                    .collect();
+                let apps = self
+                    .frappe_analyzer
+                    .site_installed_apps(Path::new(&config.bench_path), site);
+                format!(
+                    "⚙️ {} ({})\n📱 Installed Apps: {}\n\n{}",
+                    site,
+                    config_path.display(),
+                    if apps.is_empty() {
+                        "Unknown".to_string()
+                    } else {
+                        apps.join(", ")
+                    },
+                    serde_json::to_string_pretty(&shown).map_err(|e| e.to_string())?
+                )
+            }
+            (Some(key), None) => {
+                let site_config = self.frappe_analyzer.read_site_config(&config_path)?;
+                match site_config.get(key) {
+                    Some(v) => format!("⚙️ {}.{} = {}", site, key, display(key, v)),
+                    None => format!("ℹ️ {} is not set in {}", key, config_path.display()),
+                }
+            }
+            (Some(key), Some(value)) => {
+                let stored =
+                    self.frappe_analyzer
+                        .set_site_config_value(&config_path, key, value)?;
+                format!("✅ Set {}.{} = {}", site, key, display(key, &stored))
+            }
+        };
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn diff_sites(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let [left, right] = parsed.positional() else {
+            return Err("Usage: /frappe-diff-sites <siteA> <siteB>".to_string());
+        };
+
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let load = |site: &str| {
+            let config_path = Path::new(&config.sites_path)
+                .join(site)
+                .join("site_config.json");
+            if !config_path.is_file() {
+                return Err(format!(
+                    "Site '{}' not found in {}",
+                    site, config.sites_path
+                ));
+            }
+            self.frappe_analyzer
+                .read_site_config(&config_path)
+                .map_err(|e| format!("{}: {}", site, e))
+        };
+        let (left_config, right_config) = (load(left)?, load(right)?);
+        let diff = frappe_utils::diff_site_configs(&left_config, &right_config);
+
+        let show = |key: &str, value: &serde_json::Value| {
+            if frappe_utils::is_sensitive_config_key(key) {
+                "********".to_string()
+            } else {
+                value.to_string()
+            }
+        };
+        let mut text = format!("🔀 Site config: {} ↔ {}\n", left, right);
+        if diff.is_empty() {
+            text.push_str("\n✅ Both configs are identical");
+        }
+        for (site, keys, site_config) in [
+            (left, &diff.only_left, &left_config),
+            (right, &diff.only_right, &right_config),
+        ] {
+            if keys.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("\nOnly in {} ({}):\n", site, keys.len()));
+            for key in keys {
+                text.push_str(&format!("  • {} = {}\n", key, show(key, &site_config[key])));
+            }
+        }
+        if !diff.changed.is_empty() {
+            text.push_str(&format!("\nDifferent values ({}):\n", diff.changed.len()));
+            for key in &diff.changed {
+                if frappe_utils::is_sensitive_config_key(key) {
+                    text.push_str(&format!("  • {}: values differ (redacted)\n", key));
+                } else {
                     text.push_str(&format!(
-                        "\n📋 {} diagnostics generated for failed tests",
-                        diagnostics.len()
+                        "  • {}: {} = {} | {} = {}\n",
+                        key, left, left_config[key], right, right_config[key]
                     ));
                 }
-
-                Ok(SlashCommandOutput {
-                    text,
-                    sections: vec![],
-                })
             }
-            Err(e) => Err(format!("Failed to run tests: {}", e)),
         }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
     }
 
-    fn create_doctype_json(&self, name: &str, module: &str) -> String {
-        let _snake_case = name.to_lowercase().replace(" ", "_");
-        let suggested_fields = self.generate_smart_fields(name);
-        let fields_json = suggested_fields
-            .iter()
-            .enumerate()
-            .map(|(i, (fieldname, fieldtype, label))| {
-                format!(
-                    r#"        {{
-            "fieldname": "{}",
-            "fieldtype": "{}",
-            "label": "{}",
-            "reqd": {}
-        }}"#,
-                    fieldname,
-                    fieldtype,
-                    label,
-                    if i == 0 { 1 } else { 0 }
+    fn maintenance_mode(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let enable = match parsed.get(0) {
+            Some("on") => true,
+            Some("off") => false,
+            _ => {
+                return Err(
+                    "Usage: /frappe-maintenance-mode <on|off> [site] [--pause-scheduler]"
+                        .to_string(),
                 )
-            })
-            .collect::<Vec<_>>()
-            .join(",\n");
+            }
+        };
 
-        let field_order = suggested_fields
-            .iter()
-            .map(|(fieldname, _, _)| format!(r#"        "{}""#, fieldname))
-            .collect::<Vec<_>>()
-            .join(",\n");
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let site = parsed
+            .get(1)
+            .map(|s| s.to_string())
+            .or(config.default_site.clone())
+            .ok_or("No site given and no default site configured".to_string())?;
+        let config_path = Path::new(&config.sites_path)
+            .join(&site)
+            .join("site_config.json");
+        if !config_path.is_file() {
+            return Err(format!(
+                "Site '{}' not found in {}",
+                site, config.sites_path
+            ));
+        }
 
-        format!(
-            r#"{{
-    "actions": [],
-    "allow_rename": 1,
-    "creation": "2024-01-01 00:00:00.000000",
-    "doctype": "DocType",
-    "editable_grid": 1,
-    "engine": "InnoDB",
-    "field_order": [
-{}
-    ],
-    "fields": [
-{}
-    ],
-    "index_web_pages_for_search": 1,
-    "links": [],
-    "modified": "2024-01-01 00:00:00.000000",
-    "modified_by": "Administrator",
-    "module": "{}",
-    "name": "{}",
-    "naming_rule": "By fieldname",
-    "owner": "Administrator",
-    "permissions": [
-        {{
-            "create": 1,
-            "delete": 1,
-            "email": 1,
-            "export": 1,
-            "print": 1,
-            "read": 1,
-            "report": 1,
-            "role": "System Manager",
-            "share": 1,
-            "write": 1
-        }}
-    ],
-    "sort_field": "modified",
-    "sort_order": "DESC",
-    "states": [],
-    "track_changes": 1
-}}"#,
-            field_order, fields_json, module, name
-        )
+        let mut keys = vec!["maintenance_mode"];
+        if parsed.has("pause-scheduler") {
+            keys.push("pause_scheduler");
+        }
+
+        let previous = self.frappe_analyzer.read_site_config(&config_path)?;
+        let state = |value: Option<&serde_json::Value>| match value {
+            Some(serde_json::Value::Number(n)) if n.as_i64() != Some(0) => "on",
+            Some(serde_json::Value::Bool(true)) => "on",
+            _ => "off",
+        };
+
+        let mut text = format!(
+            "{} Maintenance mode {} for {}\n",
+            if enable { "🚧" } else { "✅" },
+            if enable { "enabled" } else { "disabled" },
+            site
+        );
+        for key in keys {
+            self.frappe_analyzer.set_site_config_value(
+                &config_path,
+                key,
+                if enable { "1" } else { "0" },
+            )?;
+            text.push_str(&format!(
+                "  {}: {} → {}\n",
+                key,
+                state(previous.get(key)),
+                if enable { "on" } else { "off" }
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
     }
 
-    fn generate_smart_fields(&self, doctype_name: &str) -> Vec<(String, String, String)> {
-        let mut fields = Vec::new();
+    fn list_apps(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
 
-        let name_field = if doctype_name.to_lowercase().contains("item") {
-            (
-                "item_name".to_string(),
-                "Data".to_string(),
-                "Item Name".to_string(),
-            )
-        } else {
-            ("title".to_string(), "Data".to_string(), "Title".to_string())
-        };
-        fields.push(name_field);
+        let apps = self
+            .frappe_analyzer
+            .read_apps_txt(Path::new(&config.bench_path))?;
+        if apps.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: "ℹ️ apps.txt lists no apps".to_string(),
+                sections: vec![],
+            });
+        }
 
-        let doctype_lower = doctype_name.to_lowercase();
+        let mut text = format!("📦 Apps ({})\n\n", apps.len());
+        for app in &apps {
+            let app_path = Path::new(&config.apps_path).join(app);
+            let installed = self
+                .frappe_analyzer
+                .find_installed_app(Path::new(&config.bench_path), app);
+            let line = if let (false, Some(site_packages)) = (app_path.is_dir(), installed) {
+                format!(
+                    "  🔒 {} {} (installed in env, read-only)\n",
+                    app,
+                    self.frappe_analyzer
+                        .read_app_version(&site_packages, app)
+                        .unwrap_or_else(|| "(version unknown)".to_string())
+                )
+            } else if !app_path.is_dir() {
+                format!("  ⚠️ {} (missing from apps/)\n", app)
+            } else {
+                match self.frappe_analyzer.read_app_version(&app_path, app) {
+                    Some(version) => format!("  • {} {}\n", app, version),
+                    None => format!("  • {} (version unknown)\n", app),
+                }
+            };
+            text.push_str(&line);
+        }
 
-        if doctype_lower.contains("customer") || doctype_lower.contains("supplier") {
-            fields.push((
-                "contact_person".to_string(),
-                "Data".to_string(),
-                "Contact Person".to_string(),
-            ));
-            fields.push(("email".to_string(), "Data".to_string(), "Email".to_string()));
-            fields.push((
-                "phone".to_string(),
-                "Phone".to_string(),
-                "Phone".to_string(),
-            ));
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn app_doctypes(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let app = args
+            .first()
+            .ok_or("Usage: /frappe-app-doctypes <app>".to_string())?;
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+        let doctypes = analyzer.app_doctypes(app)?;
+        if doctypes.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: format!("ℹ️ {} has no DocTypes", app),
+                sections: vec![],
+            });
         }
 
-        if doctype_lower.contains("transaction")
-            || doctype_lower.contains("order")
-            || doctype_lower.contains("invoice")
-        {
-            fields.push(("date".to_string(), "Date".to_string(), "Date".to_string()));
-            fields.push((
-                "total_amount".to_string(),
-                "Currency".to_string(),
-                "Total Amount".to_string(),
-            ));
-            fields.push((
-                "status".to_string(),
-                "Select".to_string(),
-                "Status".to_string(),
+        let mut text = format!("📋 DocTypes in {} ({})\n", app, doctypes.len());
+        let mut module = None;
+        for doctype in doctypes {
+            if module != Some(&doctype.module) {
+                module = Some(&doctype.module);
+                text.push_str(&format!("\n📁 {}\n", doctype.module));
+            }
+            text.push_str(&format!(
+                "  • {} ({} field{}){}\n",
+                doctype.name,
+                doctype.fields.len(),
+                if doctype.fields.len() == 1 { "" } else { "s" },
+                if doctype.controller_path.is_some() {
+                    ""
+                } else {
+                    " ⚠️ no controller"
+                }
             ));
         }
 
-        if doctype_lower.contains("item") || doctype_lower.contains("product") {
-            fields.push((
-                "item_code".to_string(),
-                "Data".to_string(),
-                "Item Code".to_string(),
-            ));
-            fields.push((
-                "description".to_string(),
-                "Text".to_string(),
-                "Description".to_string(),
-            ));
-            fields.push((
-                "unit_price".to_string(),
-                "Currency".to_string(),
-                "Unit Price".to_string(),
-            ));
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn permissions_matrix(
+        &self,
+        app: Option<&str>,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let mut analyzer = FrappeAnalyzer::new();
+        let root_path_str = self.bench_root(worktree);
+        if analyzer.analyze_project(Path::new(&root_path_str)).is_err() {
+            return Err("Failed to analyze Frappe project".to_string());
+        }
+        let matrix = analyzer.permission_matrix(app)?;
+        let scope = app.unwrap_or("all apps");
+        if matrix.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: format!("ℹ️ No DocType permissions found in {}", scope),
+                sections: vec![],
+            });
         }
 
-        fields.push((
-            "is_active".to_string(),
-            "Check".to_string(),
-            "Is Active".to_string(),
-        ));
-        fields.push((
-            "remarks".to_string(),
-            "Text".to_string(),
-            "Remarks".to_string(),
-        ));
+        const RIGHTS: [&str; 5] = ["Read", "Write", "Create", "Delete", "Submit"];
+        let role_width = matrix
+            .iter()
+            .map(|row| row.role.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("Role".len());
 
-        fields
+        let mut text = format!(
+            "🔐 Permissions in {} (DocTypes granting each right)\n\n{:<role_width$}",
+            scope, "Role"
+        );
+        for right in RIGHTS {
+            text.push_str(&format!("  {:>6}", right));
+        }
+        text.push('\n');
+        for row in &matrix {
+            text.push_str(&format!("{:<role_width$}", row.role));
+            for count in [row.read, row.write, row.create, row.delete, row.submit] {
+                text.push_str(&format!("  {:>6}", count));
+            }
+            text.push('\n');
+        }
+
+        let suspicious: Vec<&str> = matrix
+            .iter()
+            .filter(|row| row.writes_without_read())
+            .map(|row| row.role.as_str())
+            .collect();
+        if !suspicious.is_empty() {
+            text.push_str(&format!(
+                "\n⚠️ Write without read on any DocType (likely misconfigured): {}\n",
+                suspicious.join(", ")
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
     }
 
-    fn create_doctype_controller(&self, name: &str, _module: &str) -> String {
-        let _snake_case = name.to_lowercase().replace(" ", "_");
-        format!(
-            r#"# Copyright (c) 2024, Frappe Technologies and contributors
-# For license information, please see license.txt
+    fn show_procfile(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let no_dev = parsed.has("no-dev");
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
 
-import frappe
-from frappe.model.document import Document
+        let entries = self
+            .frappe_analyzer
+            .parse_procfile(Path::new(&config.bench_path));
+        if entries.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: format!(
+                    "ℹ️ No Procfile services found in {}\nRun `bench setup procfile` to create one.",
+                    config.bench_path
+                ),
+                sections: vec![],
+            });
+        }
+
+        let mut text = format!(
+            "🧾 bench start{} launches:\n\n",
+            if no_dev { " --no-dev" } else { "" }
+        );
+        let width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+        for entry in entries.iter().filter(|e| !(no_dev && e.dev_only())) {
+            text.push_str(&format!(
+                "  • {:<width$}  {}{}\n",
+                entry.name,
+                entry.command,
+                entry
+                    .port
+                    .map(|port| format!("  (port {})", port))
+                    .unwrap_or_default(),
+                width = width
+            ));
+        }
+        if no_dev {
+            text.push_str("\nℹ️ Without dev mode the web server doesn't auto-reload and assets aren't watched.");
+        }
 
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
 
-class {}(Document):
-    def validate(self):
-        """Called before saving the document"""
-        pass
+    fn frappe_logs(
+        &self,
+        args: &[String],
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &["tail"])?;
+        let count = match parsed.value("tail") {
+            Some(value) => value
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or(format!("--tail must be a positive number, got '{}'", value))?,
+            None => 50,
+        };
+        let bench_root = self.bench_root(worktree);
+        let bench_path = Path::new(&bench_root);
+        let analyzer = FrappeAnalyzer::new();
 
-    def before_save(self):
-        """Called before saving the document"""
-        pass
+        let Some(name) = parsed.get(0) else {
+            let logs = analyzer.bench_logs(bench_path)?;
+            if logs.is_empty() {
+                return Ok(SlashCommandOutput {
+                    text: format!("ℹ️ No log files in {}/logs", bench_root),
+                    sections: vec![],
+                });
+            }
+            let mut text = format!("📜 Frappe logs in {}/logs ({}):\n", bench_root, logs.len());
+            for log in &logs {
+                text.push_str(&format!("  • {} ({} bytes)\n", log.name, log.size));
+            }
+            return Ok(SlashCommandOutput {
+                text,
+                sections: vec![],
+            });
+        };
 
-    def after_insert(self):
-        """Called after inserting the document"""
-        pass
+        let lines = analyzer.tail_bench_log(bench_path, name, count)?;
+        let mut text = format!("📜 {} (last {} lines)\n\n", name, lines.len());
+        for line in &lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
 
-    def on_update(self):
-        """Called after updating the document"""
-        pass
+    fn open_site(
+        &self,
+        site: Option<&str>,
+        worktree: &Worktree,
+    ) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let site = match site {
+            Some(site) => {
+                if !Path::new(&config.sites_path).join(site).is_dir() {
+                    return Err(format!(
+                        "Site '{}' not found in {}",
+                        site, config.sites_path
+                    ));
+                }
+                Some(site.to_string())
+            }
+            None => config.default_site.clone(),
+        };
 
-    def on_cancel(self):
-        """Called when cancelling the document"""
-        pass
+        let bench_path = Path::new(&config.bench_path);
+        let url = self.frappe_analyzer.site_url(bench_path, site.as_deref());
+        let port = self.frappe_analyzer.web_port(bench_path);
+        // Frappe routes by Host header, so ping as the site even when the URL
+        // falls back to localhost
+        let ping = process_manager::http_ping(
+            "127.0.0.1",
+            port,
+            site.as_deref().unwrap_or("localhost"),
+            Duration::from_secs(2),
+        );
 
-    def on_trash(self):
-        """Called before deleting the document"""
-        pass
-"#,
-            name.replace(" ", "")
-        )
+        let opener = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(target_os = "windows") {
+            "start"
+        } else {
+            "xdg-open"
+        };
+        let mut text = format!("🌐 {}\n\n", url);
+        match ping {
+            Ok(latency) => text.push_str(&format!(
+                "✅ The site is responding ({} ms)\n",
+                latency.as_millis()
+            )),
+            Err(e) => text.push_str(&format!(
+                "⚠️ Nothing answered on port {}: {}\nStart the server with /frappe-bench-start.\n",
+                port, e
+            )),
+        }
+        if let Some(site) = site.as_deref() {
+            if !url.starts_with(&format!("http://{}:", site)) {
+                text.push_str(&format!(
+                    "ℹ️ {} doesn't resolve on this machine, so the URL uses localhost; with several sites, add it to /etc/hosts to reach it by name.\n",
+                    site
+                ));
+            }
+        }
+        text.push_str(&format!("\nOpen it with: {} {}", opener, url));
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
     }
 
-    fn create_doctype_client_script(&self, name: &str) -> String {
-        format!(
-            r#"// Copyright (c) 2024, Frappe Technologies and contributors
-// For license information, please see license.txt
+    fn health_check(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        const TIMEOUT: Duration = Duration::from_secs(2);
 
-frappe.ui.form.on('{}', {{
-    refresh: function(frm) {{
-        // Called when form is refreshed
-    }},
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let site = config
+            .default_site
+            .clone()
+            .unwrap_or_else(|| "localhost".to_string());
+        let endpoints = self
+            .frappe_analyzer
+            .service_endpoints(Path::new(&config.bench_path));
 
-    onload: function(frm) {{
-        // Called when form is loaded
-    }},
+        // Every probe has its own timeout, so running them side by side
+        // bounds the whole command by the slowest one
+        let results: Vec<Result<Duration, String>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = endpoints
+                .iter()
+                .map(|endpoint| {
+                    let site = &site;
+                    scope.spawn(move || {
+                        if endpoint.name == "web" {
+                            process_manager::http_ping(&endpoint.host, endpoint.port, site, TIMEOUT)
+                        } else {
+                            process_manager::check_tcp(&endpoint.host, endpoint.port, TIMEOUT)
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(Err("check panicked".to_string())))
+                .collect()
+        });
 
-    before_save: function(frm) {{
-        // Called before saving the document
-    }},
+        let mut text = format!(
+            "🩺 Dev Stack Health ({})\nURL: {}\n\n",
+            site,
+            self.frappe_analyzer.site_url(
+                Path::new(&config.bench_path),
+                config.default_site.as_deref()
+            )
+        );
+        let mut failures = 0;
+        for (endpoint, result) in endpoints.iter().zip(results) {
+            let target = if endpoint.name == "web" {
+                format!("{}:{}/api/method/ping", endpoint.host, endpoint.port)
+            } else {
+                format!("{}:{}", endpoint.host, endpoint.port)
+            };
+            match result {
+                Ok(latency) => text.push_str(&format!(
+                    "  ✅ {} {} ({} ms)\n",
+                    endpoint.name,
+                    target,
+                    latency.as_millis()
+                )),
+                Err(e) => {
+                    failures += 1;
+                    text.push_str(&format!("  ❌ {} {}: {}\n", endpoint.name, target, e));
+                }
+            }
+        }
 
-    after_save: function(frm) {{
-        // Called after saving the document
-    }},
+        match self.process_manager.get_bench_process_id() {
+            Some(id) => text.push_str(&format!("  ✅ bench start tracked as {}\n", id)),
+            None => {
+                failures += 1;
+                text.push_str("  ❌ bench start is not running under Latte\n");
+            }
+        }
 
-    validate: function(frm) {{
-        // Called during validation
-    }}
-}});
-"#,
-            name
-        )
+        text.push_str(&if failures == 0 {
+            "\n✅ All checks passed".to_string()
+        } else {
+            format!(
+                "\n⚠️ {} check{} failed. Start the stack with /frappe-bench-start.",
+                failures,
+                if failures == 1 { "" } else { "s" }
+            )
+        });
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
     }
 
-    fn search_doctypes(
-        &self,
-        query: &str,
-        worktree: &Worktree,
-    ) -> Result<SlashCommandOutput, String> {
-        let mut analyzer = FrappeAnalyzer::new();
-        let root_path_str = worktree.root_path(); // own the String
-        let root_path = Path::new(&root_path_str); // borrow from the owned String
-        if let Err(_) = analyzer.analyze_project(root_path) {
-            return Err("Failed to analyze Frappe project".to_string());
-        }
+    fn git_status(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
 
-        let results = analyzer.search_doctypes(query);
+        let apps = self
+            .frappe_analyzer
+            .read_apps_txt(Path::new(&config.bench_path))?;
 
-        if results.is_empty() {
-            return Ok(SlashCommandOutput {
-                text: format!("No DocTypes found matching '{}'", query),
-                sections: vec![],
-            });
-        }
+        // Each app is its own repo, so query them in parallel
+        let statuses: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = apps
+                .iter()
+                .map(|app| {
+                    let app_path = Path::new(&config.apps_path).join(app);
+                    scope.spawn(move || process_manager::git_status(&app_path))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().ok().flatten())
+                .collect()
+        });
 
-        let mut text = format!("Found {} DocTypes matching '{}':\n\n", results.len(), query);
-        for doctype in results.iter().take(10) {
+        let mut text = "🔀 Git Status\n\n".to_string();
+        let mut skipped = Vec::new();
+        for (app, status) in apps.iter().zip(statuses) {
+            match status {
+                Some(status) if status.dirty_files == 0 => {
+                    text.push_str(&format!("  ✅ {} [{}] clean\n", app, status.branch))
+                }
+                Some(status) => text.push_str(&format!(
+                    "  ✏️ {} [{}] {} changed file{}\n",
+                    app,
+                    status.branch,
+                    status.dirty_files,
+                    if status.dirty_files == 1 { "" } else { "s" }
+                )),
+                None => skipped.push(app.as_str()),
+            }
+        }
+        if !skipped.is_empty() {
             text.push_str(&format!(
-                "• {} (Module: {})\n  Path: {}\n  Fields: {}\n\n",
-                doctype.name,
-                doctype.module,
-                doctype.file_path.display(),
-                doctype.fields.len()
+                "\nℹ️ Not git repositories: {}\n",
+                skipped.join(", ")
             ));
         }
 
-        if results.len() > 10 {
-            text.push_str(&format!("... and {} more results\n", results.len() - 10));
-        }
-
         Ok(SlashCommandOutput {
             text,
             sections: vec![],
@@ -758,30 +4670,59 @@ frappe.ui.form.on('{}', {{
 
     fn analyze_current_project(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
         let mut analyzer = FrappeAnalyzer::new();
-        let root_path_str = worktree.root_path(); // own the String
+        let root_path_str = self.bench_root(worktree);
         let root_path = Path::new(&root_path_str); // borrow from the owned String
         match analyzer.analyze_project(root_path) {
             Ok(_) => {
-                if let Some(project) = analyzer.get_project() {
-                    let mut text = format!("📊 Frappe Project Analysis\n");
+                let result = if let Some(project) = analyzer.get_project() {
+                    let mut text = "📊 Frappe Project Analysis\n".to_string();
                     text.push_str(&format!(
                         "📁 Bench Path: {}\n",
                         project.bench_path.display()
                     ));
                     text.push_str(&format!(
-                        "🌐 Default Site: {}\n\n",
+                        "🌐 Default Site: {}\n",
                         project.default_site.as_deref().unwrap_or("Not configured")
                     ));
+                    text.push_str(&format!(
+                        "🪑 Bench Version: {}\n\n",
+                        self.process_manager
+                            .detect_bench_version(&project.bench_path.to_string_lossy())
+                            .unwrap_or_else(|| "Unknown".to_string())
+                    ));
+
+                    let app_field_stats = analyzer.field_type_stats_by_app();
 
                     text.push_str(&format!("📱 Apps ({}):\n", project.apps.len()));
                     for app in &project.apps {
                         text.push_str(&format!(
-                            "  • {} ({} DocTypes, {} Pages, {} Reports)\n",
+                            "  • {} ({} DocTypes, {} Pages, {} Reports){}\n",
                             app.name,
                             app.doctypes.len(),
                             app.pages.len(),
-                            app.reports.len()
+                            app.reports.len(),
+                            if app.editable {
+                                ""
+                            } else {
+                                " [installed in env, read-only]"
+                            }
                         ));
+
+                        let total_fields: usize =
+                            app.doctypes.iter().map(|dt| dt.fields.len()).sum();
+                        if total_fields > 0 {
+                            text.push_str(&format!("      Fields: {}\n", total_fields));
+                            if let Some(top_types) = app_field_stats.get(&app.name) {
+                                let top = top_types
+                                    .iter()
+                                    .map(|(field_type, count)| {
+                                        format!("{} ({})", field_type, count)
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join(", ");
+                                text.push_str(&format!("      Top Field Types: {}\n", top));
+                            }
+                        }
                     }
 
                     text.push_str(&format!("\n🏢 Sites ({}):\n", project.sites.len()));
@@ -791,6 +4732,10 @@ frappe.ui.form.on('{}', {{
                             site.name,
                             site.database.as_deref().unwrap_or("Unknown")
                         ));
+                        let apps = analyzer.site_installed_apps(&project.bench_path, &site.name);
+                        if !apps.is_empty() {
+                            text.push_str(&format!("      Apps: {}\n", apps.join(", ")));
+                        }
                     }
 
                     let total_doctypes: usize = project.apps.iter().map(|a| a.doctypes.len()).sum();
@@ -807,7 +4752,7 @@ frappe.ui.form.on('{}', {{
                         }
 
                         let mut sorted_types: Vec<_> = field_types.into_iter().collect();
-                        sorted_types.sort_by(|a, b| b.1.cmp(&a.1));
+                        sorted_types.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
 
                         text.push_str("📊 Top Field Types:\n");
                         for (field_type, count) in sorted_types.iter().take(5) {
@@ -815,30 +4760,396 @@ frappe.ui.form.on('{}', {{
                         }
                     }
 
+                    let timings = &project.timings;
+                    text.push_str(&format!(
+                        "\n⏱️ Analysis completed in {} ms (apps: {} ms, sites: {} ms, default site: {} ms)\n",
+                        timings.total.as_millis(),
+                        timings.apps.as_millis(),
+                        timings.sites.as_millis(),
+                        timings.default_site.as_millis()
+                    ));
+
+                    if !project.skipped_paths.is_empty() {
+                        text.push_str(&format!(
+                            "\n⚠️ Skipped {} unreadable path(s), results may be incomplete:\n",
+                            project.skipped_paths.len()
+                        ));
+                        for (path, reason) in project.skipped_paths.iter().take(10) {
+                            text.push_str(&format!("  • {}: {}\n", path.display(), reason));
+                        }
+                        if project.skipped_paths.len() > 10 {
+                            text.push_str(&format!(
+                                "  … and {} more\n",
+                                project.skipped_paths.len() - 10
+                            ));
+                        }
+                    }
+                    if !project.parse_errors.is_empty() {
+                        text.push_str(&format!(
+                            "\n⚠️ {} file(s) failed to parse (run /frappe-doctor for details)\n",
+                            project.parse_errors.len()
+                        ));
+                    }
+
                     Ok(SlashCommandOutput {
                         text,
                         sections: vec![],
                     })
                 } else {
                     Err("Failed to get project information".to_string())
-                }
+                };
+                *self.analyzed_project.lock().unwrap() = Some(analyzer);
+                result
             }
             Err(e) => Err(format!("Analysis failed: {}", e)),
         }
     }
 
-    fn list_running_processes(&self) -> Result<SlashCommandOutput, String> {
-        let processes = self.process_manager.list_running_processes();
+    fn show_stats(&self, worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let root_path_str = self.bench_root(worktree);
+        let root_path = Path::new(&root_path_str);
+
+        let mut cached = self.analyzed_project.lock().unwrap();
+        let reusable = cached
+            .as_ref()
+            .and_then(|analyzer| analyzer.get_project())
+            .is_some_and(|project| project.bench_path == root_path);
+        if !reusable {
+            let mut analyzer = FrappeAnalyzer::new();
+            analyzer
+                .analyze_project(root_path)
+                .map_err(|e| format!("Analysis failed: {}", e))?;
+            *cached = Some(analyzer);
+        }
+        let stats = cached
+            .as_ref()
+            .map(|analyzer| analyzer.project_stats())
+            .unwrap_or_default();
+
+        let mut text = "📊 Frappe Project Stats\n".to_string();
+        text.push_str(&format!("📱 Apps: {}\n", stats.apps));
+        text.push_str(&format!("📋 DocTypes: {}\n", stats.doctypes));
+        text.push_str(&format!("📄 Pages: {}\n", stats.pages));
+        text.push_str(&format!("📈 Reports: {}\n", stats.reports));
+        text.push_str(&format!("🔤 Fields: {}\n", stats.fields));
+
+        if !stats.modules.is_empty() {
+            text.push_str("\n🗂️ DocTypes per Module (top 10):\n");
+            for (module, count) in &stats.modules {
+                text.push_str(&format!("  • {}: {}\n", module, count));
+            }
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn doctor(&self, args: &[String], worktree: &Worktree) -> Result<SlashCommandOutput, String> {
+        let parsed = CommandArgs::parse(args, &[])?;
+        let fix = parsed.has("fix");
+        let clean = parsed.has("clean");
+
+        let config = self
+            .detect_frappe_workspace(worktree)
+            .ok_or("Not a Frappe workspace".to_string())?;
+        let apps = self
+            .frappe_analyzer
+            .read_apps_txt(Path::new(&config.bench_path))?;
+
+        let mut text = "🩺 Frappe Doctor\n\n".to_string();
+        let mut problems = 0;
+        for app in &apps {
+            let app_path = Path::new(&config.apps_path).join(app);
+            if !app_path.is_dir() {
+                continue;
+            }
+
+            let missing = self.frappe_analyzer.missing_init_files(&app_path, app);
+            if missing.is_empty() {
+                continue;
+            }
+            problems += missing.len();
+            text.push_str(&format!(
+                "📦 {}: {} missing __init__.py\n",
+                app,
+                missing.len()
+            ));
+            for dir in &missing {
+                let init = dir.join("__init__.py");
+                if fix {
+                    fs::write(&init, "")
+                        .map_err(|e| format!("Could not create {}: {}", init.display(), e))?;
+                    text.push_str(&format!("  ✅ Created {}\n", init.display()));
+                } else {
+                    text.push_str(&format!("  ❌ {}\n", dir.display()));
+                }
+            }
+        }
+
+        let mut analyzer = FrappeAnalyzer::new();
+        let missing_inits = problems;
+        if analyzer
+            .analyze_project(Path::new(&config.bench_path))
+            .is_err()
+        {
+            text.push_str("⚠️ Could not analyze DocTypes\n");
+        }
+        if let Some(project) = analyzer.get_project() {
+            if !project.parse_errors.is_empty() {
+                problems += project.parse_errors.len();
+                text.push_str(&format!(
+                    "🧾 {} file(s) failed to parse and were left out of the analysis\n",
+                    project.parse_errors.len()
+                ));
+                for (path, error) in &project.parse_errors {
+                    text.push_str(&format!("  ❌ {}\n     {}\n", path.display(), error));
+                }
+            }
+            for app in project.apps.iter().filter(|app| app.editable) {
+                let issues = analyzer.doctype_issues(app);
+                if issues.is_empty() {
+                    continue;
+                }
+                problems += issues.len();
+                text.push_str(&format!(
+                    "📦 {}: {} DocType issue(s)\n",
+                    app.name,
+                    issues.len()
+                ));
+                for issue in &issues {
+                    text.push_str(&format!(
+                        "  {} {}: {}\n     {}\n",
+                        if issue.error { "❌" } else { "⚠️" },
+                        issue.doctype,
+                        issue.message,
+                        issue.path.display()
+                    ));
+                }
+            }
+        }
+
+        let mut orphaned_pyc = 0;
+        for app in &apps {
+            let app_path = Path::new(&config.apps_path).join(app);
+            let orphans = self.frappe_analyzer.orphaned_bytecode(&app_path);
+            if orphans.is_empty() {
+                continue;
+            }
+            problems += orphans.len();
+            let bytes: u64 = orphans.iter().map(|file| file.size).sum();
+            text.push_str(&format!(
+                "📦 {}: {} .pyc file(s) without source ({} bytes)\n",
+                app,
+                orphans.len(),
+                bytes
+            ));
+            for orphan in &orphans {
+                if clean {
+                    fs::remove_file(&orphan.path).map_err(|e| {
+                        format!("Could not remove {}: {}", orphan.path.display(), e)
+                    })?;
+                    // Drop __pycache__ folders left empty; fails harmlessly otherwise
+                    if let Some(cache) = orphan
+                        .path
+                        .parent()
+                        .filter(|dir| dir.ends_with("__pycache__"))
+                    {
+                        let _ = fs::remove_dir(cache);
+                    }
+                    text.push_str(&format!("  🗑️ Removed {}\n", orphan.path.display()));
+                } else {
+                    orphaned_pyc += 1;
+                    text.push_str(&format!("  ⚠️ {}\n", orphan.path.display()));
+                }
+            }
+        }
+
+        let outdated = self
+            .frappe_analyzer
+            .outdated_assets(Path::new(&config.bench_path));
+        if !outdated.is_empty() {
+            problems += outdated.len();
+            let bytes: u64 = outdated.iter().map(|file| file.size).sum();
+            text.push_str(&format!(
+                "🧱 {} built asset(s) older than their source ({} bytes)\n",
+                outdated.len(),
+                bytes
+            ));
+            for asset in &outdated {
+                text.push_str(&format!("  ⚠️ {}\n", asset.path.display()));
+            }
+            text.push_str("  ℹ️ Run /frappe-bench-build to rebuild them.\n");
+        }
+
+        if problems == 0 {
+            text.push_str("✅ No problems found");
+        } else {
+            if !fix && missing_inits > 0 {
+                text.push_str("\nℹ️ Run /frappe-doctor --fix to create the missing files.");
+            }
+            if orphaned_pyc > 0 {
+                text.push_str(
+                    "\nℹ️ Run /frappe-doctor --clean to remove the stale .pyc files (sources are never touched).",
+                );
+            }
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    /// `/frappe-list-processes all|running|stopped|failed|killed [text]`; any
+    /// other word filters by command.
+    fn list_processes_filtered(&self, args: &[String]) -> Result<SlashCommandOutput, String> {
+        let mut words: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        let status = match words.first().map(|w| w.to_lowercase()).as_deref() {
+            Some("all") => None,
+            Some("running") => Some(ProcessStatus::Running),
+            Some("stopped") => Some(ProcessStatus::Stopped),
+            Some("failed") => Some(ProcessStatus::Failed),
+            Some("killed") => Some(ProcessStatus::Killed),
+            _ => {
+                words.insert(0, "all");
+                None
+            }
+        };
+        let query = words[1..].join(" ");
+        let query = (!query.is_empty()).then_some(query);
+
+        let mut processes = self
+            .process_manager
+            .list_processes_filtered(status.clone(), query.as_deref());
+        processes.sort_by_key(|p| p.start_time);
 
+        let mut filter = match &status {
+            Some(status) => format!("{:?}", status).to_lowercase(),
+            None => "all".to_string(),
+        };
+        if let Some(query) = &query {
+            filter.push_str(&format!(", command contains '{}'", query));
+        }
         if processes.is_empty() {
             return Ok(SlashCommandOutput {
-                text: "ℹ️ No Frappe processes are currently running".to_string(),
+                text: format!("ℹ️ No processes match ({})", filter),
                 sections: vec![],
             });
         }
 
-        let mut text = format!("🔄 Running Processes ({})\n\n", processes.len());
+        let mut text = format!("🔄 Processes ({}): {}\n\n", filter, processes.len());
         for process in processes {
+            let icon = match process.status {
+                ProcessStatus::Starting | ProcessStatus::Running => "🟢",
+                ProcessStatus::Stopped => "⚪",
+                ProcessStatus::Failed | ProcessStatus::Killed => "🔴",
+            };
+            text.push_str(&format!("{} {}\n", icon, process.id));
+            text.push_str(&format!("   Command: {}\n", process.command_summary()));
+            text.push_str(&format!("   Status: {:?}\n", process.status));
+            if let Some(code) = process.exit_code {
+                text.push_str(&format!("   Exit Code: {}\n", code));
+            }
+            if process.restarts > 0 {
+                text.push_str(&format!("   Restarts: {}\n", process.restarts));
+            }
+            text.push('\n');
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn show_config(&self) -> Result<SlashCommandOutput, String> {
+        if let Some(error) = self.settings_error.lock().unwrap().clone() {
+            return Ok(SlashCommandOutput {
+                text: format!("❌ Could not load settings\n{}", error),
+                sections: vec![],
+            });
+        }
+
+        let loaded = self.settings.lock().unwrap().clone();
+        let settings = &loaded.settings;
+
+        let mut text = "⚙️ Latte Settings\n".to_string();
+        text.push_str(&format!(
+            "📄 Source: {}\n\n",
+            loaded
+                .path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| format!("defaults (no {} found)", settings::SETTINGS_FILE))
+        ));
+
+        let entries = [
+            ("bench_executable", settings.bench_executable.clone()),
+            (
+                "default_site",
+                settings
+                    .default_site
+                    .clone()
+                    .unwrap_or_else(|| "(from common_site_config.json)".to_string()),
+            ),
+            ("log_buffer_size", settings.log_buffer_size.to_string()),
+            (
+                "log_capture_level",
+                settings.log_capture_level.as_str().to_string(),
+            ),
+            (
+                "command_timeout_secs",
+                settings.command_timeout_secs.to_string(),
+            ),
+            ("max_output_lines", settings.max_output_lines.to_string()),
+            (
+                "collapse_repeated_lines",
+                settings.collapse_repeated_lines.to_string(),
+            ),
+            ("output_style", settings.output_style.as_str().to_string()),
+            (
+                "keepalive_restarts",
+                settings.keepalive_restarts.to_string(),
+            ),
+        ];
+        for (key, value) in entries {
+            text.push_str(&format!(
+                "  • {}: {} ({})\n",
+                key,
+                value,
+                loaded.source_of(key)
+            ));
+        }
+
+        Ok(SlashCommandOutput {
+            text,
+            sections: vec![],
+        })
+    }
+
+    fn list_running_processes(&self, args: &[String]) -> Result<SlashCommandOutput, String> {
+        if !args.is_empty() {
+            return self.list_processes_filtered(args);
+        }
+
+        let mut processes = self.process_manager.list_processes();
+
+        if processes.is_empty() {
+            return Ok(SlashCommandOutput {
+                text: "ℹ️ No Frappe processes are currently running".to_string(),
+                sections: vec![],
+            });
+        }
+
+        processes.sort_by_key(|p| p.start_time);
+        let (running, finished): (Vec<_>, Vec<_>) = processes
+            .into_iter()
+            .partition(|p| matches!(p.status, ProcessStatus::Starting | ProcessStatus::Running));
+
+        let mut text = format!("🔄 Running Processes ({})\n\n", running.len());
+        for process in running {
             let duration = process
                 .start_time
                 .elapsed()
@@ -852,11 +5163,35 @@ frappe.ui.form.on('{}', {{
                     .map(|p| p.to_string())
                     .unwrap_or("N/A".to_string())
             ));
-            text.push_str(&format!("   Command: {}\n", process.command));
+            text.push_str(&format!("   Command: {}\n", process.command_summary()));
             text.push_str(&format!("   Running for: {}s\n", duration));
+            if process.error_count > 0 {
+                text.push_str(&format!("   Errors: {}\n", process.error_count));
+            }
             text.push_str(&format!("   Status: {:?}\n\n", process.status));
         }
 
+        if !finished.is_empty() {
+            text.push_str(&format!("🏁 Finished Processes ({})\n\n", finished.len()));
+            for process in finished {
+                let icon = if process.status == ProcessStatus::Stopped {
+                    "⚪"
+                } else {
+                    "🔴"
+                };
+                text.push_str(&format!("{} {}\n", icon, process.id));
+                text.push_str(&format!("   Command: {}\n", process.command_summary()));
+                text.push_str(&format!("   Status: {:?}\n", process.status));
+                text.push_str(&format!(
+                    "   Exit Code: {}\n\n",
+                    process
+                        .exit_code
+                        .map(|c| c.to_string())
+                        .unwrap_or("N/A".to_string())
+                ));
+            }
+        }
+
         Ok(SlashCommandOutput {
             text,
             sections: vec![],
@@ -864,8 +5199,21 @@ frappe.ui.form.on('{}', {{
     }
 
     fn stop_all_processes(&self) -> Result<SlashCommandOutput, String> {
+        let stopped_watchers: Vec<String> = {
+            let mut watchers = self.test_watchers.lock().unwrap();
+            watchers
+                .drain()
+                .map(|(app, watcher)| {
+                    watcher.stop();
+                    format!("test watcher ({})", app)
+                })
+                .collect()
+        };
+
         match self.process_manager.stop_all_processes() {
-            Ok(stopped_processes) => {
+            Ok(mut stopped_processes) => {
+                stopped_processes.extend(stopped_watchers);
+
                 if stopped_processes.is_empty() {
                     Ok(SlashCommandOutput {
                         text: "ℹ️ No processes were running to stop".to_string(),
@@ -893,3 +5241,103 @@ frappe.ui.form.on('{}', {{
 }
 
 register_extension!(LatteExtension);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(args: &[&str]) -> Result<DocTypeFlags, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        DocTypeFlags::from_args(&CommandArgs::parse(&args, &["from"])?)
+    }
+
+    fn fieldnames(doctype: &serde_json::Value) -> Vec<&str> {
+        doctype["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|field| field["fieldname"].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_doctype_flags_reject_conflicts() {
+        assert_eq!(
+            flags(&["Shop Order", "Selling", "--single", "--istable"]).unwrap_err(),
+            "--single and --istable cannot be combined"
+        );
+        assert_eq!(
+            flags(&["Shop Order", "Selling", "--tree", "--single"]).unwrap_err(),
+            "--single and --tree cannot be combined"
+        );
+        assert_eq!(
+            flags(&["Shop Order", "Selling", "--istable", "--tree"]).unwrap_err(),
+            "--istable and --tree cannot be combined"
+        );
+
+        let ok = flags(&["Shop Order", "Selling", "--tree", "--submittable"]).unwrap();
+        assert_eq!(ok.enabled(), vec!["submittable", "tree"]);
+        assert!(flags(&["Shop Order", "Selling"])
+            .unwrap()
+            .enabled()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_create_doctype_json_follows_flags() {
+        let extension = LatteExtension::new();
+
+        let plain =
+            extension.create_doctype_json("Shop Order", "Selling", &DocTypeFlags::default());
+        assert_eq!(plain["naming_rule"], "By fieldname");
+        assert!(plain.get("issingle").is_none());
+        assert_eq!(plain["permissions"][0]["delete"], 1);
+
+        let single = extension.create_doctype_json(
+            "Shop Settings",
+            "Selling",
+            &DocTypeFlags {
+                single: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(single["issingle"], 1);
+        assert!(single.get("naming_rule").is_none());
+        assert!(single.get("autoname").is_none());
+        assert!(single["permissions"][0].get("delete").is_none());
+
+        let tree = extension.create_doctype_json(
+            "Shop Territory",
+            "Selling",
+            &DocTypeFlags {
+                tree: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(tree["is_tree"], 1);
+        assert_eq!(tree["nsm_parent_field"], "parent_shop_territory");
+        let names = fieldnames(&tree);
+        for expected in [
+            "parent_shop_territory",
+            "is_group",
+            "lft",
+            "rgt",
+            "old_parent",
+        ] {
+            assert!(names.contains(&expected), "missing {}", expected);
+        }
+        assert_eq!(tree["field_order"].as_array().unwrap().len(), names.len());
+
+        let child = extension.create_doctype_json(
+            "Shop Order Item",
+            "Selling",
+            &DocTypeFlags {
+                istable: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(child["istable"], 1);
+        assert!(child.get("naming_rule").is_none());
+        assert_eq!(child["permissions"], serde_json::json!([]));
+    }
+}
@@ -1,12 +1,17 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use std::process::{ChildStderr, ChildStdout};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::settings::{LatteSettings, OutputStyle};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessInfo {
@@ -17,8 +22,53 @@ pub struct ProcessInfo {
     pub status: ProcessStatus,
     pub pid: Option<u32>,
     pub start_time: SystemTime,
+    pub exit_code: Option<i32>,
+    /// Stdout (plus Latte's own lines) and stderr from the raw log, filled in
+    /// when the info is read.
     pub output_lines: Vec<String>,
     pub error_lines: Vec<String>,
+    /// Error-level lines seen so far, including ones not kept in the log.
+    pub error_count: usize,
+    /// Slash command that started the process, e.g. `frappe-bench-migrate`.
+    /// Empty for processes started outside a command.
+    pub initiator: String,
+    /// Times keepalive has restarted the process after a crash.
+    pub restarts: usize,
+}
+
+impl ProcessInfo {
+    fn starting(
+        id: String,
+        initiator: &str,
+        command: String,
+        args: Vec<String>,
+        working_dir: &str,
+    ) -> Self {
+        Self {
+            id,
+            command,
+            args,
+            working_dir: working_dir.to_string(),
+            status: ProcessStatus::Starting,
+            pid: None,
+            start_time: SystemTime::now(),
+            exit_code: None,
+            output_lines: Vec::new(),
+            error_lines: Vec::new(),
+            error_count: 0,
+            initiator: initiator.to_string(),
+            restarts: 0,
+        }
+    }
+
+    /// `command`, plus the slash command that started it when known.
+    pub fn command_summary(&self) -> String {
+        if self.initiator.is_empty() {
+            self.command.clone()
+        } else {
+            format!("{} (started by /{})", self.command, self.initiator)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -36,97 +86,497 @@ pub struct LogLine {
     pub level: LogLevel,
     pub content: String,
     pub source: LogSource,
+    /// How many identical lines in a row this entry stands for; above 1 only
+    /// when `collapse_repeated_lines` is on.
+    pub repeat_count: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Ordered from least to most severe.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
 pub enum LogLevel {
+    Trace,
+    Debug,
     Info,
     Warning,
     Error,
-    Debug,
-    Trace,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LogSource {
     Stdout,
     Stderr,
     System,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchVersion {
+    pub raw: String,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl BenchVersion {
+    /// Picks the first `major.minor[.patch]` token out of `bench --version` output.
+    pub fn parse(output: &str) -> Option<Self> {
+        let version_re = Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?").ok()?;
+        let captures = version_re.captures(output)?;
+
+        Some(BenchVersion {
+            raw: captures.get(0)?.as_str().to_string(),
+            major: captures.get(1)?.as_str().parse().ok()?,
+            minor: captures.get(2)?.as_str().parse().ok()?,
+            patch: captures
+                .get(3)
+                .and_then(|m| m.as_str().parse().ok())
+                .unwrap_or(0),
+        })
+    }
+
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+
+    // bench 5 is the first release that forwards these options to frappe
+    pub fn supports_verbose_tests(&self) -> bool {
+        self.at_least(5, 0)
+    }
+
+    pub fn supports_ui_tests(&self) -> bool {
+        self.at_least(5, 0)
+    }
+
+    pub fn supports_app_build(&self) -> bool {
+        self.at_least(5, 0)
+    }
+
+    /// Benches before 5 stop after the email prompt; later ones go on to ask
+    /// for a license, GitHub workflow and branch.
+    pub fn supports_new_app_prompts(&self) -> bool {
+        self.at_least(5, 0)
+    }
+}
+
+/// Live processes are recorded here (in the bench root) so they can be
+/// re-adopted after the extension restarts.
+pub const REGISTRY_FILE: &str = ".latte_processes.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RegistryEntry {
+    process_id: String,
+    pid: u32,
+    command: String,
+    working_dir: String,
+    start_time: SystemTime,
+    // Absent in registries written before initiators were tracked
+    #[serde(default)]
+    initiator: String,
+}
+
+/// Called from the reader threads with every line a process prints, as soon
+/// as it arrives.
+pub type LineListener = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Extra wiring for a bench process's stdio on top of the captured output.
+enum StdioHook {
+    Plain,
+    /// Written to stdin, which is then closed.
+    Input(String),
+    Listener(LineListener),
+    /// Script the console runs at startup through `PYTHONSTARTUP`; stdin is
+    /// inherited as for `Plain`.
+    Preload(PathBuf),
+}
+
+/// Display command and argv for `bench --site <site> <command> [args...]`.
+fn site_command_line(site: &str, command: &str, args: Vec<String>) -> (String, Vec<String>) {
+    let full_command = format!("bench --site {} {}", site, command);
+    let mut cmd_args = vec!["--site".to_string(), site.to_string(), command.to_string()];
+    cmd_args.extend(args);
+    (full_command, cmd_args)
+}
+
+/// Process handles by id. Listing and log reads only need the read side, so
+/// they don't queue behind each other while the reader threads append lines.
+type ProcessMap = Arc<RwLock<HashMap<String, ProcessHandle>>>;
+
 pub struct ProcessManager {
-    processes: Arc<Mutex<HashMap<String, ProcessHandle>>>,
-    log_buffer_size: usize,
+    processes: ProcessMap,
+    log_buffer_size: AtomicUsize,
+    log_capture_level: Mutex<LogLevel>,
+    collapse_repeated_lines: AtomicBool,
+    bench_executable: Mutex<String>,
+    output_style: Mutex<OutputStyle>,
+    /// By bench root and the executable that was asked.
+    bench_versions: Mutex<HashMap<(String, PathBuf), Option<BenchVersion>>>,
+    registry_dir: Arc<Mutex<Option<PathBuf>>>,
+    keepalive_restarts: AtomicUsize,
+    keepalive: KeepAliveMap,
+    scratch_files: ScratchFileMap,
+    migrate_outcomes: MigrateOutcomeMap,
+}
+
+/// How reader threads store a process's output, fixed when it's spawned.
+#[derive(Debug, Clone, Copy)]
+struct LogCapture {
+    buffer_size: usize,
+    min_level: LogLevel,
+    collapse_repeats: bool,
+}
+
+/// How to relaunch a process started with keepalive.
+#[derive(Debug, Clone)]
+struct KeepAlive {
+    program: PathBuf,
+    max_restarts: usize,
 }
 
+/// Keepalive policies by process id. An entry is removed once the process
+/// stops for good, which also cancels a restart waiting out its backoff.
+type KeepAliveMap = Arc<Mutex<HashMap<String, KeepAlive>>>;
+
+/// Temporary files a process reads while it runs, by process id. They are
+/// deleted once the process stops for good.
+type ScratchFileMap = Arc<Mutex<HashMap<String, PathBuf>>>;
+
+/// Migrate runs by process id. The outcome is filled in by the monitor
+/// thread when the process exits, so it's there however long migrate takes.
+type MigrateOutcomeMap = Arc<Mutex<HashMap<String, Option<MigrateOutcome>>>>;
+
 struct ProcessHandle {
     info: ProcessInfo,
     child: Option<Child>,
+    /// The display log, filtered by `log_capture_level` and with repeats
+    /// collapsed when `collapse_repeated_lines` is on.
     log_lines: Vec<LogLine>,
+    /// Every line in arrival order across stdout and stderr, for commands
+    /// that parse the output. Bounded by the same buffer size as the display
+    /// log but never filtered.
+    raw_lines: Vec<LogLine>,
+}
+
+impl ProcessHandle {
+    fn new(info: ProcessInfo) -> Self {
+        Self {
+            info,
+            child: None,
+            log_lines: Vec::new(),
+            raw_lines: Vec::new(),
+        }
+    }
+
+    /// `info` with its output and error lines taken from the raw log.
+    fn snapshot(&self) -> ProcessInfo {
+        let mut info = self.info.clone();
+        let (errors, output): (Vec<&LogLine>, Vec<&LogLine>) = self
+            .raw_lines
+            .iter()
+            .partition(|line| line.source == LogSource::Stderr);
+        info.output_lines = output.iter().map(|line| line.content.clone()).collect();
+        info.error_lines = errors.iter().map(|line| line.content.clone()).collect();
+        info
+    }
+
+    fn push_raw(&mut self, line: LogLine, buffer_size: usize) {
+        self.raw_lines.push(line);
+        if self.raw_lines.len() > buffer_size {
+            self.raw_lines.remove(0);
+        }
+    }
 }
 
 impl ProcessManager {
     pub fn new() -> Self {
         Self {
-            processes: Arc::new(Mutex::new(HashMap::new())),
-            log_buffer_size: 1000, // Keep last 1000 log lines per process
+            processes: Arc::new(RwLock::new(HashMap::new())),
+            log_buffer_size: AtomicUsize::new(1000), // Keep last 1000 log lines per process
+            log_capture_level: Mutex::new(LogLevel::Trace),
+            collapse_repeated_lines: AtomicBool::new(false),
+            bench_executable: Mutex::new("bench".to_string()),
+            output_style: Mutex::new(OutputStyle::default()),
+            bench_versions: Mutex::new(HashMap::new()),
+            registry_dir: Arc::new(Mutex::new(None)),
+            keepalive_restarts: AtomicUsize::new(3),
+            keepalive: Arc::new(Mutex::new(HashMap::new())),
+            scratch_files: Arc::new(Mutex::new(HashMap::new())),
+            migrate_outcomes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Applies user settings; only affects processes spawned afterwards.
+    pub fn configure(&self, settings: &LatteSettings) {
+        self.log_buffer_size
+            .store(settings.log_buffer_size, Ordering::SeqCst);
+        *self.log_capture_level.lock().unwrap() = settings.log_capture_level;
+        self.collapse_repeated_lines
+            .store(settings.collapse_repeated_lines, Ordering::SeqCst);
+        *self.bench_executable.lock().unwrap() = settings.bench_executable.clone();
+        *self.output_style.lock().unwrap() = settings.output_style;
+        self.keepalive_restarts
+            .store(settings.keepalive_restarts, Ordering::SeqCst);
+    }
+
+    fn log_capture(&self) -> LogCapture {
+        LogCapture {
+            buffer_size: self.log_buffer_size.load(Ordering::SeqCst),
+            min_level: *self.log_capture_level.lock().unwrap(),
+            collapse_repeats: self.collapse_repeated_lines.load(Ordering::SeqCst),
+        }
+    }
+
+    /// How many times a keepalive process is restarted before it's left failed.
+    pub fn keepalive_restarts(&self) -> usize {
+        self.keepalive_restarts.load(Ordering::SeqCst)
+    }
+
+    pub fn output_style(&self) -> OutputStyle {
+        *self.output_style.lock().unwrap()
+    }
+
+    pub fn bench_executable(&self) -> String {
+        self.bench_executable.lock().unwrap().clone()
+    }
+
+    /// Finds the configured bench executable: an explicit path (relative ones
+    /// are taken from the bench root), a `PATH` lookup, then the bench's own
+    /// virtualenv.
+    pub fn resolve_bench_executable(&self, bench_path: &str) -> Option<PathBuf> {
+        let executable = self.bench_executable();
+        let configured = Path::new(&executable);
+        if configured.components().count() > 1 {
+            let path = Path::new(bench_path).join(configured);
+            return path.is_file().then_some(path);
         }
+
+        std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .chain([venv_bin_dir(Path::new(bench_path))])
+            .map(|dir| dir.join(configured))
+            .flat_map(|path| {
+                // Windows executables need their .exe spelled out to be found
+                if cfg!(windows) && path.extension().is_none() {
+                    vec![path.with_extension(std::env::consts::EXE_EXTENSION), path]
+                } else {
+                    vec![path]
+                }
+            })
+            .find(|path| path.is_file())
+    }
+
+    /// Same lookup as `resolve_bench_executable`, with remediation text when
+    /// bench cannot be found.
+    pub fn ensure_bench_executable(&self, bench_path: &str) -> Result<PathBuf, String> {
+        self.resolve_bench_executable(bench_path).ok_or_else(|| {
+            format!(
+                "Could not find the bench executable '{}'.\n\
+                 Install it with `pip install frappe-bench` (or `pipx install frappe-bench`), \
+                 or set \"bench_executable\" in {} to the full path of your bench binary.",
+                self.bench_executable(),
+                crate::settings::SETTINGS_FILE
+            )
+        })
+    }
+
+    /// Runs `bench --version` once per bench path; later calls hit the cache.
+    pub fn detect_bench_version(&self, bench_path: &str) -> Option<String> {
+        self.bench_version(bench_path).map(|version| version.raw)
+    }
+
+    /// Asks the same executable `spawn_bench` would run, so venv installs and
+    /// the configured executable are versioned too.
+    pub fn bench_version(&self, bench_path: &str) -> Option<BenchVersion> {
+        let program = self.resolve_bench_executable(bench_path)?;
+        let mut versions = self.bench_versions.lock().unwrap();
+        versions
+            .entry((bench_path.to_string(), program.clone()))
+            .or_insert_with(|| {
+                let output = Command::new(&program)
+                    .arg("--version")
+                    .current_dir(bench_path)
+                    .output()
+                    .ok()?;
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                BenchVersion::parse(&stdout).or_else(|| BenchVersion::parse(&stderr))
+            })
+            .clone()
     }
 
     pub fn start_bench_process(
         &self,
         id: String,
+        initiator: &str,
+        bench_path: &str,
+        command: &str,
+        args: Vec<String>,
+    ) -> Result<String, String> {
+        let full_command = format!("bench {}", command);
+        let mut cmd_args = vec![command.to_string()];
+        cmd_args.extend(args);
+
+        self.spawn_bench(
+            id,
+            initiator,
+            bench_path,
+            full_command,
+            cmd_args,
+            StdioHook::Plain,
+        )
+    }
+
+    /// Like `start_bench_process`, but writes `input` to the process's stdin and
+    /// then closes it, so interactive prompts get answered instead of hanging.
+    pub fn start_bench_process_with_input(
+        &self,
+        id: String,
+        initiator: &str,
         bench_path: &str,
         command: &str,
         args: Vec<String>,
+        input: String,
     ) -> Result<String, String> {
         let full_command = format!("bench {}", command);
         let mut cmd_args = vec![command.to_string()];
-        cmd_args.extend(args.clone());
+        cmd_args.extend(args);
+
+        self.spawn_bench(
+            id,
+            initiator,
+            bench_path,
+            full_command,
+            cmd_args,
+            StdioHook::Input(input),
+        )
+    }
+
+    /// Runs a site-scoped bench command, i.e. `bench --site <site> <command> [args...]`.
+    pub fn start_site_command(
+        &self,
+        id: String,
+        initiator: &str,
+        bench_path: &str,
+        site: &str,
+        command: &str,
+        args: Vec<String>,
+    ) -> Result<String, String> {
+        let (full_command, cmd_args) = site_command_line(site, command, args);
+        self.spawn_bench(
+            id,
+            initiator,
+            bench_path,
+            full_command,
+            cmd_args,
+            StdioHook::Plain,
+        )
+    }
 
-        let mut child = Command::new("bench")
+    /// `start_site_command` that also hands each output line to `listener`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn start_site_command_with_listener(
+        &self,
+        id: String,
+        initiator: &str,
+        bench_path: &str,
+        site: &str,
+        command: &str,
+        args: Vec<String>,
+        listener: LineListener,
+    ) -> Result<String, String> {
+        let (full_command, cmd_args) = site_command_line(site, command, args);
+        self.spawn_bench(
+            id,
+            initiator,
+            bench_path,
+            full_command,
+            cmd_args,
+            StdioHook::Listener(listener),
+        )
+    }
+
+    /// `bench --site <site> <command>` with `input` written to stdin, then
+    /// closed.
+    pub fn start_site_command_with_input(
+        &self,
+        id: String,
+        initiator: &str,
+        bench_path: &str,
+        site: &str,
+        command: &str,
+        input: String,
+    ) -> Result<String, String> {
+        let (full_command, cmd_args) = site_command_line(site, command, Vec::new());
+        self.spawn_bench(
+            id,
+            initiator,
+            bench_path,
+            full_command,
+            cmd_args,
+            StdioHook::Input(input),
+        )
+    }
+
+    fn spawn_bench(
+        &self,
+        id: String,
+        initiator: &str,
+        bench_path: &str,
+        full_command: String,
+        cmd_args: Vec<String>,
+        hook: StdioHook,
+    ) -> Result<String, String> {
+        let program = self
+            .resolve_bench_executable(bench_path)
+            .unwrap_or_else(|| PathBuf::from(self.bench_executable()));
+        let mut command = Command::new(program);
+        if let StdioHook::Preload(script) = &hook {
+            command.env("PYTHONSTARTUP", script);
+        }
+        let mut child = command
             .args(&cmd_args)
             .current_dir(bench_path)
+            .stdin(if matches!(hook, StdioHook::Input(_)) {
+                Stdio::piped()
+            } else {
+                Stdio::inherit()
+            })
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
             .map_err(|e| format!("Failed to start bench process: {}", e))?;
 
-        let pid = child.id();
-
-        let process_info = ProcessInfo {
-            id: id.clone(),
-            command: full_command,
-            args: cmd_args,
-            working_dir: bench_path.to_string(),
-            status: ProcessStatus::Starting,
-            start_time: SystemTime::now(),
-            pid: Some(pid),
-            output_lines: Vec::new(),
-            error_lines: Vec::new(),
-        };
-
-        // Start monitoring threads before moving child into ProcessHandle
-        self.start_output_monitoring(&id, child);
-
-        // Store the process after monitoring is set up
-        let process_handle = ProcessHandle {
-            info: process_info,
-            child: None, // Child is consumed by start_output_monitoring; process_monitoring will check status
-            log_lines: Vec::new(),
+        let listener = match hook {
+            StdioHook::Input(input) => {
+                if let Some(mut stdin) = child.stdin.take() {
+                    // Dropping stdin afterwards sends EOF, so unanswered prompts
+                    // abort rather than block forever.
+                    let _ = stdin.write_all(input.as_bytes());
+                }
+                None
+            }
+            StdioHook::Listener(listener) => Some(listener),
+            StdioHook::Plain | StdioHook::Preload(_) => None,
         };
 
-        {
-            let mut processes = self.processes.lock().unwrap();
-            processes.insert(id.clone(), process_handle);
-        }
-
-        // Start process monitoring
-        self.start_process_monitoring(&id);
-
-        Ok(id)
+        let info = ProcessInfo::starting(id, initiator, full_command, cmd_args, bench_path);
+        self.track_process(info, child, listener)
     }
 
+    #[cfg(test)]
     pub fn start_simple_command(
         &self,
         id: String,
@@ -134,7 +584,7 @@ impl ProcessManager {
         command: &str,
         args: Vec<String>,
     ) -> Result<String, String> {
-        let mut child = Command::new(command)
+        let child = Command::new(command)
             .args(&args)
             .current_dir(working_dir)
             .stdout(Stdio::piped())
@@ -142,312 +592,603 @@ impl ProcessManager {
             .spawn()
             .map_err(|e| format!("Failed to start command: {}", e))?;
 
-        let pid = child.id();
-
-        let process_info = ProcessInfo {
-            id: id.clone(),
-            command: command.to_string(),
-            args,
-            working_dir: working_dir.to_string(),
-            status: ProcessStatus::Starting,
-            start_time: SystemTime::now(),
-            pid: Some(pid),
-            output_lines: Vec::new(),
-            error_lines: Vec::new(),
-        };
-
-        // Start monitoring threads before moving child into ProcessHandle
-        self.start_output_monitoring(&id, child);
+        let info = ProcessInfo::starting(id, "", command.to_string(), args, working_dir);
+        self.track_process(info, child, None)
+    }
 
-        // Store the process after monitoring is set up
-        let process_handle = ProcessHandle {
-            info: process_info,
-            child: None, // Child is consumed by start_output_monitoring
-            log_lines: Vec::new(),
-        };
+    fn track_process(
+        &self,
+        mut info: ProcessInfo,
+        child: Child,
+        listener: Option<LineListener>,
+    ) -> Result<String, String> {
+        let id = info.id.clone();
+        info.pid = Some(child.id());
 
+        // Register the process first so the monitoring threads can find its handle
         {
-            let mut processes = self.processes.lock().unwrap();
-            processes.insert(id.clone(), process_handle);
+            let mut processes = self.processes.write().unwrap();
+            processes.insert(id.clone(), ProcessHandle::new(info));
+            ProcessManager::persist_registry(&processes, &self.registry_dir);
         }
 
-        // Start process monitoring
+        self.start_output_monitoring(&id, child, listener);
         self.start_process_monitoring(&id);
 
         Ok(id)
     }
 
-    fn start_output_monitoring(&self, process_id: &str, child: Child) {
-        let processes_ref = Arc::clone(&self.processes);
-        let id = process_id.to_string();
-        let buffer_size = self.log_buffer_size;
+    fn start_output_monitoring(
+        &self,
+        process_id: &str,
+        child: Child,
+        listener: Option<LineListener>,
+    ) {
+        ProcessManager::attach_streams(
+            &self.processes,
+            process_id,
+            child,
+            self.log_capture(),
+            listener,
+        );
+    }
+
+    /// Stores `child` in its handle and starts a reader thread per output
+    /// stream. The child itself stays in the handle so process monitoring and
+    /// stop_process can reach it.
+    fn attach_streams(
+        processes: &ProcessMap,
+        process_id: &str,
+        mut child: Child,
+        capture: LogCapture,
+        listener: Option<LineListener>,
+    ) {
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
 
-        // Store the child in ProcessHandle
         {
-            let mut processes = processes_ref.lock().unwrap();
-            if let Some(handle) = processes.get_mut(&id) {
+            let mut proc_map = processes.write().unwrap();
+            if let Some(handle) = proc_map.get_mut(process_id) {
                 handle.child = Some(child);
             }
         }
 
-        // Clone Arc for stdout monitoring
-        let stdout_processes = Arc::clone(&processes_ref);
-        let stdout_id = id.clone();
-        thread::spawn(move || {
-            if let Some(mut child) = {
-                let mut processes = stdout_processes.lock().unwrap();
-                processes
-                    .get_mut(&stdout_id)
-                    .and_then(|handle| handle.child.take())
-            } {
+        if let Some(stdout) = stdout {
+            let stdout_processes = Arc::clone(processes);
+            let stdout_id = process_id.to_string();
+            let stdout_listener = listener.clone();
+            thread::spawn(move || {
                 ProcessManager::monitor_stream(
                     &stdout_processes,
                     &stdout_id,
                     LogSource::Stdout,
-                    &mut child,
-                    buffer_size,
+                    Box::new(BufReader::new(stdout)),
+                    capture,
+                    stdout_listener,
                 );
-                // Restore child
-                let mut processes = stdout_processes.lock().unwrap();
-                if let Some(handle) = processes.get_mut(&stdout_id) {
-                    handle.child = Some(child);
-                }
-            }
-        });
+            });
+        }
 
-        // Clone Arc for stderr monitoring
-        let stderr_processes = Arc::clone(&processes_ref);
-        let stderr_id = id.clone();
-        thread::spawn(move || {
-            if let Some(mut child) = {
-                let mut processes = stderr_processes.lock().unwrap();
-                processes
-                    .get_mut(&stderr_id)
-                    .and_then(|handle| handle.child.take())
-            } {
+        if let Some(stderr) = stderr {
+            let stderr_processes = Arc::clone(processes);
+            let stderr_id = process_id.to_string();
+            thread::spawn(move || {
                 ProcessManager::monitor_stream(
                     &stderr_processes,
                     &stderr_id,
                     LogSource::Stderr,
-                    &mut child,
-                    buffer_size,
+                    Box::new(BufReader::new(stderr)),
+                    capture,
+                    listener,
                 );
-                // Restore child
-                let mut processes = stderr_processes.lock().unwrap();
-                if let Some(handle) = processes.get_mut(&stderr_id) {
-                    handle.child = Some(child);
-                }
-            }
-        });
+            });
+        }
     }
 
     fn monitor_stream(
-        processes: &Arc<Mutex<HashMap<String, ProcessHandle>>>,
+        processes: &ProcessMap,
         process_id: &String,
         source: LogSource,
-        child: &mut Child,
-        buffer_size: usize,
+        mut reader: Box<dyn BufRead>,
+        capture: LogCapture,
+        listener: Option<LineListener>,
     ) {
-        let reader: Option<Box<dyn BufRead>> = match source {
-            LogSource::Stdout => {
-                if let Some(stdout) = child.stdout.take() {
-                    Some(Box::new(BufReader::new(stdout)) as Box<dyn BufRead>)
-                } else {
-                    None
-                }
-            }
-            LogSource::Stderr => {
-                if let Some(stderr) = child.stderr.take() {
-                    Some(Box::new(BufReader::new(stderr)) as Box<dyn BufRead>)
-                } else {
-                    None
-                }
-            }
-            LogSource::System => {
-                None // System logs not handled via child process
-            }
-        };
-
-        if let Some(mut reader) = reader {
-            let mut line = String::new();
-            loop {
-                match reader.read_line(&mut line) {
-                    Ok(0) => break, // EOF
-                    Ok(_) => {
-                        let log_line = LogLine {
-                            timestamp: SystemTime::now(),
-                            level: ProcessManager::detect_log_level(&line),
-                            content: line.trim_end().to_string(),
-                            source: source.clone(),
-                        };
-
-                        // Add to process logs
-                        let mut proc_map = processes.lock().unwrap();
+        let buffer_size = capture.buffer_size;
+        let mut line = String::new();
+        loop {
+            match reader.read_line(&mut line) {
+                Ok(0) => break, // EOF
+                Ok(_) => {
+                    let log_line = LogLine {
+                        timestamp: SystemTime::now(),
+                        level: ProcessManager::detect_log_level(&line),
+                        content: line.trim_end().to_string(),
+                        source: source.clone(),
+                        repeat_count: 1,
+                    };
+
+                    // Add to process logs
+                    {
+                        let mut proc_map = processes.write().unwrap();
                         if let Some(handle) = proc_map.get_mut(process_id) {
-                            handle.log_lines.push(log_line);
-
-                            // Also add to the info for quick access
-                            match source {
-                                LogSource::Stdout => {
-                                    handle.info.output_lines.push(line.trim_end().to_string());
-                                }
-                                LogSource::Stderr => {
-                                    handle.info.error_lines.push(line.trim_end().to_string());
-                                }
-                                LogSource::System => {
-                                    handle.info.output_lines.push(line.trim_end().to_string());
-                                }
+                            if log_line.level == LogLevel::Error {
+                                handle.info.error_count += 1;
+                            }
+                            handle.push_raw(log_line.clone(), buffer_size);
+
+                            // Only the display log is filtered or collapsed
+                            let repeated = handle.log_lines.last_mut().filter(|last| {
+                                capture.collapse_repeats
+                                    && last.content == log_line.content
+                                    && last.source == log_line.source
+                            });
+                            if let Some(last) = repeated {
+                                last.repeat_count += 1;
+                            } else if log_line.level >= capture.min_level {
+                                handle.log_lines.push(log_line);
                             }
 
-                            // Keep buffer size manageable
                             if handle.log_lines.len() > buffer_size {
                                 handle.log_lines.remove(0);
                             }
-                            if handle.info.output_lines.len() > buffer_size {
-                                handle.info.output_lines.remove(0);
-                            }
-                            if handle.info.error_lines.len() > buffer_size {
-                                handle.info.error_lines.remove(0);
-                            }
                         }
-                        line.clear();
                     }
-                    Err(_) => break,
+
+                    // Outside the lock, so listeners may query the manager
+                    if let Some(listener) = &listener {
+                        listener(line.trim_end());
+                    }
+                    line.clear();
                 }
+                Err(_) => break,
             }
         }
     }
 
     fn start_process_monitoring(&self, process_id: &str) {
         let processes_ref = Arc::clone(&self.processes);
+        let registry_dir = Arc::clone(&self.registry_dir);
+        let keepalive = Arc::clone(&self.keepalive);
+        let scratch_files = Arc::clone(&self.scratch_files);
+        let migrate_outcomes = Arc::clone(&self.migrate_outcomes);
+        let capture = self.log_capture();
         let id = process_id.to_string();
 
         thread::spawn(move || {
             loop {
                 thread::sleep(Duration::from_secs(1));
 
-                let should_continue = {
-                    let mut proc_map = processes_ref.lock().unwrap();
+                let (should_continue, crashed) = {
+                    let mut proc_map = processes_ref.write().unwrap();
                     if let Some(handle) = proc_map.get_mut(&id) {
                         if let Some(ref mut child) = handle.child {
                             match child.try_wait() {
                                 Ok(Some(status)) => {
+                                    // None when the process was killed by a signal
+                                    handle.info.exit_code = status.code();
                                     handle.info.status = if status.success() {
                                         ProcessStatus::Stopped
                                     } else {
                                         ProcessStatus::Failed
                                     };
                                     handle.child = None;
-                                    false // Stop monitoring
+                                    ProcessManager::record_migrate_outcome(
+                                        &migrate_outcomes,
+                                        handle,
+                                    );
+                                    ProcessManager::persist_registry(&proc_map, &registry_dir);
+                                    (false, !status.success()) // Stop monitoring
                                 }
                                 Ok(None) => {
                                     if handle.info.status == ProcessStatus::Starting {
                                         handle.info.status = ProcessStatus::Running;
                                     }
-                                    true // Continue monitoring
+                                    (true, false) // Continue monitoring
                                 }
                                 Err(_) => {
                                     handle.info.status = ProcessStatus::Failed;
                                     handle.child = None;
-                                    false // Stop monitoring
+                                    ProcessManager::record_migrate_outcome(
+                                        &migrate_outcomes,
+                                        handle,
+                                    );
+                                    ProcessManager::persist_registry(&proc_map, &registry_dir);
+                                    (false, false) // Stop monitoring
                                 }
                             }
                         } else {
-                            false // No child process, stop monitoring
+                            (false, false) // No child process, stop monitoring
                         }
                     } else {
-                        false // Process not found, stop monitoring
+                        (false, false) // Process not found, stop monitoring
                     }
                 };
 
-                if !should_continue {
-                    break;
+                if should_continue {
+                    continue;
+                }
+                if crashed
+                    && ProcessManager::restart_crashed(
+                        &processes_ref,
+                        &registry_dir,
+                        &keepalive,
+                        &id,
+                        capture,
+                    )
+                {
+                    continue;
+                }
+                keepalive.lock().unwrap().remove(&id);
+                if let Some(path) = scratch_files.lock().unwrap().remove(&id) {
+                    let _ = fs::remove_file(path);
                 }
+                break;
             }
         });
     }
 
-    pub fn stop_process(&self, process_id: &str) -> Result<(), String> {
-        let mut proc_map = self.processes.lock().unwrap();
-        if let Some(handle) = proc_map.get_mut(process_id) {
-            if let Some(ref mut child) = handle.child {
-                child
-                    .kill()
-                    .map_err(|e| format!("Failed to kill process: {}", e))?;
-                handle.info.status = ProcessStatus::Killed;
-                handle.child = None;
-                Ok(())
-            } else {
-                Err("Process is not running".to_string())
-            }
-        } else {
-            Err("Process not found".to_string())
+    /// Summarizes a migrate run as it exits. Called under the process map's
+    /// lock, so the outcome is ready by the time anyone sees the new status.
+    fn record_migrate_outcome(outcomes: &MigrateOutcomeMap, handle: &ProcessHandle) {
+        if let Some(slot) = outcomes.lock().unwrap().get_mut(&handle.info.id) {
+            let apps_path = Path::new(&handle.info.working_dir).join("apps");
+            let succeeded = handle.info.status == ProcessStatus::Stopped;
+            *slot = Some(MigrateOutcome::from_log(
+                &handle.raw_lines,
+                &apps_path,
+                succeeded,
+            ));
         }
     }
 
-    pub fn get_process_info(&self, process_id: &str) -> Option<ProcessInfo> {
-        let proc_map = self.processes.lock().unwrap();
-        proc_map.get(process_id).map(|handle| handle.info.clone())
-    }
-
-    pub fn get_process_logs(&self, process_id: &str) -> Vec<LogLine> {
-        let proc_map = self.processes.lock().unwrap();
-        proc_map
-            .get(process_id)
-            .map(|handle| handle.log_lines.clone())
-            .unwrap_or_default()
-    }
+    /// Relaunches a crashed keepalive process after a backoff that doubles
+    /// with each attempt (1s, 2s, 4s, ...). Returns false when the process has
+    /// no keepalive, has used up its restarts or couldn't be relaunched,
+    /// leaving it Failed.
+    fn restart_crashed(
+        processes: &ProcessMap,
+        registry_dir: &Arc<Mutex<Option<PathBuf>>>,
+        keepalive: &KeepAliveMap,
+        id: &str,
+        capture: LogCapture,
+    ) -> bool {
+        let buffer_size = capture.buffer_size;
+        let Some(policy) = keepalive.lock().unwrap().get(id).cloned() else {
+            return false;
+        };
 
-    pub fn get_recent_logs(&self, process_id: &str, count: usize) -> Vec<LogLine> {
-        let proc_map = self.processes.lock().unwrap();
-        if let Some(handle) = proc_map.get(process_id) {
-            let start = if handle.log_lines.len() > count {
-                handle.log_lines.len() - count
-            } else {
-                0
+        let (attempt, args, working_dir) = {
+            let mut proc_map = processes.write().unwrap();
+            let Some(handle) = proc_map.get_mut(id) else {
+                return false;
             };
-            handle.log_lines[start..].to_vec()
-        } else {
-            Vec::new()
+            let exit = handle
+                .info
+                .exit_code
+                .map(|code| format!("exit code {}", code))
+                .unwrap_or_else(|| "a signal".to_string());
+            if handle.info.restarts >= policy.max_restarts {
+                ProcessManager::push_system_line(
+                    handle,
+                    LogLevel::Error,
+                    format!(
+                        "Stopped by {}; giving up after {} restart(s)",
+                        exit, handle.info.restarts
+                    ),
+                    buffer_size,
+                );
+                return false;
+            }
+            handle.info.restarts += 1;
+            let attempt = handle.info.restarts;
+            ProcessManager::push_system_line(
+                handle,
+                LogLevel::Warning,
+                format!(
+                    "Stopped by {}; restarting in {}s (attempt {}/{})",
+                    exit,
+                    ProcessManager::restart_backoff(attempt).as_secs(),
+                    attempt,
+                    policy.max_restarts
+                ),
+                buffer_size,
+            );
+            (
+                attempt,
+                handle.info.args.clone(),
+                handle.info.working_dir.clone(),
+            )
+        };
+
+        thread::sleep(ProcessManager::restart_backoff(attempt));
+        // stop_process during the backoff withdraws the policy
+        if !keepalive.lock().unwrap().contains_key(id) {
+            return false;
         }
-    }
 
-    pub fn list_processes(&self) -> Vec<ProcessInfo> {
-        let proc_map = self.processes.lock().unwrap();
-        proc_map
-            .values()
-            .map(|handle| handle.info.clone())
-            .collect()
+        let spawned = Command::new(&policy.program)
+            .args(&args)
+            .current_dir(&working_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut proc_map = processes.write().unwrap();
+        let Some(handle) = proc_map.get_mut(id) else {
+            return false;
+        };
+        match spawned {
+            Ok(child) => {
+                handle.info.status = ProcessStatus::Running;
+                handle.info.pid = Some(child.id());
+                handle.info.exit_code = None;
+                ProcessManager::persist_registry(&proc_map, registry_dir);
+                drop(proc_map);
+                ProcessManager::attach_streams(processes, id, child, capture, None);
+                true
+            }
+            Err(e) => {
+                ProcessManager::push_system_line(
+                    handle,
+                    LogLevel::Error,
+                    format!("Restart failed: {}", e),
+                    buffer_size,
+                );
+                false
+            }
+        }
     }
 
-    pub fn list_running_processes(&self) -> Vec<ProcessInfo> {
-        let proc_map = self.processes.lock().unwrap();
-        proc_map
+    fn restart_backoff(attempt: usize) -> Duration {
+        Duration::from_secs(1 << (attempt.saturating_sub(1)).min(5))
+    }
+
+    /// Adds a line of Latte's own to the process log, e.g. a keepalive restart.
+    fn push_system_line(
+        handle: &mut ProcessHandle,
+        level: LogLevel,
+        content: String,
+        buffer_size: usize,
+    ) {
+        let line = LogLine {
+            timestamp: SystemTime::now(),
+            level,
+            content,
+            source: LogSource::System,
+            repeat_count: 1,
+        };
+        handle.push_raw(line.clone(), buffer_size);
+        handle.log_lines.push(line);
+        if handle.log_lines.len() > buffer_size {
+            handle.log_lines.remove(0);
+        }
+    }
+
+    pub fn stop_process(&self, process_id: &str) -> Result<(), String> {
+        let pending_restart = self.keepalive.lock().unwrap().remove(process_id).is_some();
+        let mut proc_map = self.processes.write().unwrap();
+        let handle = proc_map
+            .get_mut(process_id)
+            .ok_or("Process not found".to_string())?;
+
+        if let Some(ref mut child) = handle.child {
+            child
+                .kill()
+                .map_err(|e| format!("Failed to kill process: {}", e))?;
+        } else if let (ProcessStatus::Running, Some(pid)) = (&handle.info.status, handle.info.pid) {
+            // Adopted from the registry: we have a pid but no Child to kill
+            terminate_pid(pid)?;
+        } else if pending_restart && handle.info.status == ProcessStatus::Failed {
+            // Crashed and waiting out its keepalive backoff; removing the
+            // policy above cancels the restart
+        } else {
+            return Err("Process is not running".to_string());
+        }
+
+        handle.info.status = ProcessStatus::Killed;
+        handle.child = None;
+        ProcessManager::persist_registry(&proc_map, &self.registry_dir);
+        Ok(())
+    }
+
+    /// Reloads `<bench>/.latte_processes.json`, re-adopting processes that are
+    /// still alive as `Running` and pruning the rest. Only the first call for a
+    /// bench reads the file; after that the registry is kept up to date as
+    /// processes start and stop. Returns the ids that were adopted.
+    pub fn restore_registry(&self, bench_path: &str) -> Vec<String> {
+        let bench_dir = PathBuf::from(bench_path);
+        {
+            let mut registry_dir = self.registry_dir.lock().unwrap();
+            if registry_dir.as_ref() == Some(&bench_dir) {
+                return Vec::new();
+            }
+            *registry_dir = Some(bench_dir.clone());
+        }
+
+        let entries: Vec<RegistryEntry> = fs::read_to_string(bench_dir.join(REGISTRY_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let mut adopted = Vec::new();
+        {
+            let mut proc_map = self.processes.write().unwrap();
+            for entry in entries {
+                if proc_map.contains_key(&entry.process_id) || !is_pid_alive(entry.pid) {
+                    continue;
+                }
+
+                proc_map.insert(
+                    entry.process_id.clone(),
+                    ProcessHandle::new(ProcessInfo {
+                        id: entry.process_id.clone(),
+                        command: entry.command,
+                        args: Vec::new(),
+                        working_dir: entry.working_dir,
+                        status: ProcessStatus::Running,
+                        pid: Some(entry.pid),
+                        start_time: entry.start_time,
+                        exit_code: None,
+                        output_lines: Vec::new(),
+                        error_lines: Vec::new(),
+                        error_count: 0,
+                        initiator: entry.initiator,
+                        restarts: 0,
+                    }),
+                );
+                adopted.push(entry.process_id);
+            }
+            ProcessManager::persist_registry(&proc_map, &self.registry_dir);
+        }
+
+        for id in &adopted {
+            self.start_adopted_monitoring(id);
+        }
+
+        adopted
+    }
+
+    // Adopted processes aren't our children, so liveness is polled by pid
+    fn start_adopted_monitoring(&self, process_id: &str) {
+        let processes_ref = Arc::clone(&self.processes);
+        let registry_dir = Arc::clone(&self.registry_dir);
+        let id = process_id.to_string();
+
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_secs(1));
+
+            let mut proc_map = processes_ref.write().unwrap();
+            let Some(handle) = proc_map.get_mut(&id) else {
+                break;
+            };
+            if handle.info.status != ProcessStatus::Running {
+                break;
+            }
+            if !handle.info.pid.map(is_pid_alive).unwrap_or(false) {
+                handle.info.status = ProcessStatus::Stopped;
+                ProcessManager::persist_registry(&proc_map, &registry_dir);
+                break;
+            }
+        });
+    }
+
+    /// Rewrites the registry with the live processes started in the bench.
+    fn persist_registry(
+        processes: &HashMap<String, ProcessHandle>,
+        registry_dir: &Mutex<Option<PathBuf>>,
+    ) {
+        let Some(dir) = registry_dir.lock().unwrap().clone() else {
+            return;
+        };
+
+        let entries: Vec<RegistryEntry> = processes
             .values()
             .filter(|handle| {
                 matches!(
                     handle.info.status,
                     ProcessStatus::Starting | ProcessStatus::Running
-                )
+                ) && Path::new(&handle.info.working_dir) == dir
             })
-            .map(|handle| handle.info.clone())
-            .collect()
+            .filter_map(|handle| {
+                Some(RegistryEntry {
+                    process_id: handle.info.id.clone(),
+                    pid: handle.info.pid?,
+                    command: handle.info.command.clone(),
+                    working_dir: handle.info.working_dir.clone(),
+                    start_time: handle.info.start_time,
+                    initiator: handle.info.initiator.clone(),
+                })
+            })
+            .collect();
+
+        let path = dir.join(REGISTRY_FILE);
+        if entries.is_empty() {
+            let _ = fs::remove_file(path);
+        } else if let Ok(json) = serde_json::to_string_pretty(&entries) {
+            let _ = fs::write(path, json);
+        }
     }
 
-    pub fn cleanup_finished_processes(&self) {
-        let mut proc_map = self.processes.lock().unwrap();
-        proc_map.retain(|_, handle| {
-            !matches!(
-                handle.info.status,
-                ProcessStatus::Stopped | ProcessStatus::Failed | ProcessStatus::Killed
-            )
-        });
+    pub fn get_process_info(&self, process_id: &str) -> Option<ProcessInfo> {
+        let proc_map = self.processes.read().unwrap();
+        proc_map.get(process_id).map(ProcessHandle::snapshot)
+    }
+
+    /// Blocks until the process leaves the Starting/Running states or the timeout
+    /// elapses. Returns the final info if the process finished in time.
+    pub fn wait_for_exit(&self, process_id: &str, timeout: Duration) -> Option<ProcessInfo> {
+        let deadline = SystemTime::now() + timeout;
+        loop {
+            let info = self.get_process_info(process_id)?;
+            if !matches!(
+                info.status,
+                ProcessStatus::Starting | ProcessStatus::Running
+            ) {
+                return Some(info);
+            }
+            if SystemTime::now() >= deadline {
+                return None;
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+
+    pub fn get_process_logs(&self, process_id: &str) -> Vec<LogLine> {
+        let proc_map = self.processes.read().unwrap();
+        proc_map
+            .get(process_id)
+            .map(|handle| handle.log_lines.clone())
+            .unwrap_or_default()
+    }
+
+    /// Every line the process printed, in arrival order across stdout and
+    /// stderr, regardless of `log_capture_level` and repeat collapsing.
+    #[cfg(test)]
+    pub fn get_raw_logs(&self, process_id: &str) -> Vec<LogLine> {
+        let proc_map = self.processes.read().unwrap();
+        proc_map
+            .get(process_id)
+            .map(|handle| handle.raw_lines.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn list_processes(&self) -> Vec<ProcessInfo> {
+        let proc_map = self.processes.read().unwrap();
+        proc_map.values().map(ProcessHandle::snapshot).collect()
+    }
+
+    /// Processes in `status` (Running also covers Starting) whose command line
+    /// contains `command_contains`, ignoring case. `None` skips that filter.
+    pub fn list_processes_filtered(
+        &self,
+        status: Option<ProcessStatus>,
+        command_contains: Option<&str>,
+    ) -> Vec<ProcessInfo> {
+        let needle = command_contains.map(|s| s.to_lowercase());
+        let proc_map = self.processes.read().unwrap();
+        proc_map
+            .values()
+            .filter(|handle| match &status {
+                Some(ProcessStatus::Running) => matches!(
+                    handle.info.status,
+                    ProcessStatus::Starting | ProcessStatus::Running
+                ),
+                Some(status) => handle.info.status == *status,
+                None => true,
+            })
+            .filter(|handle| {
+                needle.as_ref().is_none_or(|needle| {
+                    handle.info.command.to_lowercase().contains(needle)
+                        || handle.info.id.to_lowercase().contains(needle)
+                })
+            })
+            .map(ProcessHandle::snapshot)
+            .collect()
     }
 
     pub fn stop_all_processes(&self) -> Result<Vec<String>, String> {
         let mut stopped_processes = Vec::new();
         let process_ids: Vec<String> = {
-            let proc_map = self.processes.lock().unwrap();
+            let proc_map = self.processes.read().unwrap();
             proc_map.keys().cloned().collect()
         };
 
@@ -460,19 +1201,25 @@ impl ProcessManager {
         Ok(stopped_processes)
     }
 
-    pub fn is_bench_running(&self) -> bool {
-        let proc_map = self.processes.lock().unwrap();
-        proc_map.values().any(|handle| {
-            handle.info.command.contains("bench start")
-                && matches!(
-                    handle.info.status,
-                    ProcessStatus::Starting | ProcessStatus::Running
-                )
-        })
+    /// Id of the live `bench start` for `bench_path`; starts for other
+    /// benches don't count.
+    pub fn bench_process_for(&self, bench_path: &str) -> Option<String> {
+        let proc_map = self.processes.read().unwrap();
+        proc_map
+            .values()
+            .find(|handle| {
+                handle.info.command.contains("bench start")
+                    && matches!(
+                        handle.info.status,
+                        ProcessStatus::Starting | ProcessStatus::Running
+                    )
+                    && Path::new(&handle.info.working_dir) == Path::new(bench_path)
+            })
+            .map(|handle| handle.info.id.clone())
     }
 
     pub fn get_bench_process_id(&self) -> Option<String> {
-        let proc_map = self.processes.lock().unwrap();
+        let proc_map = self.processes.read().unwrap();
         proc_map
             .values()
             .find(|handle| {
@@ -506,76 +1253,29 @@ impl ProcessManager {
         let mut output = String::new();
 
         for log in logs {
-            let level_icon = match log.level {
-                LogLevel::Error => "🔥",
-                LogLevel::Warning => "⚠️",
-                LogLevel::Info => "ℹ️",
-                LogLevel::Debug => "🐛",
-                LogLevel::Trace => "🔍",
-            };
-
-            let source_prefix = match log.source {
-                LogSource::Stdout => "",
-                LogSource::Stderr => "[ERR] ",
-                LogSource::System => "[SYS] ",
-            };
-
-            output.push_str(&format!(
-                "{} {}{}\n",
-                level_icon, source_prefix, log.content
-            ));
-        }
-
-        output
-    }
-
-    pub fn extract_clickable_errors(&self, process_id: &str) -> Vec<ClickableError> {
-        let logs = self.get_process_logs(process_id);
-        let mut errors = Vec::new();
-
-        for log in logs {
-            if log.level == LogLevel::Error {
-                if let Some(error) = self.parse_error_line(&log.content) {
-                    errors.push(error);
-                }
-            }
+            output.push_str(&format_log_line(&log));
+            output.push('\n');
         }
 
-        errors
+        self.output_style().apply(&output)
     }
 
-    fn parse_error_line(&self, line: &str) -> Option<ClickableError> {
-        // Pattern to match Python traceback file references
-        let file_pattern = Regex::new(r#"File "([^"]+)", line (\d+)"#).ok()?;
-
-        if let Some(captures) = file_pattern.captures(line) {
-            let file_path = captures.get(1)?.as_str().to_string();
-            let line_number = captures.get(2)?.as_str().parse::<u32>().ok()?;
-
-            return Some(ClickableError {
-                file_path,
-                line_number,
-                message: line.to_string(),
-                error_type: ErrorType::PythonTraceback,
-            });
-        }
-
-        // Pattern to match JavaScript/Node.js errors
-        let js_pattern = Regex::new(r"at ([^(]+) \(([^:]+):(\d+):(\d+)\)").ok()?;
-
-        if let Some(captures) = js_pattern.captures(line) {
-            let file_path = captures.get(2)?.as_str().to_string();
-            let line_number = captures.get(3)?.as_str().parse::<u32>().ok()?;
-
-            return Some(ClickableError {
-                file_path,
-                line_number,
-                message: line.to_string(),
-                error_type: ErrorType::JavaScriptError,
-            });
-        }
-
-        None
+    /// Buffered log lines of `process_id` whose content matches `pattern`,
+    /// in their original order.
+    pub fn search_logs(&self, process_id: &str, pattern: &str) -> Result<Vec<LogLine>, String> {
+        let regex =
+            Regex::new(pattern).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+        let proc_map = self.processes.read().unwrap();
+        let handle = proc_map
+            .get(process_id)
+            .ok_or(format!("Process {} not found", process_id))?;
+
+        Ok(handle
+            .log_lines
+            .iter()
+            .filter(|log| regex.is_match(&log.content))
+            .cloned()
+            .collect())
     }
 }
 
@@ -596,118 +1296,2349 @@ pub enum ErrorType {
     Generic,
 }
 
-impl Default for ProcessManager {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Patches seen in `bench migrate` output, grouped by outcome.
+#[derive(Debug, Clone, Default)]
+pub struct MigrateReport {
+    pub applied: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<String>,
+    pub errors: Vec<ClickableError>,
 }
 
-// Utility functions for bench-specific operations
-impl ProcessManager {
-    pub fn start_bench_dev_server(&self, bench_path: &str) -> Result<String, String> {
-        let process_id = format!("bench_start_{}", chrono::Utc::now().timestamp());
-        self.start_bench_process(process_id.clone(), bench_path, "start", vec![])
-    }
+impl MigrateReport {
+    /// `lines` is the combined stdout/stderr in arrival order. A patch still
+    /// running when the output ends counts as applied only if migrate exited
+    /// cleanly.
+    pub fn parse(lines: &[String], apps_path: &Path, succeeded: bool) -> Self {
+        let executing_re = Regex::new(r"^Executing (.+) in \S+(?: \(\S+\))?$").unwrap();
+        let location_re = Regex::new(r#"File "([^"]+)", line (\d+)"#).unwrap();
+        let exception_re = Regex::new(r"^[A-Za-z_][\w.]*(Error|Exception)\b").unwrap();
+
+        let mut report = MigrateReport::default();
+        let mut current: Option<String> = None;
+        let mut location: Option<(String, u32)> = None;
+        let mut exception: Option<String> = None;
+
+        let finish = |report: &mut MigrateReport,
+                      patch: String,
+                      skipped: bool,
+                      location: &mut Option<(String, u32)>,
+                      exception: &mut Option<String>| {
+            let (file_path, line_number) = location
+                .take()
+                .or_else(|| patch_file(&patch, apps_path).map(|path| (path, 1)))
+                .unwrap_or_default();
+            report.errors.push(ClickableError {
+                file_path,
+                line_number,
+                message: format!(
+                    "Patch {} failed: {}",
+                    patch,
+                    exception.take().unwrap_or_else(|| "see logs".to_string())
+                ),
+                error_type: ErrorType::Generic,
+            });
+            if skipped {
+                report.skipped.push(patch);
+            } else {
+                report.failed.push(patch);
+            }
+        };
 
-    pub fn run_bench_migrate(
-        &self,
-        bench_path: &str,
-        site: Option<&str>,
-    ) -> Result<String, String> {
-        let process_id = format!("bench_migrate_{}", chrono::Utc::now().timestamp());
-        let mut args = vec![];
+        for line in lines {
+            let trimmed = line.trim();
+            if let Some(captures) = executing_re.captures(trimmed) {
+                // Older frappe prints nothing after a successful patch
+                if let Some(patch) = current.take() {
+                    report.applied.push(patch);
+                }
+                current = Some(captures[1].to_string());
+                location = None;
+                exception = None;
+            } else if trimmed.starts_with("Success:") {
+                if let Some(patch) = current.take() {
+                    report.applied.push(patch);
+                }
+            } else if trimmed.starts_with("Failed to execute patch") {
+                if let Some(patch) = current.take() {
+                    finish(&mut report, patch, true, &mut location, &mut exception);
+                }
+            } else if trimmed.ends_with(": failed: STOPPED") {
+                let patch = current
+                    .take()
+                    .unwrap_or_else(|| trimmed.trim_end_matches(": failed: STOPPED").to_string());
+                finish(&mut report, patch, false, &mut location, &mut exception);
+            } else if current.is_some() {
+                if let Some(captures) = location_re.captures(trimmed) {
+                    // Keep the innermost frame that belongs to a patch
+                    if captures[1].contains("/patches/") {
+                        let line_number = captures[2].parse().unwrap_or(1);
+                        location = Some((captures[1].to_string(), line_number));
+                    }
+                } else if exception_re.is_match(trimmed) {
+                    exception = Some(trimmed.to_string());
+                }
+            }
+        }
 
-        if let Some(site_name) = site {
-            args.extend(vec!["--site".to_string(), site_name.to_string()]);
+        if let Some(patch) = current {
+            if succeeded {
+                report.applied.push(patch);
+            } else {
+                finish(&mut report, patch, false, &mut location, &mut exception);
+            }
         }
 
-        args.push("migrate".to_string());
-        self.start_bench_process(process_id.clone(), bench_path, "migrate", args)
+        report
     }
+}
 
-    pub fn run_bench_build(&self, bench_path: &str) -> Result<String, String> {
-        let process_id = format!("bench_build_{}", chrono::Utc::now().timestamp());
-        self.start_bench_process(process_id.clone(), bench_path, "build", vec![])
+/// Total of the "indexed N documents" lines a search reindex prints, or None
+/// when it printed none (older frappe versions stay quiet).
+pub fn indexed_document_count(lines: &[String]) -> Option<u64> {
+    let count_re = Regex::new(r"(?i)\bindexed\s+(\d+)\s+documents?\b").ok()?;
+    lines
+        .iter()
+        .filter_map(|line| {
+            count_re
+                .captures(line)?
+                .get(1)?
+                .as_str()
+                .parse::<u64>()
+                .ok()
+        })
+        .reduce(|total, count| total + count)
+}
+
+/// The stages `bench migrate` works through for each site, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MigratePhaseKind {
+    PreModelSync,
+    SyncDocTypes,
+    Patches,
+    SyncFixtures,
+    SearchIndex,
+}
+
+impl MigratePhaseKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MigratePhaseKind::PreModelSync => "Pre-model-sync patches",
+            MigratePhaseKind::SyncDocTypes => "Syncing DocTypes",
+            MigratePhaseKind::Patches => "Post-model-sync patches",
+            MigratePhaseKind::SyncFixtures => "Syncing fixtures, jobs and dashboards",
+            MigratePhaseKind::SearchIndex => "Building search index",
+        }
     }
+}
 
-    pub fn create_new_app(&self, bench_path: &str, app_name: &str) -> Result<String, String> {
-        let process_id = format!("bench_new_app_{}", chrono::Utc::now().timestamp());
-        self.start_bench_process(
-            process_id.clone(),
-            bench_path,
-            "new-app",
-            vec![app_name.to_string()],
-        )
+/// A stretch of `bench migrate` output belonging to one phase of one site.
+#[derive(Debug, Clone)]
+pub struct MigratePhase {
+    pub site: Option<String>,
+    pub kind: MigratePhaseKind,
+    pub duration: Duration,
+    pub succeeded: bool,
+}
+
+/// Splits a finished migrate's log into phases, timed from the first line of
+/// each phase to the first line of the next. A phase fails when a patch in it
+/// stopped the run, and the last phase fails when migrate didn't exit
+/// cleanly, so the summary points at the step that broke.
+pub fn migrate_phases(logs: &[LogLine], succeeded: bool) -> Vec<MigratePhase> {
+    let executing_re = Regex::new(r"^Executing (.+) in \S+(?: \(\S+\))?$").unwrap();
+
+    let mut phases = Vec::new();
+    let mut current: Option<(MigratePhase, SystemTime)> = None;
+    let mut site: Option<String> = None;
+    let mut last_seen = None;
+
+    let close = |phases: &mut Vec<MigratePhase>,
+                 current: Option<(MigratePhase, SystemTime)>,
+                 end: SystemTime| {
+        if let Some((mut phase, started)) = current {
+            phase.duration = end.duration_since(started).unwrap_or_default();
+            phases.push(phase);
+        }
+    };
+
+    for log in logs {
+        let line = log.content.trim();
+        let running = current.as_ref().map(|(phase, _)| phase.kind);
+        let (kind, new_site) = if let Some(name) = line.strip_prefix("Migrating ") {
+            site = Some(name.trim().to_string());
+            (Some(MigratePhaseKind::PreModelSync), true)
+        } else if line.starts_with("Updating DocTypes for") {
+            (Some(MigratePhaseKind::SyncDocTypes), false)
+        } else if executing_re.is_match(line) {
+            // Patches run both before and after the DocType sync
+            match running {
+                Some(kind) if kind >= MigratePhaseKind::SyncDocTypes => {
+                    (Some(MigratePhaseKind::Patches), false)
+                }
+                _ => (Some(MigratePhaseKind::PreModelSync), false),
+            }
+        } else if line.starts_with("Syncing ") {
+            (Some(MigratePhaseKind::SyncFixtures), false)
+        } else if line.contains("search index") {
+            (Some(MigratePhaseKind::SearchIndex), false)
+        } else {
+            (None, false)
+        };
+
+        let kind = match (kind, running) {
+            (None, None) => Some(MigratePhaseKind::PreModelSync),
+            (Some(kind), Some(running)) if kind == running && !new_site => None,
+            (kind, _) => kind,
+        };
+        if let Some(kind) = kind {
+            close(&mut phases, current.take(), log.timestamp);
+            current = Some((
+                MigratePhase {
+                    site: site.clone(),
+                    kind,
+                    duration: Duration::ZERO,
+                    succeeded: true,
+                },
+                log.timestamp,
+            ));
+        }
+        if line.ends_with(": failed: STOPPED") {
+            if let Some((phase, _)) = current.as_mut() {
+                phase.succeeded = false;
+            }
+        }
+        last_seen = Some(log.timestamp);
     }
 
-    pub fn create_new_site(&self, bench_path: &str, site_name: &str) -> Result<String, String> {
-        let process_id = format!("bench_new_site_{}", chrono::Utc::now().timestamp());
-        self.start_bench_process(
-            process_id.clone(),
-            bench_path,
-            "new-site",
-            vec![site_name.to_string()],
-        )
+    if let Some(end) = last_seen {
+        close(&mut phases, current, end);
+    }
+    if !succeeded {
+        if let Some(last) = phases.last_mut() {
+            last.succeeded = false;
+        }
     }
+    phases
+}
 
-    pub fn open_console(&self, bench_path: &str, site: &str) -> Result<String, String> {
-        let process_id = format!("bench_console_{}", chrono::Utc::now().timestamp());
-        self.start_bench_process(
-            process_id.clone(),
-            bench_path,
-            "console",
-            vec!["--site".to_string(), site.to_string()],
-        )
+/// A finished `bench migrate`, summarized from its raw log as it exits.
+#[derive(Debug, Clone)]
+pub struct MigrateOutcome {
+    pub succeeded: bool,
+    pub report: MigrateReport,
+    pub phases: Vec<MigratePhase>,
+    /// The last thing migrate printed, preferring stderr, for failures that
+    /// left no traceback.
+    pub last_line: Option<String>,
+}
+
+impl MigrateOutcome {
+    pub fn from_log(raw: &[LogLine], apps_path: &Path, succeeded: bool) -> Self {
+        let lines: Vec<String> = raw.iter().map(|line| line.content.clone()).collect();
+        let last_line = raw
+            .iter()
+            .rev()
+            .find(|line| line.source == LogSource::Stderr)
+            .or(raw.last())
+            .map(|line| line.content.clone());
+        MigrateOutcome {
+            succeeded,
+            report: MigrateReport::parse(&lines, apps_path, succeeded),
+            phases: migrate_phases(raw, succeeded),
+            last_line,
+        }
     }
+}
 
-    pub fn open_mariadb(&self, bench_path: &str, site: &str) -> Result<String, String> {
-        let process_id = format!("bench_mariadb_{}", chrono::Utc::now().timestamp());
-        self.start_bench_process(
-            process_id.clone(),
-            bench_path,
-            "mariadb",
-            vec!["--site".to_string(), site.to_string()],
-        )
+/// `app.patches.v1_0.fix` -> `<apps>/app/app/patches/v1_0/fix.py`; `execute:`
+/// patches have no file.
+fn patch_file(patch: &str, apps_path: &Path) -> Option<String> {
+    let module = patch.split('#').next()?.trim();
+    if module.is_empty() || module.starts_with("execute:") {
+        return None;
+    }
+    let app = module.split('.').next()?;
+    let mut path = apps_path.join(app);
+    for part in module.split('.') {
+        path.push(part);
     }
+    path.set_extension("py");
+    Some(path.to_string_lossy().to_string())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::time::Duration;
+/// Answers for the prompts `bench new-app` asks interactively.
+#[derive(Debug, Clone, Default)]
+pub struct NewAppMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub publisher: Option<String>,
+    pub email: Option<String>,
+}
 
-    #[test]
-    fn test_process_manager_creation() {
-        let manager = ProcessManager::new();
-        assert_eq!(manager.list_processes().len(), 0);
+impl NewAppMetadata {
+    pub fn default_title(app_name: &str) -> String {
+        app_name
+            .split('_')
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let mut chars = part.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                    None => String::new(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 
-    #[test]
-    fn test_detect_log_level() {
-        assert_eq!(
-            ProcessManager::detect_log_level("ERROR: Something went wrong"),
-            LogLevel::Error
-        );
-        assert_eq!(
-            ProcessManager::detect_log_level("Warning: This is a warning"),
-            LogLevel::Warning
-        );
-        assert_eq!(
-            ProcessManager::detect_log_level("Info: Normal message"),
-            LogLevel::Info
-        );
+    /// bench aborts at an empty publisher or email prompt, so blank ones are
+    /// taken from `git config` in `dir`, or placeholders without it.
+    pub fn fill_defaults(&mut self, dir: &Path) {
+        let blank = |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+        if blank(&self.publisher) {
+            self.publisher =
+                Some(git_config(dir, "user.name").unwrap_or_else(|| "Administrator".to_string()));
+        }
+        if blank(&self.email) {
+            self.email = Some(
+                git_config(dir, "user.email").unwrap_or_else(|| "admin@example.com".to_string()),
+            );
+        }
     }
 
-    #[test]
-    fn test_parse_error_line() {
-        let manager = ProcessManager::new();
-        let line = r#"File "/path/to/file.py", line 42"#;
-
-        let error = manager.parse_error_line(line);
-        assert!(error.is_some());
+    /// One line per prompt, in the order bench asks them: title, description,
+    /// publisher, email, then (with `extra_prompts`, see
+    /// `BenchVersion::supports_new_app_prompts`) license, GitHub workflow and
+    /// branch which keep bench's defaults.
+    pub fn prompt_answers(&self, app_name: &str, extra_prompts: bool) -> String {
+        let title = self
+            .title
+            .clone()
+            .unwrap_or_else(|| Self::default_title(app_name));
+        let description = self.description.clone().unwrap_or_else(|| title.clone());
+
+        let mut answers = vec![
+            title,
+            description,
+            self.publisher.clone().unwrap_or_default(),
+            self.email.clone().unwrap_or_default(),
+        ];
+        if extra_prompts {
+            answers.extend([String::new(), String::new(), String::new()]);
+        }
+        answers.join("\n") + "\n"
+    }
+}
 
-        let error = error.unwrap();
-        assert_eq!(error.file_path, "/path/to/file.py");
-        assert_eq!(error.line_number, 42);
+pub fn format_log_line(log: &LogLine) -> String {
+    let level_icon = match log.level {
+        LogLevel::Error => "🔥",
+        LogLevel::Warning => "⚠️",
+        LogLevel::Info => "ℹ️",
+        LogLevel::Debug => "🐛",
+        LogLevel::Trace => "🔍",
+    };
+
+    let source_prefix = match log.source {
+        LogSource::Stdout => "",
+        LogSource::Stderr => "[ERR] ",
+        LogSource::System => "[SYS] ",
+    };
+
+    if log.repeat_count > 1 {
+        format!(
+            "{} {}{} … (x{})",
+            level_icon, source_prefix, log.content, log.repeat_count
+        )
+    } else {
+        format!("{} {}{}", level_icon, source_prefix, log.content)
+    }
+}
+
+#[cfg(unix)]
+fn is_pid_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists and can be signalled
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn is_pid_alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_pid_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Where a bench's virtualenv keeps its executables.
+pub fn venv_bin_dir(bench_path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        bench_path.join("env").join("Scripts")
+    } else {
+        bench_path.join("env").join("bin")
+    }
+}
+
+fn connect(host: &str, port: u16, timeout: Duration) -> Result<TcpStream, String> {
+    let addr = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("cannot resolve {}: {}", host, e))?
+        .next()
+        .ok_or(format!("cannot resolve {}", host))?;
+    TcpStream::connect_timeout(&addr, timeout).map_err(|e| e.to_string())
+}
+
+/// How long a TCP connection to `host:port` took to open.
+pub fn check_tcp(host: &str, port: u16, timeout: Duration) -> Result<Duration, String> {
+    let started = Instant::now();
+    connect(host, port, timeout)?;
+    Ok(started.elapsed())
+}
+
+/// Round trip of `GET /api/method/ping` against the web server, sent with
+/// `site` as the Host so multi-site benches route it. Fails unless the reply
+/// is a 200 containing "pong".
+pub fn http_ping(host: &str, port: u16, site: &str, timeout: Duration) -> Result<Duration, String> {
+    let started = Instant::now();
+    let mut stream = connect(host, port, timeout)?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .map_err(|e| e.to_string())?;
+    let request = format!(
+        "GET /api/method/ping HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        site
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let mut response = String::new();
+    BufReader::new(stream)
+        .take(64 * 1024)
+        .read_to_string(&mut response)
+        .map_err(|e| e.to_string())?;
+    let status = response.lines().next().unwrap_or("").trim();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(if status.is_empty() {
+            "empty response".to_string()
+        } else {
+            status.to_string()
+        });
+    }
+    if !response.contains("pong") {
+        return Err("response did not contain pong".to_string());
+    }
+    Ok(started.elapsed())
+}
+
+/// PID of the process listening on TCP `port`, whether or not Latte started
+/// it. Uses `/proc` on Linux and falls back to `lsof`, or `netstat` on Windows.
+pub fn find_pid_by_port(port: u16) -> Option<u32> {
+    let inodes: Vec<u64> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| listening_inodes(&content, port))
+        .collect();
+
+    if !inodes.is_empty() {
+        let sockets: Vec<String> = inodes
+            .iter()
+            .map(|inode| format!("socket:[{}]", inode))
+            .collect();
+        let owner = fs::read_dir("/proc")
+            .ok()?
+            .filter_map(|e| e.ok())
+            .find_map(|entry| {
+                let pid: u32 = entry.file_name().to_string_lossy().parse().ok()?;
+                let holds_socket = fs::read_dir(entry.path().join("fd"))
+                    .ok()?
+                    .filter_map(|fd| fd.ok())
+                    .filter_map(|fd| fs::read_link(fd.path()).ok())
+                    .any(|target| sockets.contains(&target.to_string_lossy().to_string()));
+                holds_socket.then_some(pid)
+            });
+        if owner.is_some() {
+            return owner;
+        }
+    }
+
+    listener_pid_from_tool(port)
+}
+
+#[cfg(not(windows))]
+fn listener_pid_from_tool(port: u16) -> Option<u32> {
+    let output = Command::new("lsof")
+        .args(["-t", "-sTCP:LISTEN", "-i", &format!("tcp:{}", port)])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().parse().ok())
+}
+
+#[cfg(windows)]
+fn listener_pid_from_tool(port: u16) -> Option<u32> {
+    let output = Command::new("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .output()
+        .ok()?;
+    netstat_listening_pid(&String::from_utf8_lossy(&output.stdout), port)
+}
+
+/// PID in `netstat -ano` output that is listening on `port`, e.g.
+/// `  TCP    0.0.0.0:8000    0.0.0.0:0    LISTENING    4242`.
+#[cfg(any(windows, test))]
+fn netstat_listening_pid(output: &str, port: u16) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        let [protocol, local, _, state, pid] = columns.as_slice() else {
+            return None;
+        };
+        let local_port: u16 = local.rsplit(':').next()?.parse().ok()?;
+        (protocol.eq_ignore_ascii_case("TCP") && *state == "LISTENING" && local_port == port)
+            .then(|| pid.parse().ok())
+            .flatten()
+    })
+}
+
+/// Socket inodes in a `/proc/net/tcp` table that are listening on `port`.
+fn listening_inodes(table: &str, port: u16) -> Vec<u64> {
+    const TCP_LISTEN: &str = "0A";
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let local_port = columns.get(1)?.rsplit(':').next()?;
+            let matches = u16::from_str_radix(local_port, 16).ok()? == port
+                && columns.get(3) == Some(&TCP_LISTEN);
+            matches.then(|| columns.get(9)?.parse().ok()).flatten()
+        })
+        .collect()
+}
+
+/// Full command line of `pid`, for showing what is about to be killed.
+pub fn process_command_line(pid: u32) -> Option<String> {
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid))
+        .ok()
+        .map(|bytes| {
+            bytes
+                .split(|b| *b == 0)
+                .filter(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|line| !line.is_empty());
+    cmdline.or_else(|| listed_command_line(pid))
+}
+
+#[cfg(not(windows))]
+fn listed_command_line(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", "args=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!line.is_empty()).then_some(line)
+}
+
+/// tasklist only knows the image name, e.g. `python.exe`.
+#[cfg(windows)]
+fn listed_command_line(pid: u32) -> Option<String> {
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let image = stdout.lines().next()?.split(',').next()?.trim_matches('"');
+    (!image.is_empty() && !image.starts_with("INFO:")).then(|| image.to_string())
+}
+
+#[cfg(unix)]
+pub fn terminate_pid(pid: u32) -> Result<(), String> {
+    let status = Command::new("kill")
+        .arg(pid.to_string())
+        .status()
+        .map_err(|e| format!("Failed to kill process: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Failed to kill process {}", pid))
+    }
+}
+
+#[cfg(windows)]
+pub fn terminate_pid(pid: u32) -> Result<(), String> {
+    // /T takes the child processes bench spawns down with it
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .status()
+        .map_err(|e| format!("Failed to kill process: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Failed to kill process {}", pid))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn terminate_pid(pid: u32) -> Result<(), String> {
+    Err(format!(
+        "Cannot stop adopted process {} on this platform",
+        pid
+    ))
+}
+
+/// Branch and uncommitted file count of a checked-out app.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitStatus {
+    pub branch: String,
+    pub dirty_files: usize,
+}
+
+/// `None` when `path` isn't the root of a git checkout (or git is missing).
+pub fn git_status(path: &Path) -> Option<GitStatus> {
+    if !path.join(".git").exists() {
+        return None;
+    }
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    let porcelain = git(&["status", "--porcelain"])?;
+    // rev-parse fails before the first commit, symbolic-ref still knows the branch
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"])
+        .or_else(|| git(&["symbolic-ref", "--short", "HEAD"]))
+        .map(|branch| branch.trim().to_string())
+        .map(|branch| {
+            if branch == "HEAD" {
+                "(detached)".to_string()
+            } else {
+                branch
+            }
+        })?;
+
+    Some(GitStatus {
+        branch,
+        dirty_files: porcelain.lines().filter(|l| !l.trim().is_empty()).count(),
+    })
+}
+
+/// A `git config` value as seen from `dir`; None when unset or git is missing.
+pub fn git_config(dir: &Path, key: &str) -> Option<String> {
+    Command::new("git")
+        .args(["config", "--get", key])
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Contents of `file` as committed at HEAD of the repository containing it.
+pub fn git_show_head(file: &Path) -> Result<String, String> {
+    let (Some(dir), Some(name)) = (file.parent(), file.file_name()) else {
+        return Err(format!("Invalid path: {}", file.display()));
+    };
+    let output = Command::new("git")
+        .arg("show")
+        .arg(format!("HEAD:./{}", name.to_string_lossy()))
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("git is not available: {}", e))?;
+
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("not a git repository") {
+        Err(format!("{} is not inside a git repository", file.display()))
+    } else if stderr.contains("does not exist in 'HEAD'")
+        || stderr.contains("exists on disk, but not in 'HEAD'")
+    {
+        Err(format!("{} is not committed yet", file.display()))
+    } else if stderr.contains("invalid object name 'HEAD'") {
+        Err("The repository has no commits yet".to_string())
+    } else {
+        Err(format!("git show failed: {}", stderr.trim()))
+    }
+}
+
+/// The first statement in `sql` that would drop, truncate or wipe a table
+/// (`DELETE` without `WHERE`), if any.
+pub fn destructive_statement(sql: &str) -> Option<String> {
+    sql_statements(sql)
+        .into_iter()
+        .find(|(_, code)| {
+            // Splitting on anything but word characters also drops the
+            // parentheses around `(DELETE ...)`
+            let upper = code.to_uppercase();
+            let words: Vec<&str> = upper
+                .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .filter(|word| !word.is_empty())
+                .collect();
+            match words.first().copied() {
+                Some("DROP") | Some("TRUNCATE") => true,
+                Some("DELETE") => !words.contains(&"WHERE"),
+                _ => false,
+            }
+        })
+        .map(|(text, _)| text.trim().to_string())
+}
+
+/// Splits `sql` on the semicolons outside quotes and comments. Each statement
+/// comes with a copy that has its comments and quoted text blanked out.
+fn sql_statements(sql: &str) -> Vec<(String, String)> {
+    let mut statements = Vec::new();
+    let (mut text, mut code) = (String::new(), String::new());
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                statements.push((std::mem::take(&mut text), std::mem::take(&mut code)));
+            }
+            '\'' | '"' | '`' => {
+                text.push(c);
+                code.push(' ');
+                while let Some(inner) = chars.next() {
+                    text.push(inner);
+                    if inner == '\\' && c != '`' {
+                        text.extend(chars.next());
+                    } else if inner == c {
+                        break;
+                    }
+                }
+            }
+            '#' | '-' if c == '#' || chars.peek() == Some(&'-') => {
+                text.push(c);
+                code.push(' ');
+                for inner in chars.by_ref() {
+                    text.push(inner);
+                    if inner == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                text.push(c);
+                text.extend(chars.next());
+                code.push(' ');
+                let mut previous = ' ';
+                for inner in chars.by_ref() {
+                    text.push(inner);
+                    if previous == '*' && inner == '/' {
+                        break;
+                    }
+                    previous = inner;
+                }
+            }
+            _ => {
+                text.push(c);
+                code.push(c);
+            }
+        }
+    }
+    statements.push((text, code));
+    statements
+}
+
+/// Bench subcommands that delete sites or apps; `frappe-bench` only runs
+/// them with `--force`.
+const DESTRUCTIVE_BENCH_COMMANDS: &[&str] = &[
+    "drop-site",
+    "destroy",
+    "reinstall",
+    "uninstall-app",
+    "remove-app",
+];
+
+/// Checks a free-form bench subcommand before it's passed through.
+pub fn check_bench_subcommand(subcommand: &str, force: bool) -> Result<(), String> {
+    let valid = subcommand
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if subcommand.is_empty() || subcommand.starts_with('-') || !valid {
+        return Err(format!("Invalid bench subcommand: {}", subcommand));
+    }
+    if DESTRUCTIVE_BENCH_COMMANDS.contains(&subcommand) && !force {
+        return Err(format!(
+            "Refusing to run destructive command: bench {}\nAdd --force to run it anyway.",
+            subcommand
+        ));
+    }
+    Ok(())
+}
+
+/// The final `SomeError: message` line of a Python traceback in `lines`, as
+/// printed by both plain Python and IPython.
+pub fn python_exception(lines: &[String]) -> Option<String> {
+    let traceback = lines
+        .iter()
+        .position(|line| line.contains("Traceback (most recent call last)"))?;
+    let exception =
+        Regex::new(r"^([A-Za-z_][\w.]*(?:Error|Exception|Exit|Interrupt|Warning))(?::\s*(.*))?$")
+            .unwrap();
+
+    lines[traceback..]
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .find(|line| exception.is_match(line))
+        .map(|line| line.to_string())
+}
+
+impl Default for ProcessManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Utility functions for bench-specific operations
+impl ProcessManager {
+    /// Refuses to start a second `bench start` for the same bench, since both
+    /// would fight over its ports, unless `force` is set.
+    /// With `keepalive`, a crash (non-zero exit) restarts the server up to
+    /// the configured `keepalive_restarts` times.
+    pub fn start_bench_dev_server(
+        &self,
+        bench_path: &str,
+        force: bool,
+        keepalive: bool,
+        initiator: &str,
+    ) -> Result<String, String> {
+        if !force {
+            if let Some(id) = self.bench_process_for(bench_path) {
+                return Err(format!(
+                    "bench start is already running for {} (Process ID: {})",
+                    bench_path, id
+                ));
+            }
+        }
+        let process_id = format!("bench_start_{}", chrono::Utc::now().timestamp());
+        if keepalive {
+            let program = self
+                .resolve_bench_executable(bench_path)
+                .unwrap_or_else(|| PathBuf::from(self.bench_executable()));
+            self.keepalive.lock().unwrap().insert(
+                process_id.clone(),
+                KeepAlive {
+                    program,
+                    max_restarts: self.keepalive_restarts(),
+                },
+            );
+        }
+        let started =
+            self.start_bench_process(process_id.clone(), initiator, bench_path, "start", vec![]);
+        if started.is_err() {
+            self.keepalive.lock().unwrap().remove(&process_id);
+        }
+        started
+    }
+
+    pub fn run_bench_migrate(
+        &self,
+        bench_path: &str,
+        site: Option<&str>,
+        skip_failing: bool,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_migrate_{}", chrono::Utc::now().timestamp());
+        let mut args = vec![];
+        if skip_failing {
+            args.push("--skip-failing".to_string());
+        }
+
+        // Registered first so even an instant exit gets its outcome recorded
+        self.migrate_outcomes
+            .lock()
+            .unwrap()
+            .insert(process_id.clone(), None);
+        // --site belongs before the subcommand: `bench --site <site> migrate`
+        let started = match site {
+            Some(site_name) => self.start_site_command(
+                process_id.clone(),
+                initiator,
+                bench_path,
+                site_name,
+                "migrate",
+                args,
+            ),
+            None => {
+                self.start_bench_process(process_id.clone(), initiator, bench_path, "migrate", args)
+            }
+        };
+        if started.is_err() {
+            self.migrate_outcomes.lock().unwrap().remove(&process_id);
+        }
+        started
+    }
+
+    /// The summary of a finished migrate run; None while it's still running
+    /// or when `process_id` isn't a migrate.
+    pub fn migrate_outcome(&self, process_id: &str) -> Option<MigrateOutcome> {
+        self.migrate_outcomes
+            .lock()
+            .unwrap()
+            .get(process_id)
+            .cloned()
+            .flatten()
+    }
+
+    /// The most recently started migrate run, finished or not.
+    pub fn latest_migrate(&self) -> Option<String> {
+        // Released before taking the process map; the monitor locks them the
+        // other way round
+        let ids: Vec<String> = self
+            .migrate_outcomes
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        let proc_map = self.processes.read().unwrap();
+        ids.into_iter()
+            .filter_map(|id| proc_map.get(&id).map(|handle| (handle.info.start_time, id)))
+            .max()
+            .map(|(_, id)| id)
+    }
+
+    /// `bench --site <site> clear-cache`; `site` may be "all".
+    pub fn run_clear_cache(
+        &self,
+        bench_path: &str,
+        site: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_clear_cache_{}", chrono::Utc::now().timestamp());
+        self.start_site_command(
+            process_id,
+            initiator,
+            bench_path,
+            site,
+            "clear-cache",
+            vec![],
+        )
+    }
+
+    /// `bench --site <site> build-search-index`, which rebuilds the website
+    /// search index. `rebuild-global-search` is a different index and is not
+    /// a substitute.
+    pub fn run_reindex_search(
+        &self,
+        bench_path: &str,
+        site: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_reindex_{}", chrono::Utc::now().timestamp_millis());
+        self.start_site_command(
+            process_id,
+            initiator,
+            bench_path,
+            site,
+            "build-search-index",
+            vec![],
+        )
+    }
+
+    /// `bench [--site <site>] build [--app <app>]`; `site` may be "all".
+    pub fn run_bench_build(
+        &self,
+        bench_path: &str,
+        app: Option<&str>,
+        site: Option<&str>,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let (process_id, args) = match app {
+            Some(app) => (
+                format!("bench_build_{}_{}", app, chrono::Utc::now().timestamp()),
+                vec!["--app".to_string(), app.to_string()],
+            ),
+            None => (
+                format!("bench_build_{}", chrono::Utc::now().timestamp()),
+                vec![],
+            ),
+        };
+        match site {
+            Some(site) => {
+                self.start_site_command(process_id, initiator, bench_path, site, "build", args)
+            }
+            None => self.start_bench_process(process_id, initiator, bench_path, "build", args),
+        }
+    }
+
+    pub fn create_new_app(
+        &self,
+        bench_path: &str,
+        app_name: &str,
+        metadata: &NewAppMetadata,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let mut metadata = metadata.clone();
+        metadata.fill_defaults(Path::new(bench_path));
+        // Without a readable version assume a current bench
+        let extra_prompts = self
+            .bench_version(bench_path)
+            .is_none_or(|version| version.supports_new_app_prompts());
+        let process_id = format!("bench_new_app_{}", chrono::Utc::now().timestamp());
+        self.start_bench_process_with_input(
+            process_id.clone(),
+            initiator,
+            bench_path,
+            "new-app",
+            vec![app_name.to_string()],
+            metadata.prompt_answers(app_name, extra_prompts),
+        )
+    }
+
+    pub fn create_new_site(
+        &self,
+        bench_path: &str,
+        site_name: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_new_site_{}", chrono::Utc::now().timestamp());
+        self.start_bench_process(
+            process_id.clone(),
+            initiator,
+            bench_path,
+            "new-site",
+            vec![site_name.to_string()],
+        )
+    }
+
+    /// `bench new-site <site> --force` for a throwaway site. stdin is closed,
+    /// so a bench without a stored MariaDB root password fails instead of
+    /// waiting on the prompt.
+    pub fn create_scratch_site(
+        &self,
+        bench_path: &str,
+        site_name: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_new_site_{}", chrono::Utc::now().timestamp_millis());
+        self.start_bench_process_with_input(
+            process_id,
+            initiator,
+            bench_path,
+            "new-site",
+            vec![
+                site_name.to_string(),
+                "--force".to_string(),
+                "--admin-password".to_string(),
+                "admin".to_string(),
+            ],
+            String::new(),
+        )
+    }
+
+    /// `bench drop-site <site> --force --no-backup`, with stdin closed like
+    /// `create_scratch_site`.
+    pub fn drop_site(
+        &self,
+        bench_path: &str,
+        site_name: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_drop_site_{}", chrono::Utc::now().timestamp_millis());
+        self.start_bench_process_with_input(
+            process_id,
+            initiator,
+            bench_path,
+            "drop-site",
+            vec![
+                site_name.to_string(),
+                "--force".to_string(),
+                "--no-backup".to_string(),
+            ],
+            String::new(),
+        )
+    }
+
+    pub fn install_app(
+        &self,
+        bench_path: &str,
+        site: &str,
+        app_name: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_install_app_{}", chrono::Utc::now().timestamp());
+        self.start_site_command(
+            process_id,
+            initiator,
+            bench_path,
+            site,
+            "install-app",
+            vec![app_name.to_string()],
+        )
+    }
+
+    pub fn open_console(
+        &self,
+        bench_path: &str,
+        site: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_console_{}", chrono::Utc::now().timestamp());
+        self.start_bench_process(
+            process_id.clone(),
+            initiator,
+            bench_path,
+            "console",
+            vec!["--site".to_string(), site.to_string()],
+        )
+    }
+
+    /// `bench console --site <site>` that runs `preload` before its first
+    /// prompt. The script goes to a temporary file passed as `PYTHONSTARTUP`,
+    /// which is deleted when the console exits.
+    pub fn open_console_with_preload(
+        &self,
+        bench_path: &str,
+        site: &str,
+        preload: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_console_{}", chrono::Utc::now().timestamp_millis());
+        let script = std::env::temp_dir().join(format!("latte_{}.py", process_id));
+        fs::write(&script, preload)
+            .map_err(|e| format!("Failed to write {}: {}", script.display(), e))?;
+
+        // Registered before spawning so a console that exits straight away
+        // still has its script cleaned up by the monitor thread
+        self.scratch_files
+            .lock()
+            .unwrap()
+            .insert(process_id.clone(), script.clone());
+        let started = self.spawn_bench(
+            process_id.clone(),
+            initiator,
+            bench_path,
+            "bench console".to_string(),
+            vec![
+                "console".to_string(),
+                "--site".to_string(),
+                site.to_string(),
+            ],
+            StdioHook::Preload(script.clone()),
+        );
+        if started.is_err() {
+            self.scratch_files.lock().unwrap().remove(&process_id);
+            let _ = fs::remove_file(&script);
+        }
+        started
+    }
+
+    /// Calls a dotted Python function: `bench --site <site> execute <method>`.
+    pub fn run_execute(
+        &self,
+        bench_path: &str,
+        site: &str,
+        method: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_execute_{}", chrono::Utc::now().timestamp_millis());
+        self.start_site_command(
+            process_id,
+            initiator,
+            bench_path,
+            site,
+            "execute",
+            vec![method.to_string()],
+        )
+    }
+
+    /// Pipes `script` into `bench --site <site> console`; closing stdin ends
+    /// the session once the script has run.
+    pub fn run_console_script(
+        &self,
+        bench_path: &str,
+        site: &str,
+        script: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!(
+            "bench_console_script_{}",
+            chrono::Utc::now().timestamp_millis()
+        );
+        let mut input = script.trim_end().to_string();
+        input.push('\n');
+        self.start_site_command_with_input(
+            process_id, initiator, bench_path, site, "console", input,
+        )
+    }
+
+    /// Pipes `sql` into `bench --site <site> mariadb`. The client runs in batch
+    /// mode, so results come back as tab-separated rows with a header line.
+    pub fn run_db_query(
+        &self,
+        bench_path: &str,
+        site: &str,
+        sql: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_db_query_{}", chrono::Utc::now().timestamp_millis());
+        let mut input = sql.trim().to_string();
+        if !input.ends_with(';') {
+            input.push(';');
+        }
+        input.push('\n');
+
+        self.start_site_command_with_input(
+            process_id, initiator, bench_path, site, "mariadb", input,
+        )
+    }
+
+    pub fn open_mariadb(
+        &self,
+        bench_path: &str,
+        site: &str,
+        initiator: &str,
+    ) -> Result<String, String> {
+        let process_id = format!("bench_mariadb_{}", chrono::Utc::now().timestamp());
+        self.start_bench_process(
+            process_id.clone(),
+            initiator,
+            bench_path,
+            "mariadb",
+            vec!["--site".to_string(), site.to_string()],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn test_process_manager_creation() {
+        let manager = ProcessManager::new();
+        assert_eq!(manager.list_processes().len(), 0);
+    }
+
+    #[test]
+    fn test_detect_log_level() {
+        assert_eq!(
+            ProcessManager::detect_log_level("ERROR: Something went wrong"),
+            LogLevel::Error
+        );
+        assert_eq!(
+            ProcessManager::detect_log_level("Warning: This is a warning"),
+            LogLevel::Warning
+        );
+        assert_eq!(
+            ProcessManager::detect_log_level("Info: Normal message"),
+            LogLevel::Info
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_captures_output_and_exit() {
+        let manager = ProcessManager::new();
+        let id = manager
+            .start_simple_command(
+                "echo_test".to_string(),
+                ".",
+                "sh",
+                vec!["-c".to_string(), "echo hello; echo oops >&2".to_string()],
+            )
+            .unwrap();
+
+        let info = manager.wait_for_exit(&id, Duration::from_secs(10)).unwrap();
+        assert_eq!(info.status, ProcessStatus::Stopped);
+        assert_eq!(info.output_lines, vec!["hello".to_string()]);
+        assert_eq!(info.error_lines, vec!["oops".to_string()]);
+        assert_eq!(info.exit_code, Some(0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_log_capture_level_filters_log_lines() {
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            log_capture_level: LogLevel::Warning,
+            ..LatteSettings::default()
+        });
+        let id = manager
+            .start_simple_command(
+                "filtered".to_string(),
+                ".",
+                "sh",
+                vec![
+                    "-c".to_string(),
+                    "echo starting; echo 'WARNING: slow query'; echo 'ERROR: boom'".to_string(),
+                ],
+            )
+            .unwrap();
+
+        let info = manager.wait_for_exit(&id, Duration::from_secs(10)).unwrap();
+        let logs: Vec<String> = manager
+            .get_process_logs(&id)
+            .into_iter()
+            .map(|log| log.content)
+            .collect();
+        assert_eq!(logs, vec!["WARNING: slow query", "ERROR: boom"]);
+        assert_eq!(info.output_lines.len(), 3);
+        assert_eq!(info.error_count, 1);
+    }
+
+    #[test]
+    fn test_raw_log_keeps_arrival_order_and_every_level() {
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            log_capture_level: LogLevel::Warning,
+            log_buffer_size: 4,
+            ..LatteSettings::default()
+        });
+        manager.processes.write().unwrap().insert(
+            "migrate".to_string(),
+            ProcessHandle::new(ProcessInfo::starting(
+                "migrate".to_string(),
+                "frappe-bench-migrate",
+                "bench migrate".to_string(),
+                vec![],
+                ".",
+            )),
+        );
+        let feed = |source: LogSource, output: &'static str| {
+            ProcessManager::monitor_stream(
+                &manager.processes,
+                &"migrate".to_string(),
+                source,
+                Box::new(std::io::Cursor::new(output)),
+                manager.log_capture(),
+                None,
+            );
+        };
+        feed(
+            LogSource::Stdout,
+            "Migrating shop.local\nExecuting shop.patches.a\n",
+        );
+        feed(LogSource::Stderr, "ERROR: Traceback in a\n");
+        feed(LogSource::Stdout, "Executing shop.patches.b\n");
+        feed(LogSource::Stderr, "ERROR: Traceback in b\n");
+
+        let raw: Vec<String> = manager
+            .get_raw_logs("migrate")
+            .into_iter()
+            .map(|line| line.content)
+            .collect();
+        // Bounded by log_buffer_size, interleaved as the lines arrived
+        assert_eq!(
+            raw,
+            vec![
+                "Executing shop.patches.a",
+                "ERROR: Traceback in a",
+                "Executing shop.patches.b",
+                "ERROR: Traceback in b",
+            ]
+        );
+        assert_eq!(manager.get_process_logs("migrate").len(), 2);
+
+        let info = manager.get_process_info("migrate").unwrap();
+        assert_eq!(
+            info.output_lines,
+            vec!["Executing shop.patches.a", "Executing shop.patches.b"]
+        );
+        assert_eq!(info.error_lines.len(), 2);
+    }
+
+    #[test]
+    fn test_repeated_lines_are_collapsed() {
+        let feed = |collapse_repeats: bool| {
+            let manager = ProcessManager::new();
+            manager.processes.write().unwrap().insert(
+                "worker".to_string(),
+                ProcessHandle::new(ProcessInfo::starting(
+                    "worker".to_string(),
+                    "frappe-bench-start",
+                    "bench worker".to_string(),
+                    vec![],
+                    ".",
+                )),
+            );
+            let output = "heartbeat\nheartbeat\nheartbeat\njob done\nheartbeat\n";
+            ProcessManager::monitor_stream(
+                &manager.processes,
+                &"worker".to_string(),
+                LogSource::Stdout,
+                Box::new(std::io::Cursor::new(output)),
+                LogCapture {
+                    collapse_repeats,
+                    ..manager.log_capture()
+                },
+                None,
+            );
+            let logs = manager.get_process_logs("worker");
+            let info = manager.get_process_info("worker").unwrap();
+            (logs, info.output_lines.len())
+        };
+
+        let (logs, raw_lines) = feed(true);
+        let collapsed: Vec<(&str, u32)> = logs
+            .iter()
+            .map(|log| (log.content.as_str(), log.repeat_count))
+            .collect();
+        assert_eq!(
+            collapsed,
+            vec![("heartbeat", 3), ("job done", 1), ("heartbeat", 1)]
+        );
+        assert_eq!(raw_lines, 5);
+        assert!(format_log_line(&logs[0]).ends_with("heartbeat … (x3)"));
+        assert!(format_log_line(&logs[1]).ends_with("job done"));
+
+        let (logs, _) = feed(false);
+        assert_eq!(logs.len(), 5);
+        assert!(logs.iter().all(|log| log.repeat_count == 1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_concurrent_readers_while_logging() {
+        let manager = ProcessManager::new();
+        let id = manager
+            .start_simple_command(
+                "chatty".to_string(),
+                ".",
+                "sh",
+                vec![
+                    "-c".to_string(),
+                    "i=0; while [ $i -lt 3000 ]; do echo line $i; i=$((i+1)); done".to_string(),
+                ],
+            )
+            .unwrap();
+
+        let done = AtomicBool::new(false);
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    while !done.load(Ordering::SeqCst) {
+                        assert_eq!(manager.list_processes().len(), 1);
+                        let _ = manager.get_process_logs(&id);
+                        let _ = manager.get_process_info(&id);
+                    }
+                });
+            }
+            let info = manager.wait_for_exit(&id, Duration::from_secs(30));
+            done.store(true, Ordering::SeqCst);
+            assert_eq!(info.unwrap().status, ProcessStatus::Stopped);
+        });
+
+        let logs = manager.get_process_logs(&id);
+        assert_eq!(logs.len(), 1000);
+        assert_eq!(logs.last().unwrap().content, "line 2999");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_records_non_zero_exit_code() {
+        let manager = ProcessManager::new();
+        let id = manager
+            .start_simple_command(
+                "exit_test".to_string(),
+                ".",
+                "sh",
+                vec!["-c".to_string(), "exit 3".to_string()],
+            )
+            .unwrap();
+
+        let info = manager.wait_for_exit(&id, Duration::from_secs(10)).unwrap();
+        assert_eq!(info.status, ProcessStatus::Failed);
+        assert_eq!(info.exit_code, Some(3));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_logs() {
+        let manager = ProcessManager::new();
+        let id = manager
+            .start_simple_command(
+                "grep_test".to_string(),
+                ".",
+                "sh",
+                vec![
+                    "-c".to_string(),
+                    "echo 'GET /api/method/ping 200'; echo 'POST /api/resource/Item 500'; echo done"
+                        .to_string(),
+                ],
+            )
+            .unwrap();
+        manager.wait_for_exit(&id, Duration::from_secs(10)).unwrap();
+
+        let matches = manager.search_logs(&id, r"/api/\w+/.* \d{3}$").unwrap();
+        let contents: Vec<&str> = matches.iter().map(|log| log.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec!["GET /api/method/ping 200", "POST /api/resource/Item 500"]
+        );
+
+        let error = manager.search_logs(&id, "([").unwrap_err();
+        assert!(error.contains("Invalid pattern"));
+        assert!(manager.search_logs("missing", "x").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_second_bench_start_is_blocked() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("latte_double_start_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let fake_bench = root.join("bench");
+        fs::write(&fake_bench, "#!/bin/sh\nsleep 30\n").unwrap();
+        fs::set_permissions(&fake_bench, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            bench_executable: fake_bench.display().to_string(),
+            ..Default::default()
+        });
+        let bench = root.display().to_string();
+        let first = manager
+            .start_bench_dev_server(&bench, false, false, "frappe-bench-start")
+            .unwrap();
+        assert_eq!(manager.bench_process_for(&bench), Some(first.clone()));
+        let info = manager.get_process_info(&first).unwrap();
+        assert_eq!(info.initiator, "frappe-bench-start");
+        assert_eq!(
+            info.command_summary(),
+            "bench start (started by /frappe-bench-start)"
+        );
+        assert!(manager.bench_process_for("/some/other/bench").is_none());
+
+        let error = manager
+            .start_bench_dev_server(&bench, false, false, "frappe-bench-start")
+            .unwrap_err();
+        assert!(error.contains(&first));
+        assert_eq!(manager.list_processes().len(), 1);
+
+        manager.stop_process(&first).unwrap();
+        assert!(manager.bench_process_for(&bench).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_keepalive_restarts_crashed_bench() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("latte_keepalive_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let fake_bench = root.join("bench");
+        fs::write(&fake_bench, "#!/bin/sh\necho crashing\nexit 1\n").unwrap();
+        fs::set_permissions(&fake_bench, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            bench_executable: fake_bench.display().to_string(),
+            keepalive_restarts: 2,
+            ..Default::default()
+        });
+        let bench = root.display().to_string();
+        let id = manager
+            .start_bench_dev_server(&bench, false, true, "frappe-bench-start")
+            .unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(30);
+        while manager.keepalive.lock().unwrap().contains_key(&id) {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "keepalive never gave up"
+            );
+            thread::sleep(Duration::from_millis(200));
+        }
+
+        let info = manager.get_process_info(&id).unwrap();
+        assert_eq!(info.restarts, 2);
+        assert_eq!(info.status, ProcessStatus::Failed);
+        assert_eq!(info.exit_code, Some(1));
+
+        let proc_map = manager.processes.read().unwrap();
+        let system: Vec<&str> = proc_map[&id]
+            .log_lines
+            .iter()
+            .filter(|line| line.source == LogSource::System)
+            .map(|line| line.content.as_str())
+            .collect();
+        assert_eq!(
+            system,
+            vec![
+                "Stopped by exit code 1; restarting in 1s (attempt 1/2)",
+                "Stopped by exit code 1; restarting in 2s (attempt 2/2)",
+                "Stopped by exit code 1; giving up after 2 restart(s)",
+            ]
+        );
+        drop(proc_map);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_console_preload_runs_at_startup_and_is_cleaned_up() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("latte_preload_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let fake_bench = root.join("bench");
+        fs::write(
+            &fake_bench,
+            "#!/bin/sh\necho \"script: $PYTHONSTARTUP\"\ncat \"$PYTHONSTARTUP\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&fake_bench, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            bench_executable: fake_bench.display().to_string(),
+            ..Default::default()
+        });
+        let id = manager
+            .open_console_with_preload(
+                &root.display().to_string(),
+                "shop.local",
+                "import frappe\n",
+                "frappe-console",
+            )
+            .unwrap();
+        let script = std::env::temp_dir().join(format!("latte_{}.py", id));
+
+        let info = manager.wait_for_exit(&id, Duration::from_secs(10)).unwrap();
+        assert_eq!(
+            info.output_lines,
+            vec![
+                format!("script: {}", script.display()),
+                "import frappe".to_string(),
+            ]
+        );
+        assert_eq!(info.command, "bench console");
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while script.exists() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "preload script was not removed"
+            );
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert!(manager.scratch_files.lock().unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_bench_build_args() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("latte_build_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let fake_bench = root.join("bench");
+        fs::write(&fake_bench, "#!/bin/sh\necho \"$@\"\n").unwrap();
+        fs::set_permissions(&fake_bench, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            bench_executable: fake_bench.display().to_string(),
+            ..Default::default()
+        });
+        let bench = root.display().to_string();
+        let args = |app: Option<&str>, site: Option<&str>| {
+            let id = manager
+                .run_bench_build(&bench, app, site, "frappe-bench-build")
+                .unwrap();
+            let info = manager.wait_for_exit(&id, Duration::from_secs(10)).unwrap();
+            info.output_lines.join("\n")
+        };
+        assert_eq!(args(None, None), "build");
+        assert_eq!(
+            args(Some("shop"), Some("all")),
+            "--site all build --app shop"
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_migrate_outcome_is_recorded_on_exit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("latte_migrate_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let fake_bench = root.join("bench");
+        fs::write(
+            &fake_bench,
+            "#!/bin/sh\necho 'Migrating shop.local'\necho 'Executing shop.patches.v1_0.add_status in shop.local'\nsleep 1\n",
+        )
+        .unwrap();
+        fs::set_permissions(&fake_bench, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            bench_executable: fake_bench.display().to_string(),
+            ..Default::default()
+        });
+        let id = manager
+            .run_bench_migrate(
+                &root.display().to_string(),
+                Some("shop.local"),
+                false,
+                "frappe-bench-migrate",
+            )
+            .unwrap();
+        assert_eq!(manager.latest_migrate().as_deref(), Some(id.as_str()));
+
+        manager.wait_for_exit(&id, Duration::from_secs(10)).unwrap();
+        let outcome = manager.migrate_outcome(&id).unwrap();
+        assert!(outcome.succeeded);
+        assert_eq!(outcome.report.applied, vec!["shop.patches.v1_0.add_status"]);
+        assert!(!outcome.phases.is_empty());
+
+        let other = manager
+            .start_simple_command("echo".to_string(), ".", "echo", vec![])
+            .unwrap();
+        manager
+            .wait_for_exit(&other, Duration::from_secs(10))
+            .unwrap();
+        assert!(manager.migrate_outcome(&other).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_processes_filtered() {
+        let manager = ProcessManager::new();
+        let ok = manager
+            .start_simple_command(
+                "ok".to_string(),
+                ".",
+                "sh",
+                vec!["-c".to_string(), "true".to_string()],
+            )
+            .unwrap();
+        let failing = manager
+            .start_simple_command(
+                "failing".to_string(),
+                ".",
+                "sh",
+                vec!["-c".to_string(), "exit 3".to_string()],
+            )
+            .unwrap();
+        manager.wait_for_exit(&ok, Duration::from_secs(10)).unwrap();
+        manager
+            .wait_for_exit(&failing, Duration::from_secs(10))
+            .unwrap();
+
+        let ids = |processes: Vec<ProcessInfo>| {
+            let mut ids: Vec<String> = processes.into_iter().map(|p| p.id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(
+            ids(manager.list_processes_filtered(None, None)),
+            vec!["failing", "ok"]
+        );
+        assert_eq!(
+            ids(manager.list_processes_filtered(Some(ProcessStatus::Failed), None)),
+            vec!["failing"]
+        );
+        assert_eq!(
+            ids(manager.list_processes_filtered(None, Some("FAIL"))),
+            vec!["failing"]
+        );
+        assert!(manager
+            .list_processes_filtered(Some(ProcessStatus::Running), None)
+            .is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_registry_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("latte_registry_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let bench_path = dir.display().to_string();
+
+        let manager = ProcessManager::new();
+        manager.restore_registry(&bench_path);
+        let id = manager
+            .start_simple_command(
+                "sleeper".to_string(),
+                &bench_path,
+                "sleep",
+                vec!["30".to_string()],
+            )
+            .unwrap();
+        assert!(dir.join(REGISTRY_FILE).exists());
+
+        // A second manager stands in for the restarted extension
+        let restarted = ProcessManager::new();
+        assert_eq!(restarted.restore_registry(&bench_path), vec![id.clone()]);
+        let info = restarted.get_process_info(&id).unwrap();
+        assert_eq!(info.status, ProcessStatus::Running);
+        assert_eq!(info.pid, manager.get_process_info(&id).unwrap().pid);
+
+        restarted.stop_process(&id).unwrap();
+        assert!(!dir.join(REGISTRY_FILE).exists());
+        manager.wait_for_exit(&id, Duration::from_secs(10)).unwrap();
+
+        // Dead entries are pruned rather than adopted
+        fs::write(
+            dir.join(REGISTRY_FILE),
+            serde_json::to_string(&vec![RegistryEntry {
+                process_id: "gone".to_string(),
+                pid: info.pid.unwrap(),
+                command: "sleep".to_string(),
+                working_dir: bench_path.clone(),
+                start_time: SystemTime::now(),
+                initiator: String::new(),
+            }])
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(ProcessManager::new()
+            .restore_registry(&bench_path)
+            .is_empty());
+        assert!(!dir.join(REGISTRY_FILE).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_new_app_prompt_answers() {
+        let metadata = NewAppMetadata {
+            publisher: Some("Acme".to_string()),
+            email: Some("dev@acme.test".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            metadata.prompt_answers("library_management", true),
+            "Library Management\nLibrary Management\nAcme\ndev@acme.test\n\n\n\n"
+        );
+        assert_eq!(
+            metadata.prompt_answers("library_management", false),
+            "Library Management\nLibrary Management\nAcme\ndev@acme.test\n"
+        );
+
+        // Given values are kept
+        let mut given = metadata.clone();
+        given.fill_defaults(Path::new("/nonexistent"));
+        assert_eq!(given.publisher.as_deref(), Some("Acme"));
+        assert_eq!(given.email.as_deref(), Some("dev@acme.test"));
+
+        let repo = std::env::temp_dir().join(format!("latte_new_app_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(&repo).unwrap();
+        let initialized = Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&repo)
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        if initialized {
+            for (key, value) in [("user.name", "Jo Dev"), ("user.email", "jo@shop.test")] {
+                Command::new("git")
+                    .args(["config", key, value])
+                    .current_dir(&repo)
+                    .status()
+                    .unwrap();
+            }
+            let mut blank = NewAppMetadata {
+                email: Some(" ".to_string()),
+                ..Default::default()
+            };
+            blank.fill_defaults(&repo);
+            assert_eq!(blank.publisher.as_deref(), Some("Jo Dev"));
+            assert_eq!(blank.email.as_deref(), Some("jo@shop.test"));
+        }
+        fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[test]
+    fn test_parse_migrate_report() {
+        let lines: Vec<String> = [
+            "Migrating site1.local",
+            "Executing shop.patches.v1_0.add_sku in site1.local (_abc123)",
+            "Success: Done in 0.012s",
+            "Executing shop.patches.v1_0.fix_prices in site1.local (_abc123)",
+            "Traceback (most recent call last):",
+            "  File \"apps/frappe/frappe/modules/patch_handler.py\", line 206, in execute_patch",
+            "  File \"/bench/apps/shop/shop/patches/v1_0/fix_prices.py\", line 7, in execute",
+            "frappe.exceptions.ValidationError: Price cannot be negative",
+            "Failed to execute patch",
+            "Executing execute:frappe.delete_doc('Page', 'old') in site1.local (_abc123)",
+            "Executing shop.patches.v1_1.rebuild #2024-01-01 in site1.local (_abc123)",
+            "ModuleNotFoundError: No module named 'shop.patches.v1_1.rebuild'",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+        let report = MigrateReport::parse(&lines, Path::new("/bench/apps"), false);
+        assert_eq!(
+            report.applied,
+            vec![
+                "shop.patches.v1_0.add_sku",
+                "execute:frappe.delete_doc('Page', 'old')"
+            ]
+        );
+        assert_eq!(report.skipped, vec!["shop.patches.v1_0.fix_prices"]);
+        assert_eq!(report.failed, vec!["shop.patches.v1_1.rebuild #2024-01-01"]);
+
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(
+            report.errors[0].file_path,
+            "/bench/apps/shop/shop/patches/v1_0/fix_prices.py"
+        );
+        assert_eq!(report.errors[0].line_number, 7);
+        assert!(report.errors[0]
+            .message
+            .contains("Price cannot be negative"));
+        assert_eq!(
+            report.errors[1].file_path,
+            "/bench/apps/shop/shop/patches/v1_1/rebuild.py"
+        );
+        assert_eq!(report.errors[1].line_number, 1);
+    }
+
+    // Trimmed output of a frappe v15 `bench migrate` whose last patch fails
+    const MIGRATE_OUTPUT: &[&str] = &[
+        "Migrating shop.local",
+        "Executing `before_migrate` hooks...",
+        "Executing shop.patches.v1_0.rename_sku in shop.local (_1a2b3c)",
+        "Success: Done in 0.051s",
+        "Updating DocTypes for frappe        : [========================================] 100%",
+        "Updating DocTypes for shop          : [========================================] 100%",
+        "Executing shop.patches.v1_0.fill_prices in shop.local (_1a2b3c)",
+        "Success: Done in 0.120s",
+        "Syncing jobs...",
+        "Syncing fixtures...",
+        "Syncing dashboards...",
+        "Updating customizations for Address",
+        "Queued rebuilding of search index for shop.local",
+        "Executing `after_migrate` hooks...",
+        "Migrating blog.local",
+        "Executing blog.patches.v1_0.move_posts in blog.local (_4d5e6f)",
+        "Updating DocTypes for frappe        : [========================================] 100%",
+        "Executing blog.patches.v1_0.set_authors in blog.local (_4d5e6f)",
+        "Traceback (most recent call last):",
+        "frappe.exceptions.ValidationError: Author is mandatory",
+        "blog.patches.v1_0.set_authors: failed: STOPPED",
+    ];
+
+    #[test]
+    fn test_migrate_phases() {
+        let started = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let logs: Vec<LogLine> = MIGRATE_OUTPUT
+            .iter()
+            .enumerate()
+            .map(|(i, line)| LogLine {
+                timestamp: started + Duration::from_secs(i as u64),
+                level: ProcessManager::detect_log_level(line),
+                content: line.to_string(),
+                source: LogSource::Stdout,
+                repeat_count: 1,
+            })
+            .collect();
+
+        let phases = migrate_phases(&logs, false);
+        let summary: Vec<(&str, MigratePhaseKind, u64, bool)> = phases
+            .iter()
+            .map(|phase| {
+                (
+                    phase.site.as_deref().unwrap_or(""),
+                    phase.kind,
+                    phase.duration.as_secs(),
+                    phase.succeeded,
+                )
+            })
+            .collect();
+        use MigratePhaseKind::*;
+        assert_eq!(
+            summary,
+            vec![
+                ("shop.local", PreModelSync, 4, true),
+                ("shop.local", SyncDocTypes, 2, true),
+                ("shop.local", Patches, 2, true),
+                ("shop.local", SyncFixtures, 4, true),
+                ("shop.local", SearchIndex, 2, true),
+                ("blog.local", PreModelSync, 2, true),
+                ("blog.local", SyncDocTypes, 1, true),
+                ("blog.local", Patches, 3, false),
+            ]
+        );
+
+        let phases = migrate_phases(&logs[..14], true);
+        assert_eq!(phases.len(), 5);
+        assert!(phases.iter().all(|phase| phase.succeeded));
+        assert!(migrate_phases(&[], false).is_empty());
+    }
+
+    #[test]
+    fn test_migrate_phases_ignore_log_capture_level() {
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            log_capture_level: LogLevel::Warning,
+            collapse_repeated_lines: true,
+            ..LatteSettings::default()
+        });
+        manager.processes.write().unwrap().insert(
+            "migrate".to_string(),
+            ProcessHandle::new(ProcessInfo::starting(
+                "migrate".to_string(),
+                "frappe-bench-migrate",
+                "bench migrate".to_string(),
+                vec![],
+                ".",
+            )),
+        );
+        let output = MIGRATE_OUTPUT.join("\n") + "\n";
+        ProcessManager::monitor_stream(
+            &manager.processes,
+            &"migrate".to_string(),
+            LogSource::Stdout,
+            Box::new(std::io::Cursor::new(output)),
+            manager.log_capture(),
+            None,
+        );
+
+        let kinds = |logs: Vec<LogLine>| -> Vec<MigratePhaseKind> {
+            migrate_phases(&logs, false)
+                .iter()
+                .map(|phase| phase.kind)
+                .collect()
+        };
+        let kinds_shown = kinds(manager.get_process_logs("migrate"));
+        let kinds = kinds(manager.get_raw_logs("migrate"));
+        // Most phase markers are info lines, gone from the display log
+        assert_ne!(kinds_shown, kinds);
+        use MigratePhaseKind::*;
+        assert_eq!(
+            kinds,
+            vec![
+                PreModelSync,
+                SyncDocTypes,
+                Patches,
+                SyncFixtures,
+                SearchIndex,
+                PreModelSync,
+                SyncDocTypes,
+                Patches,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_indexed_document_count() {
+        let lines: Vec<String> = [
+            "Building search index for shop.local",
+            "Indexed 120 documents",
+            "indexed 3 document in Web Page",
+            "Reindexed 5 documents",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+        assert_eq!(indexed_document_count(&lines), Some(123));
+        assert_eq!(indexed_document_count(&lines[..1]), None);
+    }
+
+    #[test]
+    fn test_missing_bench_executable() {
+        let bench = std::env::temp_dir().join(format!("latte_bench_exe_{}", std::process::id()));
+        let bin_dir = venv_bin_dir(&bench);
+        fs::create_dir_all(&bin_dir).unwrap();
+        let bench_path = bench.to_string_lossy().to_string();
+
+        let manager = ProcessManager::new();
+        let mut settings = LatteSettings {
+            bench_executable: "latte-no-such-bench".to_string(),
+            ..LatteSettings::default()
+        };
+        manager.configure(&settings);
+        let error = manager.ensure_bench_executable(&bench_path).unwrap_err();
+        assert!(error.contains("latte-no-such-bench"));
+        assert!(error.contains(crate::settings::SETTINGS_FILE));
+
+        // The bench's virtualenv is searched after PATH
+        fs::write(bin_dir.join("latte-no-such-bench"), "").unwrap();
+        assert_eq!(
+            manager.ensure_bench_executable(&bench_path).unwrap(),
+            bin_dir.join("latte-no-such-bench")
+        );
+
+        settings.bench_executable = venv_bin_dir(Path::new(""))
+            .join("latte-no-such-bench")
+            .display()
+            .to_string();
+        manager.configure(&settings);
+        assert!(manager.resolve_bench_executable(&bench_path).is_some());
+
+        fs::remove_dir_all(&bench).unwrap();
+    }
+
+    #[test]
+    fn test_git_status() {
+        let repo = std::env::temp_dir().join(format!("latte_git_status_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(&repo).unwrap();
+        assert_eq!(git_status(&repo), None);
+
+        let initialized = Command::new("git")
+            .args(["init", "-q", "-b", "develop"])
+            .current_dir(&repo)
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        if initialized {
+            fs::write(repo.join("a.py"), "").unwrap();
+            fs::write(repo.join("b.py"), "").unwrap();
+            assert_eq!(
+                git_status(&repo),
+                Some(GitStatus {
+                    branch: "develop".to_string(),
+                    dirty_files: 2
+                })
+            );
+        }
+
+        fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[test]
+    fn test_git_show_head() {
+        let repo = std::env::temp_dir().join(format!("latte_git_show_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(repo.join("doctype")).unwrap();
+        let file = repo.join("doctype/item.json");
+        fs::write(&file, "committed").unwrap();
+
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=latte",
+                    "-c",
+                    "user.email=latte@example.com",
+                ])
+                .args(args)
+                .current_dir(&repo)
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false)
+        };
+        if git(&["init", "-q"]) {
+            assert!(git_show_head(&file).unwrap_err().contains("no commits"));
+            assert!(git(&["add", "."]) && git(&["commit", "-q", "-m", "init"]));
+            fs::write(&file, "changed").unwrap();
+            assert_eq!(git_show_head(&file).unwrap(), "committed");
+
+            fs::write(repo.join("doctype/new.json"), "").unwrap();
+            let err = git_show_head(&repo.join("doctype/new.json")).unwrap_err();
+            assert!(err.contains("not committed"), "{}", err);
+        }
+
+        fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[test]
+    fn test_listening_inodes() {
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:1F40 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:2328 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41235 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:1F40 0100007F:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 41236 1 0000000000000000 20 4 30 10 -1
+";
+        assert_eq!(listening_inodes(table, 8000), vec![41234]);
+        assert_eq!(listening_inodes(table, 9000), vec![41235]);
+        assert!(listening_inodes(table, 8001).is_empty());
+    }
+
+    #[test]
+    fn test_netstat_listening_pid() {
+        let output = "
+Active Connections
+
+  Proto  Local Address          Foreign Address        State           PID
+  TCP    0.0.0.0:8000           0.0.0.0:0              LISTENING       4242
+  TCP    127.0.0.1:8000         127.0.0.1:50123        ESTABLISHED     4242
+  TCP    [::]:9000              [::]:0                 LISTENING       5151
+";
+        assert_eq!(netstat_listening_pid(output, 8000), Some(4242));
+        assert_eq!(netstat_listening_pid(output, 9000), Some(5151));
+        assert_eq!(netstat_listening_pid(output, 50123), None);
+    }
+
+    #[test]
+    fn test_check_tcp_and_http_ping() {
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            for reply in ["{\"message\":\"pong\"}", "{}"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                while !request.ends_with("\r\n\r\n") {
+                    reader.read_line(&mut request).unwrap();
+                }
+                assert!(request.contains("Host: dev.localhost"));
+                write!(stream, "HTTP/1.1 200 OK\r\n\r\n{}", reply).unwrap();
+            }
+        });
+
+        let timeout = Duration::from_secs(2);
+        assert!(http_ping("127.0.0.1", port, "dev.localhost", timeout).is_ok());
+        assert!(http_ping("127.0.0.1", port, "dev.localhost", timeout)
+            .unwrap_err()
+            .contains("pong"));
+        server.join().unwrap();
+
+        // The listener is gone, so nothing answers on that port any more
+        assert!(check_tcp("127.0.0.1", port, timeout).is_err());
+    }
+
+    #[test]
+    fn test_python_exception() {
+        let lines = |text: &str| text.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(python_exception(&lines("In [1]: 2\nOut[1]: 2")), None);
+        assert_eq!(
+            python_exception(&lines(
+                "Traceback (most recent call last):\n  File \"<string>\", line 1\n\
+                 frappe.exceptions.DoesNotExistError: User x not found"
+            ))
+            .as_deref(),
+            Some("frappe.exceptions.DoesNotExistError: User x not found")
+        );
+        assert_eq!(
+            python_exception(&lines(
+                "NameError                                 Traceback (most recent call last)\n\
+                 ----> 1 foo\n\n\
+                 NameError: name 'foo' is not defined"
+            ))
+            .as_deref(),
+            Some("NameError: name 'foo' is not defined")
+        );
+    }
+
+    #[test]
+    fn test_destructive_statement() {
+        assert!(destructive_statement("select * from tabUser").is_none());
+        assert!(destructive_statement("delete from tabToDo where name = 'x'").is_none());
+        assert_eq!(
+            destructive_statement("select 1; delete from `tabToDo`").as_deref(),
+            Some("delete from `tabToDo`")
+        );
+        assert!(destructive_statement("  drop table tabNote").is_some());
+        assert!(destructive_statement("TRUNCATE tabNote;").is_some());
+    }
+
+    #[test]
+    fn test_destructive_statement_looks_past_comments_and_quotes() {
+        assert_eq!(
+            destructive_statement("/* x */ DROP TABLE t").as_deref(),
+            Some("/* x */ DROP TABLE t")
+        );
+        assert!(destructive_statement("(DELETE FROM tabToDo)").is_some());
+        assert!(destructive_statement("-- cleanup\ndelete from tabToDo").is_some());
+        assert!(destructive_statement("# cleanup\ntruncate tabToDo").is_some());
+        assert!(destructive_statement("delete from tabToDo -- where name = 'x'").is_some());
+
+        // Semicolons and keywords inside strings or comments don't start statements
+        assert_eq!(
+            destructive_statement("select 'a; drop table t'; delete from t").as_deref(),
+            Some("delete from t")
+        );
+        assert!(destructive_statement("select 'it''s; drop table t'").is_none());
+        assert!(destructive_statement("select \"x\\\"; drop table t\"").is_none());
+        assert!(destructive_statement("select 1 /* ; drop table t */").is_none());
+        assert!(destructive_statement("select 1 /*/ ; drop table t */").is_none());
+        assert!(destructive_statement("delete from t where note = 'a;b'").is_none());
+    }
+
+    #[test]
+    fn test_check_bench_subcommand() {
+        assert!(check_bench_subcommand("seed-data", false).is_ok());
+        assert!(check_bench_subcommand("drop-site", false).is_err());
+        assert!(check_bench_subcommand("drop-site", true).is_ok());
+        assert!(check_bench_subcommand("--version", false).is_err());
+        assert!(check_bench_subcommand("start; rm -rf /", true).is_err());
+    }
+
+    #[test]
+    fn test_parse_bench_version() {
+        let version = BenchVersion::parse("5.22.6\n").unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (5, 22, 6));
+        assert!(version.at_least(5, 0));
+        assert!(!version.at_least(5, 23));
+
+        let old = BenchVersion::parse("bench version 4.1").unwrap();
+        assert_eq!(old.raw, "4.1");
+        assert!(!old.supports_ui_tests());
+
+        assert!(BenchVersion::parse("command not found").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bench_version_uses_the_venv_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("latte_bench_version_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let bin = venv_bin_dir(&root);
+        fs::create_dir_all(&bin).unwrap();
+        let fake_bench = bin.join("latte-venv-bench");
+        fs::write(&fake_bench, "#!/bin/sh\necho 4.1.0\n").unwrap();
+        fs::set_permissions(&fake_bench, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = ProcessManager::new();
+        manager.configure(&LatteSettings {
+            bench_executable: "latte-venv-bench".to_string(),
+            ..Default::default()
+        });
+        let bench_path = root.display().to_string();
+        let version = manager.bench_version(&bench_path).unwrap();
+        assert_eq!(version.raw, "4.1.0");
+        assert!(!version.supports_ui_tests());
+
+        // Cached per bench root: a newer version on disk isn't seen
+        fs::write(&fake_bench, "#!/bin/sh\necho 5.0.0\n").unwrap();
+        assert_eq!(manager.bench_version(&bench_path).unwrap().raw, "4.1.0");
+
+        manager.configure(&LatteSettings {
+            bench_executable: "latte-missing-bench".to_string(),
+            ..Default::default()
+        });
+        assert!(manager.bench_version(&bench_path).is_none());
+        fs::remove_dir_all(&root).unwrap();
     }
 }
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::process_manager::LogLevel;
+
+pub const SETTINGS_FILE: &str = ".latte.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LatteSettings {
+    pub bench_executable: String,
+    pub default_site: Option<String>,
+    pub log_buffer_size: usize,
+    pub log_capture_level: LogLevel,
+    pub collapse_repeated_lines: bool,
+    pub command_timeout_secs: u64,
+    pub max_output_lines: usize,
+    pub output_style: OutputStyle,
+    pub keepalive_restarts: usize,
+}
+
+impl Default for LatteSettings {
+    fn default() -> Self {
+        Self {
+            bench_executable: "bench".to_string(),
+            default_site: None,
+            log_buffer_size: 1000,
+            log_capture_level: LogLevel::Trace,
+            collapse_repeated_lines: false,
+            command_timeout_secs: 10,
+            max_output_lines: 200,
+            output_style: OutputStyle::default(),
+            keepalive_restarts: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStyle {
+    #[default]
+    Emoji,
+    Plain,
+}
+
+/// Status symbols and their ASCII replacements in plain mode. Anything else
+/// in the emoji ranges is decorative and dropped.
+const PLAIN_SYMBOLS: &[(&str, &str)] = &[
+    ("✅", "[OK]"),
+    ("✓", "[OK]"),
+    ("✔", "[OK]"),
+    ("❌", "[FAIL]"),
+    ("🚨", "[FAIL]"),
+    ("⚠️", "[WARN]"),
+    ("⚠", "[WARN]"),
+    ("ℹ️", "[INFO]"),
+    ("ℹ", "[INFO]"),
+    ("🔥", "[ERROR]"),
+    ("🐛", "[DEBUG]"),
+    ("⏭️", "[SKIP]"),
+    ("⏳", "[WAIT]"),
+    ("🟢", "[RUNNING]"),
+    ("⚪", "[DONE]"),
+    ("🔴", "[FAILED]"),
+    ("•", "*"),
+    ("↔", "<->"),
+    ("→", "->"),
+    ("…", "..."),
+];
+
+impl OutputStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputStyle::Emoji => "emoji",
+            OutputStyle::Plain => "plain",
+        }
+    }
+
+    /// Rewrites `text` for this style. Emoji output is returned unchanged.
+    pub fn apply(&self, text: &str) -> String {
+        if *self == OutputStyle::Emoji || text.is_ascii() {
+            return text.to_string();
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        'outer: while let Some(c) = rest.chars().next() {
+            for (symbol, tag) in PLAIN_SYMBOLS {
+                if let Some(after) = rest.strip_prefix(symbol) {
+                    out.push_str(tag);
+                    rest = after.strip_prefix('\u{fe0f}').unwrap_or(after);
+                    continue 'outer;
+                }
+            }
+
+            rest = &rest[c.len_utf8()..];
+            if is_decorative(c) {
+                rest = rest.strip_prefix('\u{fe0f}').unwrap_or(rest);
+                rest = rest.trim_start_matches(' ');
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+}
+
+fn is_decorative(c: char) -> bool {
+    matches!(c as u32, 0x1F000..=0x1FAFF | 0x2300..=0x23FF | 0x2600..=0x27BF | 0xFE0F)
+}
+
+/// Effective settings plus where they came from.
+#[derive(Debug, Clone, Default)]
+pub struct LoadedSettings {
+    pub settings: LatteSettings,
+    pub path: Option<PathBuf>,
+    pub file_keys: Vec<String>,
+}
+
+impl LoadedSettings {
+    pub fn source_of(&self, key: &str) -> &'static str {
+        if self.file_keys.iter().any(|k| k == key) {
+            SETTINGS_FILE
+        } else {
+            "default"
+        }
+    }
+}
+
+impl LatteSettings {
+    /// Loads the first `.latte.json` found in `dirs` (in priority order). A
+    /// missing file yields defaults; an invalid one is an error.
+    pub fn load(dirs: &[&Path]) -> Result<LoadedSettings, String> {
+        let Some(path) = dirs
+            .iter()
+            .map(|dir| dir.join(SETTINGS_FILE))
+            .find(|path| path.is_file())
+        else {
+            return Ok(LoadedSettings::default());
+        };
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid JSON in {}: {}", path.display(), e))?;
+        let file_keys = value
+            .as_object()
+            .ok_or(format!("{} must contain a JSON object", path.display()))?
+            .keys()
+            .cloned()
+            .collect();
+
+        let settings: LatteSettings = serde_json::from_value(value)
+            .map_err(|e| format!("Invalid settings in {}: {}", path.display(), e))?;
+        settings
+            .validate()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        Ok(LoadedSettings {
+            settings,
+            path: Some(path),
+            file_keys,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.bench_executable.trim().is_empty() {
+            return Err("bench_executable must not be empty".to_string());
+        }
+        if self.log_buffer_size == 0 {
+            return Err("log_buffer_size must be greater than 0".to_string());
+        }
+        if self.max_output_lines == 0 {
+            return Err("max_output_lines must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("latte_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_settings_with_defaults() {
+        let dir = temp_dir("settings_defaults");
+        fs::write(dir.join(SETTINGS_FILE), r#"{"log_buffer_size": 50}"#).unwrap();
+
+        let loaded = LatteSettings::load(&[&dir]).unwrap();
+        assert_eq!(loaded.settings.log_buffer_size, 50);
+        assert_eq!(loaded.settings.bench_executable, "bench");
+        assert_eq!(loaded.source_of("log_buffer_size"), SETTINGS_FILE);
+        assert_eq!(loaded.source_of("bench_executable"), "default");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plain_output_style() {
+        let dir = temp_dir("settings_plain");
+        fs::write(dir.join(SETTINGS_FILE), r#"{"output_style": "plain"}"#).unwrap();
+        let loaded = LatteSettings::load(&[&dir]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let style = loaded.settings.output_style;
+        assert_eq!(style, OutputStyle::Plain);
+        assert_eq!(
+            style.apply("✅ Migrated\n⚠️ 2 warnings\n❌ Failed → retry\n📊 Stats"),
+            "[OK] Migrated\n[WARN] 2 warnings\n[FAIL] Failed -> retry\nStats"
+        );
+        assert_eq!(style.apply("📦 Café"), "Café");
+        assert_eq!(OutputStyle::Emoji.apply("✅ Done"), "✅ Done");
+    }
+
+    #[test]
+    fn test_load_settings_rejects_unknown_keys() {
+        let dir = temp_dir("settings_unknown");
+        fs::write(dir.join(SETTINGS_FILE), r#"{"log_bufer_size": 50}"#).unwrap();
+
+        let error = LatteSettings::load(&[&dir]).unwrap_err();
+        assert!(error.contains("log_bufer_size"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,28 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::process::{Command, Stdio};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+
+use crate::process_manager::{BenchVersion, LineListener, ProcessManager, ProcessStatus};
+
+// Watch mode polls for source changes and waits for saves to settle before re-running
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+// Whole-suite runs are killed if they haven't finished after this long
+const TEST_RUN_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+// Each setup/teardown step of a --test-site run; new-site runs every install hook
+const TEMP_SITE_STEP_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+// Failing tests within this many lines of each other in a file are cross-linked
+const RELATED_LINE_WINDOW: u32 = 50;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestResult {
@@ -42,18 +60,130 @@ pub struct TestSuite {
     pub results: Vec<TestResult>,
 }
 
+/// Counts from pytest's final line, e.g.
+/// `===== 5 passed, 2 failed, 1 skipped in 3.21s =====`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PytestSummary {
+    pub passed: u32,
+    pub failed: u32,
+    pub errors: u32,
+    pub skipped: u32,
+    pub duration: f64,
+}
+
+impl PytestSummary {
+    pub fn parse(line: &str) -> Option<Self> {
+        let line_re = Regex::new(r"^=*\s*(\d+ \w+(?:, \d+ \w+)*) in ([\d.]+)s\b").unwrap();
+        let captures = line_re.captures(line.trim())?;
+        let mut summary = PytestSummary {
+            passed: 0,
+            failed: 0,
+            errors: 0,
+            skipped: 0,
+            duration: captures[2].parse().ok()?,
+        };
+
+        for part in captures[1].split(", ") {
+            let (count, outcome) = part.split_once(' ')?;
+            let count: u32 = count.parse().ok()?;
+            match outcome {
+                "passed" | "xpassed" => summary.passed += count,
+                "failed" => summary.failed += count,
+                "error" | "errors" => summary.errors += count,
+                // Expected failures count as skipped, as in unittest
+                "skipped" | "xfailed" => summary.skipped += count,
+                "warning" | "warnings" | "deselected" | "rerun" => {}
+                _ => return None,
+            }
+        }
+        Some(summary)
+    }
+}
+
+/// Output formats for a finished suite, picked with `--format`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestReportFormat {
+    Human,
+    Json,
+    Junit,
+    Markdown,
+}
+
+impl TestReportFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "human" | "text" => Ok(TestReportFormat::Human),
+            "json" => Ok(TestReportFormat::Json),
+            "junit" | "xml" => Ok(TestReportFormat::Junit),
+            "markdown" | "md" => Ok(TestReportFormat::Markdown),
+            _ => Err(format!(
+                "Unknown report format '{}' (expected human, json, junit or markdown)",
+                name
+            )),
+        }
+    }
+}
+
+/// Narrows a `run-tests` call: `--failfast`, `--module <dotted.path>` and
+/// `--test <name>` are passed straight through to bench.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestSelection {
+    pub failfast: bool,
+    pub module: Option<String>,
+    pub test: Option<String>,
+}
+
+impl TestSelection {
+    pub fn is_empty(&self) -> bool {
+        *self == TestSelection::default()
+    }
+
+    fn bench_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(module) = &self.module {
+            args.extend(["--module".to_string(), module.clone()]);
+        }
+        if let Some(test) = &self.test {
+            args.extend(["--test".to_string(), test.clone()]);
+        }
+        if self.failfast {
+            args.push("--failfast".to_string());
+        }
+        args
+    }
+
+    /// The applied flags as bench sees them, e.g. `--module x --failfast`.
+    pub fn describe(&self) -> String {
+        self.bench_args().join(" ")
+    }
+}
+
+/// Counts for an in-flight (or just finished) run, updated line by line.
 #[derive(Debug, Clone)]
+pub struct TestProgress {
+    pub suite: TestSuite,
+    pub started: SystemTime,
+    pub finished: bool,
+    pub error: Option<String>,
+}
+
+/// Folds one line of test output into the suite being built.
+type LineParser = Box<dyn FnMut(&mut TestSuite, &str) + Send>;
+
+/// Latest progress per app, shared between runners and the extension.
+pub type SharedTestProgress = Arc<Mutex<HashMap<String, TestProgress>>>;
+
+#[derive(Clone)]
 pub struct TestRunner {
     bench_path: String,
     site_name: String,
+    /// bench as resolved by the ProcessManager, so commands built here don't
+    /// depend on the extension's own PATH.
+    bench_executable: PathBuf,
+    bench_version: Option<BenchVersion>,
     running_tests: Arc<Mutex<HashMap<String, bool>>>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TestPattern {
-    pub pattern: String,
-    pub description: String,
-    pub severity: DiagnosticSeverity,
+    process_manager: Arc<ProcessManager>,
+    progress: SharedTestProgress,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -64,6 +194,18 @@ pub enum DiagnosticSeverity {
     Hint,
 }
 
+impl DiagnosticSeverity {
+    /// Numeric severity as defined by the Language Server Protocol.
+    pub fn lsp_code(&self) -> u8 {
+        match self {
+            DiagnosticSeverity::Error => 1,
+            DiagnosticSeverity::Warning => 2,
+            DiagnosticSeverity::Info => 3,
+            DiagnosticSeverity::Hint => 4,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Diagnostic {
     pub file_path: String,
@@ -83,173 +225,355 @@ pub struct DiagnosticRelatedInfo {
     pub message: String,
 }
 
-impl TestRunner {
-    pub fn new(bench_path: String, site_name: String) -> Self {
+impl TestSuite {
+    pub fn empty(app_name: &str) -> Self {
         Self {
-            bench_path,
-            site_name,
-            running_tests: Arc::new(Mutex::new(HashMap::new())),
+            name: app_name.to_string(),
+            app: app_name.to_string(),
+            total_tests: 0,
+            passed: 0,
+            failed: 0,
+            errors: 0,
+            skipped: 0,
+            duration: 0.0,
+            results: Vec::new(),
         }
     }
 
-    pub fn run_app_tests(&self, app_name: &str) -> Result<TestSuite, String> {
-        let test_id = format!("{}::{}", app_name, chrono::Utc::now().timestamp());
+    /// Takes pytest's closing summary as the final word on the counts, since
+    /// pytest doesn't print a line per test unless run verbosely.
+    pub fn apply_pytest_summary(&mut self, summary: &PytestSummary) {
+        self.passed = summary.passed;
+        self.failed = summary.failed;
+        self.errors = summary.errors;
+        self.skipped = summary.skipped;
+        self.total_tests = self.passed + self.failed + self.errors + self.skipped;
+        self.duration = summary.duration;
+    }
 
-        // Mark test as running
-        {
-            let mut running = self.running_tests.lock().unwrap();
-            running.insert(test_id.clone(), true);
+    pub fn record(&mut self, result: TestResult) {
+        match result.status {
+            TestStatus::Passed => self.passed += 1,
+            TestStatus::Failed => self.failed += 1,
+            TestStatus::Error => self.errors += 1,
+            TestStatus::Skipped => self.skipped += 1,
+            _ => {}
         }
+        self.total_tests = self.passed + self.failed + self.errors + self.skipped;
+        self.results.push(result);
+    }
+}
 
-        let result = self.execute_tests(app_name);
-
-        // Mark test as finished
-        {
-            let mut running = self.running_tests.lock().unwrap();
-            running.remove(&test_id);
+impl TestRunner {
+    pub fn new(bench_path: String, site_name: String) -> Self {
+        let process_manager = Arc::new(ProcessManager::new());
+        Self {
+            bench_executable: Self::resolve_bench(&process_manager, &bench_path),
+            bench_path,
+            site_name,
+            bench_version: None,
+            running_tests: Arc::new(Mutex::new(HashMap::new())),
+            process_manager,
+            progress: Arc::new(Mutex::new(HashMap::new())),
         }
+    }
 
-        result
+    pub fn with_process_manager(mut self, process_manager: Arc<ProcessManager>) -> Self {
+        self.bench_executable = Self::resolve_bench(&process_manager, &self.bench_path);
+        self.process_manager = process_manager;
+        self
     }
 
-    pub fn run_specific_test(&self, app_name: &str, test_path: &str) -> Result<TestResult, String> {
-        let command = format!(
-            "cd {} && bench --site {} run-tests --app {} --test {}",
-            self.bench_path, self.site_name, app_name, test_path
-        );
+    fn resolve_bench(process_manager: &ProcessManager, bench_path: &str) -> PathBuf {
+        process_manager
+            .resolve_bench_executable(bench_path)
+            .unwrap_or_else(|| PathBuf::from(process_manager.bench_executable()))
+    }
 
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(&command)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(|e| format!("Failed to run test command: {}", e))?;
+    pub fn with_progress(mut self, progress: SharedTestProgress) -> Self {
+        self.progress = progress;
+        self
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    pub fn with_bench_version(mut self, bench_version: Option<BenchVersion>) -> Self {
+        self.bench_version = bench_version;
+        self
+    }
 
-        self.parse_single_test_result(test_path, &stdout, &stderr)
+    // Unknown versions get the modern flags; only known-old benches are downgraded
+    fn supports(&self, check: fn(&BenchVersion) -> bool) -> bool {
+        self.bench_version.as_ref().map(check).unwrap_or(true)
     }
 
-    fn execute_tests(&self, app_name: &str) -> Result<TestSuite, String> {
-        let command = format!(
-            "cd {} && bench --site {} run-tests --app {} --verbose",
-            self.bench_path, self.site_name, app_name
-        );
+    pub fn run_app_tests(&self, app_name: &str) -> Result<TestSuite, String> {
+        self.execute_tests(app_name, &TestSelection::default())
+    }
 
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(&command)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(|e| format!("Failed to run test command: {}", e))?;
+    pub fn run_selected_tests(
+        &self,
+        app_name: &str,
+        selection: &TestSelection,
+    ) -> Result<TestSuite, String> {
+        if selection.failfast && !self.supports(BenchVersion::supports_verbose_tests) {
+            return Err("This bench version does not support --failfast".to_string());
+        }
+        self.execute_tests(app_name, selection)
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    /// Runs the tests on a throwaway `test_<timestamp>` site: creates it,
+    /// installs the app, runs the tests there and drops it again. Each step
+    /// is appended to `steps` as it completes. The site is dropped whenever it
+    /// was created, even if installing or testing failed.
+    pub fn run_tests_on_temp_site(
+        &self,
+        app_name: &str,
+        selection: &TestSelection,
+        steps: &mut Vec<String>,
+    ) -> Result<TestSuite, String> {
+        let site = format!("test_{}", chrono::Utc::now().timestamp());
+        let result = self.run_on_new_site(&site, app_name, selection, steps);
+        if let Err(e) = &result {
+            steps.push(format!("❌ {}", e));
+        }
 
-        self.parse_test_output(app_name, &stdout, &stderr)
+        // new-site creates the site folder before touching the database, so
+        // its presence covers half-finished installs too
+        if Path::new(&self.bench_path)
+            .join("sites")
+            .join(&site)
+            .exists()
+        {
+            let dropped =
+                self.process_manager
+                    .drop_site(&self.bench_path, &site, "frappe-run-tests");
+            match self.wait_for_step(dropped, &format!("bench drop-site {}", site)) {
+                Ok(()) => steps.push(format!("🧹 Dropped site {}", site)),
+                Err(e) => steps.push(format!(
+                    "⚠️ {}. Remove it with: bench drop-site {} --force",
+                    e, site
+                )),
+            }
+        }
+        result
     }
 
-    fn parse_test_output(
+    fn run_on_new_site(
         &self,
+        site: &str,
         app_name: &str,
-        stdout: &str,
-        stderr: &str,
+        selection: &TestSelection,
+        steps: &mut Vec<String>,
     ) -> Result<TestSuite, String> {
-        let mut test_suite = TestSuite {
-            name: app_name.to_string(),
-            app: app_name.to_string(),
-            total_tests: 0,
-            passed: 0,
-            failed: 0,
-            errors: 0,
-            skipped: 0,
-            duration: 0.0,
-            results: Vec::new(),
+        let created =
+            self.process_manager
+                .create_scratch_site(&self.bench_path, site, "frappe-run-tests");
+        self.wait_for_step(created, &format!("bench new-site {}", site))?;
+        steps.push(format!("✅ Created site {}", site));
+
+        let installed =
+            self.process_manager
+                .install_app(&self.bench_path, site, app_name, "frappe-run-tests");
+        self.wait_for_step(installed, &format!("bench install-app {}", app_name))?;
+        steps.push(format!("✅ Installed {} on {}", app_name, site));
+
+        let runner = TestRunner {
+            site_name: site.to_string(),
+            ..self.clone()
         };
+        let suite = runner.run_selected_tests(app_name, selection)?;
+        steps.push(format!("✅ Ran {} tests on {}", suite.total_tests, site));
+        Ok(suite)
+    }
 
-        // Parse test results from output
-        let test_patterns = self.get_test_patterns();
-
-        // Parse individual test results
-        for line in stdout.lines().chain(stderr.lines()) {
-            if let Some(test_result) = self.parse_test_line(line, app_name, &test_patterns) {
-                match test_result.status {
-                    TestStatus::Passed => test_suite.passed += 1,
-                    TestStatus::Failed => test_suite.failed += 1,
-                    TestStatus::Error => test_suite.errors += 1,
-                    TestStatus::Skipped => test_suite.skipped += 1,
-                    _ => {}
-                }
-                test_suite.results.push(test_result);
+    /// Waits for a setup step started through the ProcessManager and turns a
+    /// non-zero exit into an error carrying its last stderr line.
+    fn wait_for_step(&self, started: Result<String, String>, what: &str) -> Result<(), String> {
+        let id = started?;
+        let info = self
+            .process_manager
+            .wait_for_exit(&id, TEMP_SITE_STEP_TIMEOUT)
+            .ok_or_else(|| {
+                let _ = self.process_manager.stop_process(&id);
+                format!(
+                    "{} did not finish within {}s",
+                    what,
+                    TEMP_SITE_STEP_TIMEOUT.as_secs()
+                )
+            })?;
+        if info.exit_code == Some(0) {
+            return Ok(());
+        }
+        let mut error = match info.exit_code {
+            Some(code) => format!("{} failed with exit code {}", what, code),
+            None => format!("{} was killed", what),
+        };
+        if let Some(line) = info.error_lines.iter().rev().find(|l| !l.trim().is_empty()) {
+            error.push_str(&format!(": {}", line.trim()));
+        }
+        Err(error)
+    }
+
+    /// Runs `bench run-ui-tests` (Cypress) for an app. Progress is reported
+    /// under `ui_progress_key(app)` so it doesn't clobber the unit test run.
+    pub fn run_ui_tests(&self, app_name: &str) -> Result<TestSuite, String> {
+        if !self.supports(BenchVersion::supports_ui_tests) {
+            return Err("This bench version does not support run-ui-tests".to_string());
+        }
+
+        let key = ui_progress_key(app_name);
+        let mut suite = TestSuite::empty(app_name);
+        suite.name = format!("{} UI", app_name);
+
+        let mut parser =
+            CypressParser::new(Path::new(&self.bench_path).join("apps").join(app_name));
+        let (suite, output) = self.stream_tests(
+            &key,
+            suite,
+            "run-ui-tests",
+            vec![app_name.to_string(), "--headless".to_string()],
+            Box::new(move |suite, line| parser.feed(suite, line)),
+        )?;
+
+        if suite.total_tests == 0 && output.iter().any(|line| is_missing_cypress(line)) {
+            let error =
+                "Cypress is not installed. Run `bench setup requirements --dev` first".to_string();
+            if let Some(entry) = self.progress.lock().unwrap().get_mut(&key) {
+                entry.error = Some(error.clone());
             }
+            return Err(error);
         }
 
-        test_suite.total_tests =
-            test_suite.passed + test_suite.failed + test_suite.errors + test_suite.skipped;
+        Ok(suite)
+    }
 
-        // Parse execution time
-        if let Some(duration) = self.extract_duration(stdout) {
-            test_suite.duration = duration;
+    fn execute_tests(
+        &self,
+        app_name: &str,
+        selection: &TestSelection,
+    ) -> Result<TestSuite, String> {
+        let mut args = vec!["--app".to_string(), app_name.to_string()];
+        args.extend(selection.bench_args());
+        if self.supports(BenchVersion::supports_verbose_tests) {
+            args.push("--verbose".to_string());
         }
 
-        Ok(test_suite)
+        let runner = self.clone();
+        let app = app_name.to_string();
+        let parser: LineParser = Box::new(move |suite, line| {
+            if let Some(result) = runner.parse_test_line(line, &app) {
+                suite.record(result);
+            }
+            if let Some(duration) = runner.extract_duration(line) {
+                suite.duration = duration;
+            }
+            if let Some(summary) = PytestSummary::parse(line) {
+                suite.apply_pytest_summary(&summary);
+            }
+        });
+
+        self.stream_tests(
+            app_name,
+            TestSuite::empty(app_name),
+            "run-tests",
+            args,
+            parser,
+        )
+        .map(|(suite, _)| suite)
     }
 
-    fn parse_single_test_result(
+    /// Spawns a site-scoped bench test command through the ProcessManager,
+    /// feeding every output line to `parser` so `progress` stays current while
+    /// the run is in flight. Returns the final suite and the raw output.
+    fn stream_tests(
         &self,
-        test_path: &str,
-        stdout: &str,
-        stderr: &str,
-    ) -> Result<TestResult, String> {
-        let test_patterns = self.get_test_patterns();
-
-        // Try to parse from stdout first, then stderr
-        let all_output = format!("{}\n{}", stdout, stderr);
-
-        for line in all_output.lines() {
-            if let Some(result) = self.parse_test_line(line, "", &test_patterns) {
-                return Ok(TestResult {
-                    test_name: test_path.to_string(),
-                    ..result
-                });
+        key: &str,
+        suite: TestSuite,
+        command: &str,
+        args: Vec<String>,
+        parser: LineParser,
+    ) -> Result<(TestSuite, Vec<String>), String> {
+        let test_id = format!("{}::{}", key, chrono::Utc::now().timestamp());
+        self.running_tests
+            .lock()
+            .unwrap()
+            .insert(test_id.clone(), true);
+
+        self.progress.lock().unwrap().insert(
+            key.to_string(),
+            TestProgress {
+                suite,
+                started: SystemTime::now(),
+                finished: false,
+                error: None,
+            },
+        );
+
+        let progress = Arc::clone(&self.progress);
+        let progress_key = key.to_string();
+        let parser = Mutex::new(parser);
+        let listener: LineListener = Arc::new(move |line| {
+            let mut parser = parser.lock().unwrap();
+            if let Some(entry) = progress.lock().unwrap().get_mut(&progress_key) {
+                parser(&mut entry.suite, line);
             }
-        }
+        });
+
+        let process_id = self.process_manager.start_site_command_with_listener(
+            format!(
+                "{}_{}_{}",
+                command.replace('-', "_"),
+                key.replace(':', "_"),
+                chrono::Utc::now().timestamp_millis()
+            ),
+            // run-tests and run-ui-tests are only started by their namesake commands
+            &format!("frappe-{}", command),
+            &self.bench_path,
+            &self.site_name,
+            command,
+            args,
+            listener,
+        );
 
-        // If no specific result found, determine status from exit code and output
-        let status = if stderr.contains("FAILED") || stderr.contains("ERROR") {
-            TestStatus::Failed
-        } else if stdout.contains("OK") || stdout.contains("PASSED") {
-            TestStatus::Passed
-        } else {
-            TestStatus::Error
+        let finished = process_id.and_then(|id| {
+            self.process_manager
+                .wait_for_exit(&id, TEST_RUN_TIMEOUT)
+                .ok_or_else(|| {
+                    let _ = self.process_manager.stop_process(&id);
+                    format!(
+                        "Tests did not finish within {}s",
+                        TEST_RUN_TIMEOUT.as_secs()
+                    )
+                })
+        });
+        let finished = match finished {
+            Ok(info) if info.status == ProcessStatus::Killed => {
+                Err("Test run was stopped".to_string())
+            }
+            other => other,
         };
 
-        Ok(TestResult {
-            test_name: test_path.to_string(),
-            module: "".to_string(),
-            app: "".to_string(),
-            status,
-            duration: 0.0,
-            error_message: if !stderr.is_empty() {
-                Some(stderr.to_string())
-            } else {
-                None
-            },
-            traceback: None,
-            line_number: None,
-            file_path: Some(test_path.to_string()),
-        })
+        self.running_tests.lock().unwrap().remove(&test_id);
+
+        let mut progress = self.progress.lock().unwrap();
+        let entry = progress
+            .get_mut(key)
+            .ok_or("Test progress was lost".to_string())?;
+        entry.finished = true;
+
+        match finished {
+            Ok(info) => {
+                let mut output = info.output_lines;
+                output.extend(info.error_lines);
+                Ok((entry.suite.clone(), output))
+            }
+            Err(e) => {
+                entry.error = Some(e.clone());
+                Err(e)
+            }
+        }
     }
 
-    fn parse_test_line(
-        &self,
-        line: &str,
-        app_name: &str,
-        patterns: &[TestPattern],
-    ) -> Option<TestResult> {
+    fn parse_test_line(&self, line: &str, app_name: &str) -> Option<TestResult> {
         // Pattern for pytest-style output
         let pytest_re = Regex::new(
             r"^(.+)::\s*(\w+)\s*::\s*(\w+)\s*(PASSED|FAILED|ERROR|SKIPPED)(?:\s*\[(\d+\.\d+)s\])?",
@@ -344,58 +668,9 @@ impl TestRunner {
         None
     }
 
-    fn get_test_patterns(&self) -> Vec<TestPattern> {
-        vec![
-            TestPattern {
-                pattern: r"FAILED.*AssertionError".to_string(),
-                description: "Assertion failure in test".to_string(),
-                severity: DiagnosticSeverity::Error,
-            },
-            TestPattern {
-                pattern: r"ERROR.*ImportError".to_string(),
-                description: "Import error in test".to_string(),
-                severity: DiagnosticSeverity::Error,
-            },
-            TestPattern {
-                pattern: r"WARNING".to_string(),
-                description: "Test warning".to_string(),
-                severity: DiagnosticSeverity::Warning,
-            },
-            TestPattern {
-                pattern: r"DeprecationWarning".to_string(),
-                description: "Deprecated API usage".to_string(),
-                severity: DiagnosticSeverity::Warning,
-            },
-        ]
-    }
-
-    pub fn parse_traceback(&self, error_output: &str) -> Vec<String> {
-        let mut traceback = Vec::new();
-        let mut in_traceback = false;
-
-        for line in error_output.lines() {
-            if line.starts_with("Traceback") {
-                in_traceback = true;
-            }
-
-            if in_traceback {
-                traceback.push(line.to_string());
-
-                // End of traceback is usually an exception line
-                if !line.starts_with("  ")
-                    && !line.starts_with("Traceback")
-                    && !line.trim().is_empty()
-                {
-                    break;
-                }
-            }
-        }
-
-        traceback
-    }
-
     pub fn extract_diagnostics(&self, test_results: &[TestResult]) -> Vec<Diagnostic> {
         let mut diagnostics = Vec::new();
+        let mut test_names = Vec::new();
 
         for result in test_results {
             match &result.status {
@@ -403,28 +678,160 @@ impl TestRunner {
                     if let Some(error_msg) = &result.error_message {
                         let diagnostic = self.create_diagnostic_from_error(result, error_msg);
                         diagnostics.push(diagnostic);
+                        test_names.push(result.test_name.clone());
                     }
                 }
                 _ => {}
             }
         }
 
+        // Failures close together in one file usually share a cause, so each
+        // points at its neighbours through related_info
+        let mut by_file: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, diagnostic) in diagnostics.iter().enumerate() {
+            by_file
+                .entry(diagnostic.file_path.clone())
+                .or_default()
+                .push(index);
+        }
+        for indices in by_file.values().filter(|indices| indices.len() > 1) {
+            for &index in indices {
+                let line = diagnostics[index].line_number;
+                let related: Vec<DiagnosticRelatedInfo> = indices
+                    .iter()
+                    .filter(|&&other| other != index)
+                    .filter(|&&other| {
+                        diagnostics[other].line_number.abs_diff(line) <= RELATED_LINE_WINDOW
+                    })
+                    .map(|&other| DiagnosticRelatedInfo {
+                        file_path: diagnostics[other].file_path.clone(),
+                        line_number: diagnostics[other].line_number,
+                        message: format!("Also failing: {}", test_names[other]),
+                    })
+                    .collect();
+                diagnostics[index].related_info.extend(related);
+            }
+        }
+
         diagnostics
     }
 
+    /// Counts of (errors, warnings, infos, hints).
+    pub fn summarize_diagnostics(&self, diags: &[Diagnostic]) -> (usize, usize, usize, usize) {
+        diags
+            .iter()
+            .fold((0, 0, 0, 0), |(e, w, i, h), diag| match diag.severity {
+                DiagnosticSeverity::Error => (e + 1, w, i, h),
+                DiagnosticSeverity::Warning => (e, w + 1, i, h),
+                DiagnosticSeverity::Info => (e, w, i + 1, h),
+                DiagnosticSeverity::Hint => (e, w, i, h + 1),
+            })
+    }
+
+    /// "3 errors, 1 warning", skipping severities with no diagnostics.
+    pub fn format_diagnostic_counts(&self, diags: &[Diagnostic]) -> String {
+        let (errors, warnings, infos, hints) = self.summarize_diagnostics(diags);
+        let parts: Vec<String> = [
+            (errors, "error"),
+            (warnings, "warning"),
+            (infos, "info"),
+            (hints, "hint"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{} {}{}", count, label, if *count == 1 { "" } else { "s" }))
+        .collect();
+
+        if parts.is_empty() {
+            "no diagnostics".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// Serializes diagnostics as an array of LSP `Diagnostic` objects, each tagged
+    /// with the `uri` of the file it belongs to.
+    pub fn diagnostics_to_json(&self, diags: &[Diagnostic]) -> String {
+        let entries: Vec<serde_json::Value> = diags
+            .iter()
+            .map(|diag| {
+                let related: Vec<serde_json::Value> = diag
+                    .related_info
+                    .iter()
+                    .map(|info| {
+                        serde_json::json!({
+                            "location": {
+                                "uri": Self::file_uri(&info.file_path),
+                                "range": Self::lsp_range(info.line_number, None),
+                            },
+                            "message": info.message,
+                        })
+                    })
+                    .collect();
+
+                serde_json::json!({
+                    "uri": Self::file_uri(&diag.file_path),
+                    "range": Self::lsp_range(diag.line_number, diag.column),
+                    "severity": diag.severity.lsp_code(),
+                    "code": diag.code,
+                    "source": diag.source,
+                    "message": diag.message,
+                    "relatedInformation": related,
+                })
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&entries).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Writes `.latte_diagnostics.json` into the bench directory.
+    pub fn write_diagnostics_file(&self, diags: &[Diagnostic]) -> Result<PathBuf, String> {
+        let path = Path::new(&self.bench_path).join(".latte_diagnostics.json");
+        fs::write(&path, self.diagnostics_to_json(diags))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    // Our line/column numbers are 1-based; LSP positions are 0-based. Without a
+    // column the range spans the whole line.
+    fn lsp_range(line_number: u32, column: Option<u32>) -> serde_json::Value {
+        let line = line_number.saturating_sub(1);
+        match column {
+            Some(column) => {
+                let character = column.saturating_sub(1);
+                serde_json::json!({
+                    "start": { "line": line, "character": character },
+                    "end": { "line": line, "character": character + 1 },
+                })
+            }
+            None => serde_json::json!({
+                "start": { "line": line, "character": 0 },
+                "end": { "line": line + 1, "character": 0 },
+            }),
+        }
+    }
+
+    fn file_uri(file_path: &str) -> String {
+        if file_path.starts_with("file://") {
+            file_path.to_string()
+        } else {
+            format!("file://{}", file_path)
+        }
+    }
+
     fn create_diagnostic_from_error(
         &self,
         test_result: &TestResult,
         error_message: &str,
     ) -> Diagnostic {
-        let (line_number, file_path) = self.extract_error_location(error_message);
+        let (line_number, file_path, column) = self.extract_error_location(error_message);
 
         Diagnostic {
             file_path: file_path
                 .unwrap_or_else(|| test_result.file_path.clone().unwrap_or_default()),
-            line_number: line_number.unwrap_or(1),
-            column: None,
-            message: self.clean_error_message(error_message),
+            line_number: line_number.or(test_result.line_number).unwrap_or(1),
+            column,
+            message: Self::clean_error_message(error_message),
             severity: if test_result.status == TestStatus::Error {
                 DiagnosticSeverity::Error
             } else {
@@ -439,12 +846,20 @@ impl TestRunner {
         }
     }
 
-    fn extract_error_location(&self, error_message: &str) -> (Option<u32>, Option<String>) {
+    /// Line, file and (when the traceback has Python 3.11+ `^^^^` markers)
+    /// 1-based column of the first frame in a traceback.
+    fn extract_error_location(
+        &self,
+        error_message: &str,
+    ) -> (Option<u32>, Option<String>, Option<u32>) {
         // Pattern to match file path and line number from traceback
-        let location_re = Regex::new(r#"File "([^"]+)", line (\d+)"#).ok();
+        let Ok(location_re) = Regex::new(r#"File "([^"]+)", line (\d+)"#) else {
+            return (None, None, None);
+        };
 
-        if let Some(re) = location_re {
-            if let Some(captures) = re.captures(error_message) {
+        let lines: Vec<&str> = error_message.lines().collect();
+        for (index, line) in lines.iter().enumerate() {
+            if let Some(captures) = location_re.captures(line) {
                 let file_path = captures.get(1).unwrap().as_str().to_string();
                 let line_number = captures
                     .get(2)
@@ -452,14 +867,20 @@ impl TestRunner {
                     .as_str()
                     .parse::<u32>()
                     .unwrap_or(0);
-                return (Some(line_number), Some(file_path));
+                let column = match (lines.get(index + 1), lines.get(index + 2)) {
+                    (Some(code), Some(markers)) => {
+                        caret_column(&file_path, line_number, code, markers)
+                    }
+                    _ => None,
+                };
+                return (Some(line_number), Some(file_path), column);
             }
         }
 
-        (None, None)
+        (None, None, None)
     }
 
-    fn clean_error_message(&self, error_message: &str) -> String {
+    fn clean_error_message(error_message: &str) -> String {
         // Extract just the relevant error message, not the full traceback
         let lines: Vec<&str> = error_message.lines().collect();
 
@@ -479,35 +900,106 @@ impl TestRunner {
         }
     }
 
-    pub fn stop_running_tests(&self) -> Result<(), String> {
-        // Implementation to stop running test processes
-        let running = self.running_tests.lock().unwrap();
-
-        if running.is_empty() {
-            return Ok(());
+    pub fn format_test_report(&self, suite: &TestSuite, fmt: TestReportFormat) -> String {
+        match fmt {
+            TestReportFormat::Human => self.format_test_summary(suite),
+            TestReportFormat::Json => serde_json::to_string_pretty(suite).unwrap_or_default(),
+            TestReportFormat::Junit => self.format_junit(suite),
+            TestReportFormat::Markdown => self.format_markdown(suite),
         }
+    }
 
-        // In a real implementation, you would track process IDs and kill them
-        // For now, we'll just clear the running tests map
-        drop(running);
-
-        let mut running = self.running_tests.lock().unwrap();
-        running.clear();
+    fn format_junit(&self, suite: &TestSuite) -> String {
+        let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+        xml.push_str(&format!(
+            "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(&suite.app),
+            suite.total_tests,
+            suite.failed,
+            suite.errors,
+            suite.skipped,
+            suite.duration
+        ));
+
+        for result in &suite.results {
+            xml.push_str(&format!(
+                "  <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+                xml_escape(&result.module),
+                xml_escape(&result.test_name),
+                result.duration
+            ));
+            let message = result
+                .error_message
+                .as_deref()
+                .map(|msg| xml_escape(&Self::clean_error_message(msg)))
+                .unwrap_or_default();
+            let body = result
+                .traceback
+                .as_ref()
+                .map(|lines| xml_escape(&lines.join("\n")))
+                .unwrap_or_default();
+            match result.status {
+                TestStatus::Failed => xml.push_str(&format!(
+                    ">\n    <failure message=\"{}\">{}</failure>\n  </testcase>\n",
+                    message, body
+                )),
+                TestStatus::Error => xml.push_str(&format!(
+                    ">\n    <error message=\"{}\">{}</error>\n  </testcase>\n",
+                    message, body
+                )),
+                TestStatus::Skipped => xml.push_str(">\n    <skipped/>\n  </testcase>\n"),
+                _ => xml.push_str("/>\n"),
+            }
+        }
 
-        Ok(())
+        xml.push_str("</testsuite>\n");
+        xml
     }
 
-    pub fn get_running_tests(&self) -> Vec<String> {
-        let running = self.running_tests.lock().unwrap();
-        running.keys().cloned().collect()
-    }
+    fn format_markdown(&self, suite: &TestSuite) -> String {
+        let mut md = format!(
+            "### Test results for `{}`\n\n**{}** passed, **{}** failed, **{}** errors, **{}** skipped in {:.2}s\n\n",
+            suite.app, suite.passed, suite.failed, suite.errors, suite.skipped, suite.duration
+        );
+        md.push_str("| Test | Module | Status | Duration | Message |\n");
+        md.push_str("| --- | --- | --- | --- | --- |\n");
+
+        for result in &suite.results {
+            let status = match result.status {
+                TestStatus::Passed => "✅ Passed",
+                TestStatus::Failed => "❌ Failed",
+                TestStatus::Error => "🔥 Error",
+                TestStatus::Skipped => "⏭️ Skipped",
+                TestStatus::Running => "Running",
+                TestStatus::Pending => "Pending",
+            };
+            let message = result
+                .error_message
+                .as_deref()
+                .map(Self::clean_error_message)
+                .unwrap_or_default();
+            md.push_str(&format!(
+                "| `{}` | {} | {} | {:.3}s | {} |\n",
+                result.test_name,
+                result.module,
+                status,
+                result.duration,
+                message.replace('|', "\\|").replace('\n', " ")
+            ));
+        }
 
-    pub fn is_test_running(&self, test_id: &str) -> bool {
-        let running = self.running_tests.lock().unwrap();
-        running.contains_key(test_id)
+        md
     }
 
     pub fn format_test_summary(&self, test_suite: &TestSuite) -> String {
+        self.process_manager
+            .output_style()
+            .apply(&Self::summarize(test_suite))
+    }
+
+    /// The human-readable summary of `test_suite`, before any output style is
+    /// applied.
+    pub fn summarize(test_suite: &TestSuite) -> String {
         let mut summary = format!("📊 Test Results for {}\n", test_suite.app);
         summary.push_str(&format!("⏱️  Duration: {:.2}s\n\n", test_suite.duration));
 
@@ -531,7 +1023,7 @@ impl TestRunner {
                     ));
 
                     if let Some(error_msg) = &result.error_message {
-                        let clean_msg = self.clean_error_message(error_msg);
+                        let clean_msg = Self::clean_error_message(error_msg);
                         summary.push_str(&format!("    {}\n", clean_msg));
                     }
                 }
@@ -542,17 +1034,305 @@ impl TestRunner {
     }
 }
 
-impl ToString for TestStatus {
-    fn to_string(&self) -> String {
-        match self {
-            TestStatus::Passed => "passed".to_string(),
-            TestStatus::Failed => "failed".to_string(),
-            TestStatus::Error => "error".to_string(),
-            TestStatus::Skipped => "skipped".to_string(),
-            TestStatus::Running => "running".to_string(),
-            TestStatus::Pending => "pending".to_string(),
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Column of a 3.11-style `^^^^`/`~~~~` marker line under a traceback code
+/// line. Python strips the source line's indentation before printing it, so
+/// the source file is read to add it back; without the file there's no column.
+fn caret_column(file_path: &str, line_number: u32, code: &str, markers: &str) -> Option<u32> {
+    let trimmed_markers = markers.trim();
+    if trimmed_markers.is_empty() || !trimmed_markers.chars().all(|c| c == '^' || c == '~') {
+        return None;
+    }
+
+    let marker_offset = markers.find(['^', '~'])?;
+    let code_indent = code.len() - code.trim_start().len();
+    let offset_in_code = marker_offset.checked_sub(code_indent)?;
+
+    let content = fs::read_to_string(file_path).ok()?;
+    let source_line = content.lines().nth(line_number.checked_sub(1)? as usize)?;
+    if source_line.trim() != code.trim() {
+        return None;
+    }
+    let source_indent = source_line.len() - source_line.trim_start().len();
+
+    u32::try_from(source_indent + offset_in_code + 1).ok()
+}
+
+pub fn ui_progress_key(app_name: &str) -> String {
+    format!("{}:ui", app_name)
+}
+
+fn is_missing_cypress(line: &str) -> bool {
+    let line = line.to_lowercase();
+    line.contains("cypress")
+        && (line.contains("not installed")
+            || line.contains("not found")
+            || line.contains("no such file"))
+}
+
+/// Incremental parser for Cypress' default `spec` reporter. Tests are listed
+/// as `✓ name` / `1) name` / `- name` per spec, followed by numbered failure
+/// details whose stack traces point into the spec file.
+struct CypressParser {
+    app_dir: PathBuf,
+    spec: Option<String>,
+    failures: Vec<usize>,
+    current_failure: Option<usize>,
+    in_details: bool,
+}
+
+impl CypressParser {
+    fn new(app_dir: PathBuf) -> Self {
+        Self {
+            app_dir,
+            spec: None,
+            failures: Vec::new(),
+            current_failure: None,
+            in_details: false,
+        }
+    }
+
+    fn spec_path(&self) -> Option<String> {
+        self.spec.as_ref().map(|spec| {
+            let path = if spec.contains('/') {
+                self.app_dir.join(spec)
+            } else {
+                self.app_dir.join("cypress").join("integration").join(spec)
+            };
+            path.display().to_string()
+        })
+    }
+
+    fn feed(&mut self, suite: &mut TestSuite, raw_line: &str) {
+        let ansi_re = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+        let line = ansi_re.replace_all(raw_line, "");
+        let line = line.as_ref();
+
+        let running_re = Regex::new(r"Running:\s+(\S+)").unwrap();
+        let failing_re = Regex::new(r"^\s*\d+ failing").unwrap();
+        let passed_re = Regex::new(r"^\s*[✓✔]\s+(.+?)(?:\s+\((\d+)ms\))?\s*$").unwrap();
+        let pending_re = Regex::new(r"^\s*-\s+(.+?)\s*$").unwrap();
+        let numbered_re = Regex::new(r"^\s*(\d+)\)\s+(.+?)\s*$").unwrap();
+        let error_re = Regex::new(r"^\s*(\w*Error:.*)$").unwrap();
+        let location_re =
+            Regex::new(r"((?:[\w.-]+/)*cypress/[\w./-]+\.[jt]s):(\d+):(\d+)").unwrap();
+
+        if let Some(captures) = running_re.captures(line) {
+            self.spec = Some(captures[1].to_string());
+            self.failures.clear();
+            self.current_failure = None;
+            self.in_details = false;
+            return;
+        }
+        if failing_re.is_match(line) {
+            self.in_details = true;
+            return;
+        }
+
+        if self.in_details {
+            if let Some(captures) = numbered_re.captures(line) {
+                self.current_failure = captures[1]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| self.failures.get(n.wrapping_sub(1)).copied());
+                return;
+            }
+
+            let Some(result) = self
+                .current_failure
+                .and_then(|index| suite.results.get_mut(index))
+            else {
+                return;
+            };
+            if result.error_message.is_none() {
+                if let Some(captures) = error_re.captures(line) {
+                    result.error_message = Some(captures[1].trim().to_string());
+                }
+            }
+            if result.line_number.is_none() {
+                if let Some(captures) = location_re.captures(line) {
+                    let relative = captures[1].trim_start_matches("./");
+                    result.file_path = Some(self.app_dir.join(relative).display().to_string());
+                    result.line_number = captures[2].parse().ok();
+                }
+            }
+            return;
+        }
+
+        let (test_name, status, duration) = if let Some(captures) = passed_re.captures(line) {
+            let duration = captures
+                .get(2)
+                .and_then(|m| m.as_str().parse::<f64>().ok())
+                .map(|ms| ms / 1000.0)
+                .unwrap_or(0.0);
+            (captures[1].to_string(), TestStatus::Passed, duration)
+        } else if let Some(captures) = numbered_re.captures(line) {
+            (captures[2].to_string(), TestStatus::Failed, 0.0)
+        } else if let Some(captures) = pending_re.captures(line) {
+            (captures[1].to_string(), TestStatus::Skipped, 0.0)
+        } else {
+            return;
+        };
+
+        // Only lines inside a spec are test results
+        let Some(spec) = self.spec.clone() else {
+            return;
+        };
+        if status == TestStatus::Failed {
+            self.failures.push(suite.results.len());
+        }
+        suite.duration += duration;
+        suite.record(TestResult {
+            test_name,
+            module: spec,
+            app: suite.app.clone(),
+            status,
+            duration,
+            error_message: None,
+            traceback: None,
+            line_number: None,
+            file_path: self.spec_path(),
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchState {
+    pub app: String,
+    pub runs: u32,
+    pub is_running: bool,
+    pub last_run: Option<SystemTime>,
+    pub latest: Option<TestSuite>,
+    pub last_error: Option<String>,
+}
+
+pub struct TestWatcher {
+    stop_flag: Arc<AtomicBool>,
+    state: Arc<Mutex<WatchState>>,
+}
+
+impl TestWatcher {
+    pub fn start(runner: TestRunner, app_name: &str, watch_dir: PathBuf) -> Self {
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let state = Arc::new(Mutex::new(WatchState {
+            app: app_name.to_string(),
+            runs: 0,
+            is_running: false,
+            last_run: None,
+            latest: None,
+            last_error: None,
+        }));
+
+        let thread_stop = Arc::clone(&stop_flag);
+        let thread_state = Arc::clone(&state);
+        let app = app_name.to_string();
+
+        thread::spawn(move || {
+            let mut snapshot = collect_py_mtimes(&watch_dir);
+            let mut pending_since: Option<SystemTime> = None;
+
+            while !thread_stop.load(Ordering::SeqCst) {
+                thread::sleep(WATCH_POLL_INTERVAL);
+
+                let current = collect_py_mtimes(&watch_dir);
+                if current != snapshot {
+                    snapshot = current;
+                    // Every new change restarts the debounce window
+                    pending_since = Some(SystemTime::now());
+                    continue;
+                }
+
+                let settled = pending_since
+                    .and_then(|since| since.elapsed().ok())
+                    .map(|elapsed| elapsed >= WATCH_DEBOUNCE)
+                    .unwrap_or(false);
+                if !settled || thread_stop.load(Ordering::SeqCst) {
+                    continue;
+                }
+                pending_since = None;
+
+                thread_state.lock().unwrap().is_running = true;
+                let result = runner.run_app_tests(&app);
+
+                let mut state = thread_state.lock().unwrap();
+                state.is_running = false;
+                state.runs += 1;
+                state.last_run = Some(SystemTime::now());
+                match result {
+                    Ok(suite) => {
+                        state.latest = Some(suite);
+                        state.last_error = None;
+                    }
+                    Err(e) => state.last_error = Some(e),
+                }
+            }
+        });
+
+        Self { stop_flag, state }
+    }
+
+    pub fn stop(&self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+    }
+
+    pub fn state(&self) -> WatchState {
+        self.state.lock().unwrap().clone()
+    }
+}
+
+/// Modification times of every `.py` file under `dir`, used to detect saves.
+pub fn collect_py_mtimes(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+    let mut mtimes = HashMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+    let mut visited = HashSet::new();
+
+    while let Some(current) = pending.pop() {
+        if !crate::frappe_utils::first_visit(&mut visited, &current) {
+            continue;
+        }
+        let entries = match fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if path.is_dir() {
+                if name.starts_with('.') || name == "__pycache__" || name == "node_modules" {
+                    continue;
+                }
+                pending.push(path);
+            } else if name.ends_with(".py") {
+                if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                    mtimes.insert(path, modified);
+                }
+            }
         }
     }
+
+    mtimes
+}
+
+impl fmt::Display for TestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            TestStatus::Passed => "passed",
+            TestStatus::Failed => "failed",
+            TestStatus::Error => "error",
+            TestStatus::Skipped => "skipped",
+            TestStatus::Running => "running",
+            TestStatus::Pending => "pending",
+        };
+        f.write_str(label)
+    }
 }
 
 #[cfg(test)]
@@ -563,9 +1343,8 @@ mod tests {
     fn test_parse_pytest_output() {
         let runner = TestRunner::new("/path/to/bench".to_string(), "test.local".to_string());
         let line = "test_app/test_doctype.py::TestDocType::test_create PASSED [0.123s]";
-        let patterns = runner.get_test_patterns();
 
-        let result = runner.parse_test_line(line, "test_app", &patterns);
+        let result = runner.parse_test_line(line, "test_app");
         assert!(result.is_some());
 
         let result = result.unwrap();
@@ -574,6 +1353,35 @@ mod tests {
         assert_eq!(result.test_name, "TestDocType::test_create");
     }
 
+    #[test]
+    fn test_pytest_summary_overrides_counts() {
+        let summary =
+            PytestSummary::parse("===== 5 passed, 2 failed, 1 skipped, 3 warnings in 3.21s =====")
+                .unwrap();
+        assert_eq!(
+            summary,
+            PytestSummary {
+                passed: 5,
+                failed: 2,
+                errors: 0,
+                skipped: 1,
+                duration: 3.21
+            }
+        );
+        assert_eq!(PytestSummary::parse("1 error in 0.50s").unwrap().errors, 1);
+        assert!(PytestSummary::parse("Ran 15 tests in 2.456s").is_none());
+        assert!(PytestSummary::parse("Loaded 3 fixtures in 1.0s").is_none());
+
+        let runner = TestRunner::new("/path/to/bench".to_string(), "test.local".to_string());
+        let mut suite = TestSuite::empty("shop");
+        let line = "shop/tests/test_cart.py::TestCart::test_add FAILED";
+        suite.record(runner.parse_test_line(line, "shop").unwrap());
+        suite.apply_pytest_summary(&summary);
+        assert_eq!((suite.total_tests, suite.passed, suite.failed), (8, 5, 2));
+        assert_eq!(suite.duration, 3.21);
+        assert_eq!(suite.results.len(), 1);
+    }
+
     #[test]
     fn test_extract_duration() {
         let runner = TestRunner::new("/path/to/bench".to_string(), "test.local".to_string());
@@ -583,12 +1391,407 @@ mod tests {
         assert_eq!(duration, Some(2.456));
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_clean_error_message() {
+    fn test_run_app_tests_streams_progress() {
+        use crate::settings::LatteSettings;
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("latte_progress_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let fake_bench = root.join("bench");
+        fs::write(
+            &fake_bench,
+            "#!/bin/sh\n\
+             echo \"$@\" > \"$(dirname \"$0\")/args\"\n\
+             echo 'test_create (tests.TestItem) ... ok'\n\
+             echo 'test_update (tests.TestItem) ... FAIL' >&2\n\
+             echo 'test_delete (tests.TestItem) ... ok'\n\
+             echo 'Ran 3 tests in 0.250s'\n",
+        )
+        .unwrap();
+        fs::set_permissions(&fake_bench, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = Arc::new(ProcessManager::new());
+        manager.configure(&LatteSettings {
+            bench_executable: fake_bench.display().to_string(),
+            ..Default::default()
+        });
+        let runner = TestRunner::new(root.display().to_string(), "test.local".to_string())
+            .with_process_manager(manager);
+
+        let selection = TestSelection {
+            failfast: true,
+            module: Some("shop.tests.test_item".to_string()),
+            test: None,
+        };
+        let suite = runner.run_selected_tests("shop", &selection).unwrap();
+        assert_eq!((suite.passed, suite.failed, suite.total_tests), (2, 1, 3));
+        assert_eq!(
+            fs::read_to_string(root.join("args")).unwrap().trim(),
+            "--site test.local run-tests --app shop --module shop.tests.test_item --failfast --verbose"
+        );
+        assert_eq!(suite.duration, 0.25);
+
+        let progress = runner.progress.lock().unwrap()["shop"].clone();
+        assert!(progress.finished);
+        assert_eq!(progress.suite.total_tests, 3);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_tests_on_temp_site() {
+        use crate::settings::LatteSettings;
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("latte_temp_site_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sites")).unwrap();
+        let fake_bench = root.join("bench");
+        fs::write(
+            &fake_bench,
+            "#!/bin/sh\n\
+             dir=\"$(dirname \"$0\")\"\n\
+             echo \"$@\" >> \"$dir/args\"\n\
+             case \"$1\" in\n\
+               new-site) mkdir -p \"$dir/sites/$2\" ;;\n\
+               drop-site) rm -rf \"$dir/sites/$2\" ;;\n\
+             esac\n\
+             case \"$3\" in\n\
+               install-app) [ \"$4\" = broken ] && { echo 'App broken not found' >&2; exit 1; } ;;\n\
+               run-tests) echo 'test_create (tests.TestItem) ... ok'; echo 'Ran 1 test in 0.100s' ;;\n\
+             esac\n\
+             exit 0\n",
+        )
+        .unwrap();
+        fs::set_permissions(&fake_bench, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = Arc::new(ProcessManager::new());
+        manager.configure(&LatteSettings {
+            bench_executable: fake_bench.display().to_string(),
+            ..Default::default()
+        });
+        let runner = TestRunner::new(root.display().to_string(), "dev.local".to_string())
+            .with_process_manager(manager);
+
+        let mut steps = Vec::new();
+        let suite = runner
+            .run_tests_on_temp_site("shop", &TestSelection::default(), &mut steps)
+            .unwrap();
+        assert_eq!((suite.passed, suite.total_tests), (1, 1));
+        let args = fs::read_to_string(root.join("args")).unwrap();
+        let lines: Vec<&str> = args.lines().collect();
+        let site = lines[0].split_whitespace().nth(1).unwrap().to_string();
+        assert!(site.starts_with("test_"));
+        assert_eq!(
+            lines,
+            vec![
+                format!("new-site {} --force --admin-password admin", site),
+                format!("--site {} install-app shop", site),
+                format!("--site {} run-tests --app shop --verbose", site),
+                format!("drop-site {} --force --no-backup", site),
+            ]
+        );
+        assert_eq!(steps.len(), 4);
+        assert!(steps[3].starts_with("🧹 Dropped site test_"));
+
+        // A failed install still tears the site down
+        fs::remove_file(root.join("args")).unwrap();
+        let mut steps = Vec::new();
+        let error = runner
+            .run_tests_on_temp_site("broken", &TestSelection::default(), &mut steps)
+            .unwrap_err();
+        assert!(error.contains("exit code 1: App broken not found"));
+        let args = fs::read_to_string(root.join("args")).unwrap();
+        assert!(!args.contains("run-tests"));
+        assert!(args.lines().last().unwrap().starts_with("drop-site test_"));
+        assert!(steps.last().unwrap().starts_with("🧹 Dropped site"));
+        assert_eq!(fs::read_dir(root.join("sites")).unwrap().count(), 0);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_cypress_output() {
+        let runner = TestRunner::new("/bench".to_string(), "test.local".to_string());
+        let mut parser = CypressParser::new(PathBuf::from("/bench/apps/shop"));
+        let mut suite = TestSuite::empty("shop");
+        let output = r#"
+  Running:  shop_item.js                                                    (1 of 1)
+
+  Shop Item
+    ✓ creates an item (1500ms)
+    1) validates the price
+    - prints a label
+
+  1 passing (3s)
+  1 pending
+  1 failing
+
+  1) Shop Item
+       validates the price:
+     AssertionError: expected 0 to equal 10
+      at Context.eval (webpack:///./cypress/integration/shop_item.js:25:10)
+"#;
+        for line in output.lines() {
+            parser.feed(&mut suite, line);
+        }
+
+        assert_eq!((suite.passed, suite.failed, suite.skipped), (1, 1, 1));
+        assert_eq!(suite.duration, 1.5);
+
+        let failure = &suite.results[1];
+        assert_eq!(failure.test_name, "validates the price");
+        assert_eq!(
+            failure.error_message.as_deref(),
+            Some("AssertionError: expected 0 to equal 10")
+        );
+
+        let diagnostics = runner.extract_diagnostics(&suite.results);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].file_path,
+            "/bench/apps/shop/cypress/integration/shop_item.js"
+        );
+        assert_eq!(diagnostics[0].line_number, 25);
+    }
+
+    #[test]
+    fn test_nearby_failures_are_related() {
+        let failure = |name: &str, line: u32| TestResult {
+            test_name: name.to_string(),
+            module: "shop.tests.test_cart".to_string(),
+            app: "shop".to_string(),
+            status: TestStatus::Failed,
+            duration: 0.0,
+            error_message: Some(format!(
+                "File \"/bench/apps/shop/shop/tests/test_cart.py\", line {}, in {}\nAssertionError",
+                line, name
+            )),
+            traceback: None,
+            line_number: None,
+            file_path: None,
+        };
+        let results = vec![
+            failure("test_add", 12),
+            failure("test_remove", 30),
+            failure("test_checkout", 400),
+        ];
+
+        let runner = TestRunner::new("/bench".to_string(), "test.local".to_string());
+        let diagnostics = runner.extract_diagnostics(&results);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].related_info.len(), 1);
+        assert_eq!(diagnostics[0].related_info[0].line_number, 30);
+        assert_eq!(
+            diagnostics[0].related_info[0].message,
+            "Also failing: test_remove"
+        );
+        assert_eq!(diagnostics[1].related_info[0].line_number, 12);
+        assert!(diagnostics[2].related_info.is_empty());
+    }
+
+    #[test]
+    fn test_diagnostic_column_from_traceback_markers() {
+        let root = std::env::temp_dir().join(format!("latte_columns_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let test_file = root.join("test_invoice.py");
+        fs::write(
+            &test_file,
+            "class TestInvoice:\n    def test_total(self):\n        total = compute(items) / count\n",
+        )
+        .unwrap();
+
+        let traceback = format!(
+            "Traceback (most recent call last):\n  \
+             File \"{}\", line 3, in test_total\n    \
+             total = compute(items) / count\n            \
+             ~~~~~~~~~~~~~~^~~~~~~\n\
+             ZeroDivisionError: division by zero",
+            test_file.display()
+        );
+
+        let runner = TestRunner::new("/bench".to_string(), "test.local".to_string());
+        let (line, file, column) = runner.extract_error_location(&traceback);
+        assert_eq!(line, Some(3));
+        assert_eq!(file, Some(test_file.display().to_string()));
+        // `compute` starts at column 17 in the source (8 spaces + "total = ")
+        assert_eq!(column, Some(17));
+
+        // Older tracebacks without markers still resolve the line
+        let plain = format!(
+            "  File \"{}\", line 3, in test_total\n    total = compute(items) / count\nZeroDivisionError",
+            test_file.display()
+        );
+        assert_eq!(runner.extract_error_location(&plain).2, None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_collect_py_mtimes_skips_caches() {
+        let root = std::env::temp_dir().join(format!("latte_watch_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("module").join("__pycache__")).unwrap();
+        fs::write(root.join("module").join("api.py"), "x = 1").unwrap();
+        fs::write(root.join("module").join("api.js"), "").unwrap();
+        fs::write(root.join("module").join("__pycache__").join("api.py"), "").unwrap();
+
+        let mtimes = collect_py_mtimes(&root);
+        assert_eq!(mtimes.len(), 1);
+        assert!(mtimes.contains_key(&root.join("module").join("api.py")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_diagnostics_to_json() {
+        let runner = TestRunner::new("/path/to/bench".to_string(), "test.local".to_string());
+        let diags = vec![
+            Diagnostic {
+                file_path: "/apps/app/test_a.py".to_string(),
+                line_number: 10,
+                column: Some(5),
+                message: "AssertionError".to_string(),
+                severity: DiagnosticSeverity::Error,
+                code: Some("test_failed".to_string()),
+                source: "frappe_test_runner".to_string(),
+                related_info: vec![DiagnosticRelatedInfo {
+                    file_path: "/apps/app/helpers.py".to_string(),
+                    line_number: 3,
+                    message: "called from here".to_string(),
+                }],
+            },
+            Diagnostic {
+                file_path: "/apps/app/test_b.py".to_string(),
+                line_number: 1,
+                column: None,
+                message: "unused".to_string(),
+                severity: DiagnosticSeverity::Hint,
+                code: None,
+                source: "frappe_test_runner".to_string(),
+                related_info: vec![],
+            },
+        ];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&runner.diagnostics_to_json(&diags)).unwrap();
+        let first = &json[0];
+        assert_eq!(first["uri"], "file:///apps/app/test_a.py");
+        assert_eq!(first["severity"], 1);
+        assert_eq!(first["range"]["start"]["line"], 9);
+        assert_eq!(first["range"]["start"]["character"], 4);
+        assert_eq!(
+            first["relatedInformation"][0]["location"]["range"]["start"]["line"],
+            2
+        );
+        assert_eq!(json[1]["severity"], 4);
+        assert_eq!(json[1]["range"]["end"]["line"], 1);
+    }
+
+    fn sample_suite() -> TestSuite {
+        let result = |name: &str, status: TestStatus, error: Option<&str>| TestResult {
+            test_name: name.to_string(),
+            module: "shop.tests".to_string(),
+            app: "shop".to_string(),
+            status,
+            duration: 0.5,
+            error_message: error.map(|e| e.to_string()),
+            traceback: None,
+            line_number: None,
+            file_path: None,
+        };
+        TestSuite {
+            name: "shop".to_string(),
+            app: "shop".to_string(),
+            total_tests: 2,
+            passed: 1,
+            failed: 1,
+            errors: 0,
+            skipped: 0,
+            duration: 1.0,
+            results: vec![
+                result("test_create", TestStatus::Passed, None),
+                result(
+                    "test_price",
+                    TestStatus::Failed,
+                    Some("AssertionError: 1 < 2 | \"x\""),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_format_test_report() {
+        let runner = TestRunner::new("/path/to/bench".to_string(), "test.local".to_string());
+        let suite = sample_suite();
+
+        assert_eq!(
+            TestReportFormat::parse("JUnit").unwrap(),
+            TestReportFormat::Junit
+        );
+        assert!(TestReportFormat::parse("html").is_err());
+
+        let human = runner.format_test_report(&suite, TestReportFormat::Human);
+        assert_eq!(human, runner.format_test_summary(&suite));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&runner.format_test_report(&suite, TestReportFormat::Json))
+                .unwrap();
+        assert_eq!(json["failed"], 1);
+        assert_eq!(json["results"][1]["test_name"], "test_price");
+
+        let junit = runner.format_test_report(&suite, TestReportFormat::Junit);
+        assert!(junit.starts_with("<?xml"));
+        assert!(junit.contains(r#"<testsuite name="shop" tests="2" failures="1""#));
+        assert!(
+            junit.contains(r#"<testcase classname="shop.tests" name="test_create" time="0.500"/>"#)
+        );
+        assert!(junit.contains(r#"<failure message="AssertionError: 1 &lt; 2 | &quot;x&quot;">"#));
+
+        let markdown = runner.format_test_report(&suite, TestReportFormat::Markdown);
+        let rows: Vec<&str> = markdown.lines().filter(|l| l.starts_with('|')).collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows[3].contains(r#"❌ Failed | 0.500s | AssertionError: 1 < 2 \| "x" |"#));
+    }
+
+    #[test]
+    fn test_summarize_diagnostics() {
         let runner = TestRunner::new("/path/to/bench".to_string(), "test.local".to_string());
+        let diag = |severity: DiagnosticSeverity| Diagnostic {
+            file_path: "/apps/app/test_a.py".to_string(),
+            line_number: 1,
+            column: None,
+            message: "message".to_string(),
+            severity,
+            code: None,
+            source: "frappe_test_runner".to_string(),
+            related_info: vec![],
+        };
+        let diags = vec![
+            diag(DiagnosticSeverity::Error),
+            diag(DiagnosticSeverity::Warning),
+            diag(DiagnosticSeverity::Error),
+            diag(DiagnosticSeverity::Error),
+        ];
+
+        assert_eq!(runner.summarize_diagnostics(&diags), (3, 1, 0, 0));
+        assert_eq!(
+            runner.format_diagnostic_counts(&diags),
+            "3 errors, 1 warning"
+        );
+        assert_eq!(runner.format_diagnostic_counts(&[]), "no diagnostics");
+    }
+
+    #[test]
+    fn test_clean_error_message() {
         let error = "Traceback (most recent call last):\n  File \"test.py\", line 10\n    assert False\nAssertionError: Test failed";
 
-        let clean = runner.clean_error_message(error);
+        let clean = TestRunner::clean_error_message(error);
         assert_eq!(clean, "AssertionError: Test failed");
     }
 }